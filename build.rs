@@ -0,0 +1,49 @@
+//! Converts the large static data files to bincode, which loads much faster
+//! than parsing the JSON at startup.
+
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, env, fs, path::Path};
+
+// Field order and types must match `eve_data::SystemData` and
+// `eve_data::WormholeInfo`, since bincode reads fields by position.
+
+#[derive(Deserialize, Serialize)]
+struct SystemData {
+    security: f32,
+    class: Option<u8>,
+    effect: Option<String>,
+    statics: Vec<String>,
+    #[serde(default)]
+    region: Option<String>,
+    #[serde(default)]
+    constellation: Option<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct WormholeInfo {
+    life: String,
+    from: Vec<String>,
+    #[serde(rename = "leadsTo")]
+    leads_to: String,
+    mass: u64,
+    jump: u64,
+}
+
+fn convert<T: for<'de> Deserialize<'de> + Serialize>(name: &str) {
+    let source = format!("static/{name}.json");
+    println!("cargo:rerun-if-changed={source}");
+    let text = fs::read_to_string(&source).unwrap();
+    let data: BTreeMap<String, T> = serde_json::from_str(&text).unwrap();
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join(format!("{name}.bin"));
+    fs::write(out, bincode::serialize(&data).unwrap()).unwrap();
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    convert::<SystemData>("systems");
+    convert::<WormholeInfo>("wormhole_types");
+    // updated data files are only used if they're dated later than this
+    println!("cargo:rerun-if-changed=static/data_version.txt");
+    let version = fs::read_to_string("static/data_version.txt").unwrap();
+    println!("cargo:rustc-env=EMBEDDED_DATA_VERSION={}", version.trim());
+}
@@ -0,0 +1,241 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Something that can be done from the signature list, and the key that does it.
+pub struct Action {
+    pub name: &'static str,
+    pub key: KeyCode,
+    /// Whether the action changes the map, so isn't allowed when read-only.
+    pub edits: bool,
+}
+
+impl Action {
+    const fn new(name: &'static str, key: KeyCode) -> Self {
+        Self {
+            name,
+            key,
+            edits: false,
+        }
+    }
+
+    const fn edit(name: &'static str, key: KeyCode) -> Self {
+        Self {
+            name,
+            key,
+            edits: true,
+        }
+    }
+
+    /// The key as shown to the user, e.g. "v", "Tab" or "F5".
+    pub fn binding(&self) -> String {
+        match self.key {
+            KeyCode::Char(' ') => "Space".to_owned(),
+            KeyCode::Char(c) => c.to_string(),
+            KeyCode::Enter => "Enter".to_owned(),
+            KeyCode::Tab => "Tab".to_owned(),
+            KeyCode::F(n) => format!("F{n}"),
+            other => format!("{other:?}"),
+        }
+    }
+
+    /// The key press that runs the action.
+    pub fn key_event(&self) -> KeyEvent {
+        KeyEvent::new(self.key, KeyModifiers::NONE)
+    }
+}
+
+/// Every action in the signature list, in the order they're listed.
+pub const ACTIONS: &[Action] = &[
+    Action::edit("Paste scan results", KeyCode::Char('v')),
+    Action::edit("Toggle clipboard watching", KeyCode::Char('V')),
+    Action::edit("Edit signature", KeyCode::Enter),
+    Action::edit("New signature", KeyCode::Char('n')),
+    Action::edit("Quick-add wormhole", KeyCode::Char('h')),
+    Action::edit("Cycle signature type", KeyCode::Char('t')),
+    Action::edit("Delete signature", KeyCode::Char('d')),
+    Action::new("Select signature", KeyCode::Char(' ')),
+    Action::edit("Ignore signature", KeyCode::Char('i')),
+    Action::new("Show ignored signatures", KeyCode::Char('I')),
+    Action::edit("Pin signature to top", KeyCode::Char('p')),
+    Action::edit("Favorite signature", KeyCode::Char('f')),
+    Action::new("Favorites", KeyCode::Char('F')),
+    Action::new("Next wormhole", KeyCode::Char('w')),
+    Action::edit("Toggle wormhole EOL", KeyCode::Char('l')),
+    Action::edit("Cycle wormhole mass", KeyCode::Char('M')),
+    Action::edit("Mark all wormholes EOL", KeyCode::Char('E')),
+    Action::edit("Set K162 origin", KeyCode::Char('o')),
+    Action::edit("Connection note", KeyCode::Char('N')),
+    Action::edit("System note", KeyCode::Char('T')),
+    Action::new("Rolling plan", KeyCode::Char('R')),
+    Action::new("Exit route", KeyCode::Char('e')),
+    Action::new("Inbound connections", KeyCode::Char('b')),
+    Action::new("Map", KeyCode::Tab),
+    Action::new("Go to system", KeyCode::Char('g')),
+    Action::new("Find signature in any system", KeyCode::Char('/')),
+    Action::new("Browse systems by class", KeyCode::Char('G')),
+    Action::edit("Move signature to system", KeyCode::Char('m')),
+    Action::edit("Copy signature to system", KeyCode::Char('c')),
+    Action::edit("Clean up empty systems", KeyCode::Char('C')),
+    Action::new("Copy signature", KeyCode::Char('y')),
+    Action::new("Copy system export", KeyCode::Char('Y')),
+    Action::new("Copy wormhole export", KeyCode::Char('W')),
+    Action::new("Export chain graph", KeyCode::Char('x')),
+    Action::new("Save map snapshot", KeyCode::Char('S')),
+    Action::new("Save chain report", KeyCode::Char('P')),
+    Action::new("Export chain for other mappers", KeyCode::Char('J')),
+    Action::edit("Import teammate map", KeyCode::Char('O')),
+    Action::edit("Undo signature move or teammate import", KeyCode::Char('U')),
+    Action::new("Check map for problems", KeyCode::Char('K')),
+    Action::new("Reload config", KeyCode::F(5)),
+    Action::new("Help", KeyCode::Char('?')),
+    Action::new("Quit", KeyCode::Char('q')),
+];
+
+/// Keys for getting around that aren't actions of their own, with what they do.
+const NAVIGATION: [(&str, &str); 5] = [
+    ("Up/Down", "Move the selection"),
+    ("PageUp/PageDown/Home/End", "Move a page at a time"),
+    ("1-9", "Select the numbered row"),
+    ("Ctrl+P", "Search actions"),
+    ("Esc", "Close a popup"),
+];
+
+/// Lines of the help popup: every action with its key, then the navigation keys.
+pub fn help_lines() -> Vec<String> {
+    let mut lines: Vec<_> = ACTIONS
+        .iter()
+        .map(|action| format!("{:<8} {}", action.binding(), action.name))
+        .collect();
+    lines.push(String::new());
+    lines.extend(
+        NAVIGATION
+            .iter()
+            .map(|(keys, does)| format!("{keys:<8} {does}")),
+    );
+    lines
+}
+
+/// The action that changes the map which the key runs, if any.
+///
+/// Actions are bound to plain keys, so Ctrl and Alt combinations never match.
+pub fn editing_action(key: &KeyEvent) -> Option<&'static Action> {
+    if key
+        .modifiers
+        .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+    {
+        return None;
+    }
+    ACTIONS
+        .iter()
+        .find(|action| action.edits && action.key == key.code)
+}
+
+/// How well the query matches the name, lower being better, or `None` if it doesn't.
+///
+/// The query's characters must all appear in the name in order, ignoring case.
+/// Names containing the query as it was typed come first, then tighter matches.
+fn match_score(name: &str, query: &str) -> Option<usize> {
+    let name = name.to_lowercase();
+    let query = query.to_lowercase();
+    if let Some(start) = name.find(&query) {
+        return Some(start);
+    }
+    let mut chars = name.char_indices();
+    let mut first = None;
+    let mut last = 0;
+    for q in query.chars() {
+        let (i, _) = chars.find(|(_, c)| *c == q)?;
+        first.get_or_insert(i);
+        last = i;
+    }
+    Some(name.len() + last - first.unwrap_or(0))
+}
+
+/// Actions matching the query, best first.
+pub fn search(query: &str) -> Vec<&'static Action> {
+    let mut matches: Vec<_> = ACTIONS
+        .iter()
+        .filter_map(|action| match_score(action.name, query.trim()).map(|s| (s, action)))
+        .collect();
+    matches.sort_by_key(|(score, _)| *score);
+    matches.into_iter().map(|(_, action)| action).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{editing_action, help_lines, search, ACTIONS};
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    #[test]
+    fn test_search() {
+        assert_eq!(search("").len(), ACTIONS.len());
+
+        let names: Vec<_> = search("eol").iter().map(|a| a.name).collect();
+        assert_eq!(
+            names[..2],
+            ["Toggle wormhole EOL", "Mark all wormholes EOL"]
+        );
+        // matches spread out through the name come after
+        assert!(names.contains(&"Next wormhole"));
+
+        let found = search("cpy wh");
+        assert_eq!(found[0].name, "Copy wormhole export");
+        assert_eq!(found[0].binding(), "W");
+
+        assert!(search("zzz").is_empty());
+    }
+
+    #[test]
+    fn test_help_lines() {
+        let lines = help_lines();
+        for action in ACTIONS {
+            let line = format!("{:<8} {}", action.binding(), action.name);
+            assert!(lines.contains(&line), "{} isn't listed", action.name);
+        }
+        assert!(lines.contains(&"Space    Select signature".to_owned()));
+        assert!(lines.iter().any(|line| line.starts_with("Ctrl+P")));
+    }
+
+    #[test]
+    fn test_editing_action() {
+        let plain = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        assert_eq!(
+            editing_action(&plain(KeyCode::Char('v'))).map(|a| a.name),
+            Some("Paste scan results")
+        );
+        assert_eq!(
+            editing_action(&plain(KeyCode::Enter)).map(|a| a.name),
+            Some("Edit signature")
+        );
+        // navigation, search and exports stay available
+        for key in ['w', 'g', 'e', 'x', 'Y', 'q'] {
+            assert!(
+                editing_action(&plain(KeyCode::Char(key))).is_none(),
+                "{key}"
+            );
+        }
+        assert!(editing_action(&plain(KeyCode::Tab)).is_none());
+        // Ctrl+P is the palette, not "Pin signature to top"
+        assert!(editing_action(&plain(KeyCode::Char('p'))).is_some());
+        let ctrl_p = KeyEvent::new(KeyCode::Char('p'), KeyModifiers::CONTROL);
+        assert!(editing_action(&ctrl_p).is_none());
+    }
+
+    #[test]
+    fn test_bindings_unique() {
+        for (i, action) in ACTIONS.iter().enumerate() {
+            assert!(
+                !ACTIONS[i + 1..].iter().any(|other| other.key == action.key),
+                "{} shares a key",
+                action.name
+            );
+        }
+        assert_eq!(
+            ACTIONS
+                .iter()
+                .find(|a| a.key == KeyCode::F(5))
+                .unwrap()
+                .binding(),
+            "F5"
+        );
+    }
+}
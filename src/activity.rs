@@ -0,0 +1,79 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// How long activity is kept and shown for.
+pub const ACTIVITY_HOURS: i64 = 24;
+
+/// Something that happened to a signature in a system.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ActivityKind {
+    Appeared,
+    Disappeared,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActivityEvent {
+    pub at: DateTime<Utc>,
+    pub kind: ActivityKind,
+}
+
+/// Add an event to a system's log, dropping ones that have aged out.
+pub fn record(events: &mut Vec<ActivityEvent>, kind: ActivityKind, at: DateTime<Utc>) {
+    let cutoff = at - Duration::hours(ACTIVITY_HOURS);
+    events.retain(|event| event.at >= cutoff);
+    events.push(ActivityEvent { at, kind });
+}
+
+/// Signatures appearing in each hour of the window, oldest first.
+pub fn hourly_appearances(events: &[ActivityEvent], now: DateTime<Utc>) -> Vec<u64> {
+    let mut buckets = vec![0; ACTIVITY_HOURS as usize];
+    for event in events.iter().filter(|e| e.kind == ActivityKind::Appeared) {
+        let hours_ago = (now - event.at).num_hours();
+        if (0..ACTIVITY_HOURS).contains(&hours_ago) {
+            buckets[(ACTIVITY_HOURS - 1 - hours_ago) as usize] += 1;
+        }
+    }
+    buckets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hourly_appearances, record, ActivityKind, ACTIVITY_HOURS};
+    use chrono::{Duration, Utc};
+
+    #[test]
+    fn test_record_drops_old_events() {
+        let now = Utc::now();
+        let mut events = Vec::new();
+        record(
+            &mut events,
+            ActivityKind::Appeared,
+            now - Duration::hours(ACTIVITY_HOURS + 1),
+        );
+        record(&mut events, ActivityKind::Disappeared, now);
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, ActivityKind::Disappeared);
+    }
+
+    #[test]
+    fn test_hourly_appearances() {
+        let now = Utc::now();
+        let mut events = Vec::new();
+        record(
+            &mut events,
+            ActivityKind::Appeared,
+            now - Duration::hours(3),
+        );
+        record(&mut events, ActivityKind::Appeared, now);
+        record(&mut events, ActivityKind::Appeared, now);
+        record(&mut events, ActivityKind::Disappeared, now);
+
+        let buckets = hourly_appearances(&events, now);
+
+        assert_eq!(buckets.len(), ACTIVITY_HOURS as usize);
+        assert_eq!(buckets[23], 2);
+        assert_eq!(buckets[20], 1);
+        assert_eq!(buckets.iter().sum::<u64>(), 3);
+    }
+}
@@ -0,0 +1,203 @@
+use crate::{
+    config::AlertMode,
+    eve_data::{Signature, SignatureId, SignatureType, WormholeMass},
+    sites::site_tier,
+};
+use std::{
+    io::{self, Write},
+    time::{Duration, Instant},
+};
+
+/// Minimum time between two alerts.
+const ALERT_DEBOUNCE: Duration = Duration::from_secs(30);
+/// How long the border stays highlighted in flash mode.
+const FLASH_DURATION: Duration = Duration::from_secs(2);
+
+/// Something worth drawing the user's attention to.
+#[derive(Debug, PartialEq)]
+pub enum Alert {
+    NewConnection(SignatureId),
+    WormholeCritical(SignatureId),
+    ValuableSite(SignatureId),
+}
+
+/// Compare a system's signatures before and after a change for alert-worthy transitions.
+pub fn detect_alerts(before: &[Signature], after: &[Signature]) -> Vec<Alert> {
+    let mut alerts = Vec::new();
+    for sig in after {
+        let SignatureType::Wormhole(data) = &sig.signature_type else {
+            continue;
+        };
+        match before.iter().find(|b| b.identifier == sig.identifier) {
+            Some(Signature {
+                signature_type: SignatureType::Wormhole(old),
+                ..
+            }) => {
+                if data.mass == WormholeMass::Critical && old.mass != WormholeMass::Critical {
+                    alerts.push(Alert::WormholeCritical(sig.identifier.clone()));
+                }
+            }
+            _ => alerts.push(Alert::NewConnection(sig.identifier.clone())),
+        }
+    }
+    alerts
+}
+
+/// Sites in `after` worth at least `min_tier` that weren't already known to be before.
+pub fn detect_valuable_sites(
+    before: &[Signature],
+    after: &[Signature],
+    min_tier: u8,
+) -> Vec<Alert> {
+    after
+        .iter()
+        .filter(|sig| site_tier(&sig.signature_type).is_some_and(|tier| tier >= min_tier))
+        .filter(|sig| {
+            !before
+                .iter()
+                .any(|b| b.identifier == sig.identifier && b.signature_type == sig.signature_type)
+        })
+        .map(|sig| Alert::ValuableSite(sig.identifier.clone()))
+        .collect()
+}
+
+/// Emits the configured bell or flash, at most once per debounce period.
+pub struct Alerter {
+    mode: AlertMode,
+    last_alert: Option<Instant>,
+    flash_until: Option<Instant>,
+}
+
+impl Alerter {
+    pub fn new(mode: AlertMode) -> Self {
+        Self {
+            mode,
+            last_alert: None,
+            flash_until: None,
+        }
+    }
+
+    /// Alert the user, unless alerts are off or one was emitted recently.
+    ///
+    /// Returns whether the alert was emitted.
+    pub fn trigger(&mut self, now: Instant) -> bool {
+        if self.mode == AlertMode::Off {
+            return false;
+        }
+        if let Some(last) = self.last_alert {
+            if now.duration_since(last) < ALERT_DEBOUNCE {
+                return false;
+            }
+        }
+        self.last_alert = Some(now);
+        match self.mode {
+            AlertMode::Off => {}
+            AlertMode::Bell => {
+                let mut stdout = io::stdout();
+                let _ = stdout.write_all(b"\x07");
+                let _ = stdout.flush();
+            }
+            AlertMode::Flash => {
+                self.flash_until = Some(now + FLASH_DURATION);
+            }
+        }
+        true
+    }
+
+    /// Whether the screen border should currently be highlighted.
+    pub fn is_flashing(&self, now: Instant) -> bool {
+        match self.flash_until {
+            Some(until) => now < until,
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{detect_alerts, detect_valuable_sites, Alert, Alerter, ALERT_DEBOUNCE};
+    use crate::{
+        config::AlertMode,
+        eve_data::{Signature, SignatureId, SignatureType, SignatureWormhole, WormholeMass},
+    };
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_detect_alerts_new_connection() {
+        let before = vec![Signature::new("ABC", "123", SignatureType::Unknown)];
+        let after = vec![
+            Signature::new(
+                "ABC",
+                "123",
+                SignatureType::Wormhole(SignatureWormhole::default()),
+            ),
+            Signature::new("DEF", "456", SignatureType::Relic(None)),
+        ];
+
+        assert_eq!(
+            detect_alerts(&before, &after),
+            vec![Alert::NewConnection(SignatureId::new("ABC", "123"))]
+        );
+    }
+
+    #[test]
+    fn test_detect_alerts_critical() {
+        let before = vec![Signature::new(
+            "ABC",
+            "123",
+            SignatureType::Wormhole(SignatureWormhole::default()),
+        )];
+        let after = vec![Signature::new(
+            "ABC",
+            "123",
+            SignatureType::Wormhole(SignatureWormhole {
+                mass: WormholeMass::Critical,
+                ..Default::default()
+            }),
+        )];
+
+        assert_eq!(
+            detect_alerts(&before, &after),
+            vec![Alert::WormholeCritical(SignatureId::new("ABC", "123"))]
+        );
+        assert!(detect_alerts(&after, &after).is_empty());
+    }
+
+    #[test]
+    fn test_detect_valuable_sites() {
+        let relic = |name: &str| SignatureType::Relic(Some(name.to_owned()));
+        let before = vec![Signature::new("ABC", "123", SignatureType::Relic(None))];
+        let after = vec![
+            Signature::new("ABC", "123", relic("Ruined Angel Temple Site")),
+            Signature::new("DEF", "456", relic("Forgotten Perimeter Power Array")),
+            Signature::new("GHI", "789", SignatureType::Unknown),
+        ];
+
+        assert_eq!(
+            detect_valuable_sites(&before, &after, 3),
+            vec![Alert::ValuableSite(SignatureId::new("ABC", "123"))]
+        );
+        assert_eq!(detect_valuable_sites(&before, &after, 1).len(), 2);
+        assert!(detect_valuable_sites(&after, &after, 1).is_empty());
+    }
+
+    #[test]
+    fn test_alerter_debounce() {
+        let mut alerter = Alerter::new(AlertMode::Flash);
+        let start = Instant::now();
+
+        assert!(alerter.trigger(start));
+        assert!(alerter.is_flashing(start));
+        assert!(!alerter.trigger(start + Duration::from_secs(1)));
+        assert!(alerter.trigger(start + ALERT_DEBOUNCE));
+    }
+
+    #[test]
+    fn test_alerter_off() {
+        let mut alerter = Alerter::new(AlertMode::Off);
+        let now = Instant::now();
+
+        assert!(!alerter.trigger(now));
+        assert!(!alerter.is_flashing(now));
+    }
+}
@@ -0,0 +1,73 @@
+use crate::eve_data::parse_paste;
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+fn hash(text: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Watches clipboard contents for new scanner results to ingest without pressing 'v'.
+#[derive(Default)]
+pub struct ClipboardWatch {
+    pub enabled: bool,
+    last_hash: Option<u64>,
+}
+
+impl ClipboardWatch {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            last_hash: None,
+        }
+    }
+
+    /// Turn watching on or off. The clipboard as it is now is never ingested.
+    pub fn toggle(&mut self, current: &str) {
+        self.enabled = !self.enabled;
+        self.last_hash = Some(hash(current));
+    }
+
+    /// Whether the clipboard holds scanner results that haven't been seen yet.
+    pub fn is_new_scan(&mut self, text: &str) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        let hash = hash(text);
+        if self.last_hash == Some(hash) {
+            return false;
+        }
+        self.last_hash = Some(hash);
+        !parse_paste(text).is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ClipboardWatch;
+
+    const SCAN: &str = "UWG-400\tCosmic Signature\tWormhole\tUnstable Wormhole\t100.0%\t33.21 AU";
+
+    #[test]
+    fn test_skips_unchanged_clipboard() {
+        let mut watch = ClipboardWatch::new(true);
+
+        assert!(watch.is_new_scan(SCAN));
+        assert!(!watch.is_new_scan(SCAN));
+        assert!(!watch.is_new_scan("some chat text"));
+        assert!(watch.is_new_scan(SCAN));
+    }
+
+    #[test]
+    fn test_toggle() {
+        let mut watch = ClipboardWatch::default();
+        assert!(!watch.is_new_scan(SCAN));
+
+        watch.toggle(SCAN);
+        assert!(watch.enabled);
+        assert!(!watch.is_new_scan(SCAN));
+    }
+}
@@ -0,0 +1,708 @@
+use crate::config::BadgeGlyphs;
+use crate::eve_data::{
+    ConnectionKind, Signature, SignatureId, SignatureType, SignatureWormhole, SystemClassification,
+    WormholeLife, WormholeMass, ALL_SYSTEMS,
+};
+use crate::routes::{nearest_hub, HubRoute};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::Write,
+};
+
+/// A single physical wormhole, shared by the signatures on each side of it.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct Connection {
+    /// Signatures for the hole, as (system, signature) pairs.
+    pub ends: Vec<(String, SignatureId)>,
+    pub note: Option<String>,
+}
+
+impl Connection {
+    pub fn has_end(&self, system: &str, id: &SignatureId) -> bool {
+        self.ends.iter().any(|(s, i)| s == system && i == id)
+    }
+}
+
+/// The signature on the other side of a wormhole: one in its destination leading back.
+pub fn find_pair(
+    system_data: &HashMap<String, Vec<Signature>>,
+    system: &str,
+    id: &SignatureId,
+) -> Option<(String, SignatureId)> {
+    let destination = system_data
+        .get(system)?
+        .iter()
+        .find(|sig| &sig.identifier == id)
+        .and_then(|sig| match &sig.signature_type {
+            SignatureType::Wormhole(wh) => wh.destination.as_ref(),
+            _ => None,
+        })?;
+    let other = system_data.get(destination)?.iter().find(|sig| {
+        matches!(
+            &sig.signature_type,
+            SignatureType::Wormhole(wh) if wh.destination.as_deref() == Some(system)
+        )
+    })?;
+    Some((destination.clone(), other.identifier.clone()))
+}
+
+/// A wormhole leading from one system to a known destination.
+pub struct ChainEdge<'a> {
+    pub from: &'a str,
+    pub to: &'a str,
+    pub signature: &'a Signature,
+    pub wormhole: &'a SignatureWormhole,
+}
+
+/// All wormhole connections with a known destination, ordered by source system.
+pub fn chain_edges(system_data: &HashMap<String, Vec<Signature>>) -> Vec<ChainEdge<'_>> {
+    let mut systems: Vec<_> = system_data.keys().collect();
+    systems.sort();
+    let mut edges = Vec::new();
+    for system in systems {
+        for signature in &system_data[system] {
+            if let SignatureType::Wormhole(wormhole) = &signature.signature_type {
+                if let Some(destination) = &wormhole.destination {
+                    edges.push(ChainEdge {
+                        from: system,
+                        to: destination,
+                        signature,
+                        wormhole,
+                    });
+                }
+            }
+        }
+    }
+    edges
+}
+
+/// A system in the map, with how many hops it is from the root.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapNode {
+    pub system: String,
+    pub depth: usize,
+    /// Systems folded into this node when it stands in for a stale branch, itself first.
+    pub collapsed: Vec<String>,
+}
+
+/// Systems reachable from the root through known wormholes, depth-first.
+///
+/// Wormholes can be travelled in either direction, so holes leading into
+/// a system link it to its source too.
+pub fn map_tree(system_data: &HashMap<String, Vec<Signature>>, root: &str) -> Vec<MapNode> {
+    let mut neighbors: HashMap<&str, Vec<&str>> = HashMap::new();
+    for edge in chain_edges(system_data) {
+        neighbors.entry(edge.from).or_default().push(edge.to);
+        neighbors.entry(edge.to).or_default().push(edge.from);
+    }
+    for next in neighbors.values_mut() {
+        next.sort();
+        next.dedup();
+    }
+
+    let mut nodes = Vec::new();
+    let mut stack = vec![(root, 0)];
+    let mut visited = vec![root];
+    while let Some((system, depth)) = stack.pop() {
+        nodes.push(MapNode {
+            collapsed: Vec::new(),
+            system: system.to_owned(),
+            depth,
+        });
+        if let Some(next) = neighbors.get(system) {
+            for neighbor in next.iter().rev() {
+                if !visited.contains(neighbor) {
+                    visited.push(neighbor);
+                    stack.push((neighbor, depth + 1));
+                }
+            }
+        }
+    }
+    nodes
+}
+
+/// Collapse branches of the map where every system is stale into their first system.
+///
+/// The root is never collapsed, nor is a branch starting at an `expanded`
+/// system. Branches of a single system are left alone, since collapsing them
+/// would hide nothing.
+pub fn collapse_stale(
+    nodes: Vec<MapNode>,
+    is_stale: impl Fn(&str) -> bool,
+    expanded: &[String],
+) -> Vec<MapNode> {
+    let mut collapsed = Vec::new();
+    let mut index = 0;
+    while index < nodes.len() {
+        let node = &nodes[index];
+        let end = nodes[index + 1..]
+            .iter()
+            .position(|n| n.depth <= node.depth)
+            .map_or(nodes.len(), |offset| index + 1 + offset);
+        let branch = &nodes[index..end];
+        if node.depth > 0
+            && branch.len() > 1
+            && !expanded.contains(&node.system)
+            && branch.iter().all(|n| is_stale(&n.system))
+        {
+            collapsed.push(MapNode {
+                collapsed: branch.iter().map(|n| n.system.clone()).collect(),
+                ..node.clone()
+            });
+            index = end;
+        } else {
+            collapsed.push(node.clone());
+            index += 1;
+        }
+    }
+    collapsed
+}
+
+/// A map node as a line of text, e.g. "  J173213  4/1" or "  Jita  stale ×3".
+pub fn map_line(system_data: &HashMap<String, Vec<Signature>>, node: &MapNode) -> String {
+    let mut line = format!("{}{}", "  ".repeat(node.depth), node.system);
+    if !node.collapsed.is_empty() {
+        line.push_str(&format!("  stale ×{}", node.collapsed.len()));
+    } else if let Some((total, unknown)) = node_counter(system_data, &node.system) {
+        line.push_str(&format!("  {total}/{unknown}"));
+    }
+    line
+}
+
+/// Signatures in the system as "total/unknown", or `None` if there aren't any.
+///
+/// Placeholders and ignored signatures aren't counted.
+pub fn node_counter(
+    system_data: &HashMap<String, Vec<Signature>>,
+    system: &str,
+) -> Option<(usize, usize)> {
+    let sigs: Vec<_> = system_data
+        .get(system)?
+        .iter()
+        .filter(|sig| !sig.is_placeholder() && !sig.ignored)
+        .collect();
+    if sigs.is_empty() {
+        return None;
+    }
+    let unknown = sigs
+        .iter()
+        .filter(|sig| sig.signature_type == SignatureType::Unknown)
+        .count();
+    Some((sigs.len(), unknown))
+}
+
+/// Status of the connections and intel for a system on the map.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct NodeBadges {
+    /// A connection touching the system is EOL.
+    pub eol: bool,
+    /// A connection touching the system is mass-critical.
+    pub crit: bool,
+    /// Kill intel shows recent kills in the system.
+    pub kills: bool,
+    /// The closest trade hub and its jump count, for k-space systems.
+    pub hub: Option<(&'static str, usize)>,
+}
+
+impl NodeBadges {
+    /// Badges as glyphs from the set, e.g. "⌛⚠ Jita 4", or an empty string if there aren't any.
+    pub fn render(&self, glyphs: BadgeGlyphs) -> String {
+        let mut out = String::new();
+        if self.eol {
+            out.push_str(glyphs.eol());
+        }
+        if self.crit {
+            out.push_str(glyphs.crit());
+        }
+        if self.kills {
+            out.push_str(glyphs.kills());
+        }
+        if let Some((hub, jumps)) = self.hub {
+            if !out.is_empty() {
+                out.push(' ');
+            }
+            out.push_str(&format!("{hub} {jumps}"));
+        }
+        out
+    }
+}
+
+/// Badges for a system from its own wormholes, those leading into it, cached kill counts,
+/// and its trade hub routes if they've been fetched.
+pub fn node_badges(
+    system_data: &HashMap<String, Vec<Signature>>,
+    recent_kills: &HashMap<String, u32>,
+    hub_routes: Option<&[(&'static str, HubRoute)]>,
+    system: &str,
+) -> NodeBadges {
+    let own = system_data
+        .get(system)
+        .into_iter()
+        .flatten()
+        .filter(|sig| !sig.ignored)
+        .filter_map(|sig| match &sig.signature_type {
+            SignatureType::Wormhole(wormhole) => Some(wormhole),
+            _ => None,
+        });
+    let inbound = inbound_edges(system_data, system);
+    let mut badges = NodeBadges {
+        kills: recent_kills.get(system).is_some_and(|kills| *kills > 0),
+        hub: hub_routes.and_then(nearest_hub),
+        ..Default::default()
+    };
+    for wormhole in own.chain(inbound.iter().map(|edge| edge.wormhole)) {
+        badges.eol |= wormhole.life == WormholeLife::EndOfLife;
+        badges.crit |= wormhole.mass == WormholeMass::Critical;
+    }
+    badges
+}
+
+/// Wormholes in other systems that lead into the system.
+pub fn inbound_edges<'a>(
+    system_data: &'a HashMap<String, Vec<Signature>>,
+    system: &str,
+) -> Vec<ChainEdge<'a>> {
+    chain_edges(system_data)
+        .into_iter()
+        .filter(|edge| edge.to == system && edge.from != system)
+        .collect()
+}
+
+/// Systems connected to each system, in either direction.
+///
+/// With `safe_only`, EOL and critical holes are left out.
+fn chain_neighbors(
+    system_data: &HashMap<String, Vec<Signature>>,
+    safe_only: bool,
+) -> HashMap<&str, Vec<&str>> {
+    let mut neighbors: HashMap<&str, Vec<&str>> = HashMap::new();
+    for edge in chain_edges(system_data) {
+        if safe_only
+            && (edge.wormhole.life == WormholeLife::EndOfLife
+                || edge.wormhole.mass == WormholeMass::Critical)
+        {
+            continue;
+        }
+        neighbors.entry(edge.from).or_default().push(edge.to);
+        neighbors.entry(edge.to).or_default().push(edge.from);
+    }
+    neighbors
+}
+
+/// Preference order for exits that are the same number of hops away.
+fn exit_rank(system: &str) -> Option<u8> {
+    match ALL_SYSTEMS.get(system)?.classification() {
+        SystemClassification::HighSec => Some(0),
+        SystemClassification::LowSec => Some(1),
+        SystemClassification::NullSec => Some(2),
+        SystemClassification::WSpace(_) => None,
+    }
+}
+
+/// Systems passed through to reach the best k-space exit, starting with `from`.
+///
+/// Wormholes can be travelled in either direction. The exit with the fewest
+/// hops wins, with ties going to high-sec, then low-sec, then null-sec. With
+/// `safe_only`, EOL and critical holes are avoided.
+pub fn best_exit(
+    system_data: &HashMap<String, Vec<Signature>>,
+    from: &str,
+    safe_only: bool,
+) -> Option<Vec<String>> {
+    let neighbors = chain_neighbors(system_data, safe_only);
+
+    let mut previous: HashMap<&str, &str> = HashMap::new();
+    let mut queue = VecDeque::from([(from, 0)]);
+    let mut best: Option<(usize, u8, &str)> = None;
+    while let Some((system, hops)) = queue.pop_front() {
+        if best.is_some_and(|(best_hops, _, _)| hops > best_hops) {
+            break;
+        }
+        if system != from {
+            if let Some(rank) = exit_rank(system) {
+                let candidate = (hops, rank, system);
+                if best.is_none_or(|b| candidate < b) {
+                    best = Some(candidate);
+                }
+                continue;
+            }
+        }
+        let mut next = neighbors.get(system).cloned().unwrap_or_default();
+        next.sort();
+        for neighbor in next {
+            if neighbor != from && !previous.contains_key(neighbor) {
+                previous.insert(neighbor, system);
+                queue.push_back((neighbor, hops + 1));
+            }
+        }
+    }
+
+    let (_, _, exit) = best?;
+    let mut path = vec![exit.to_owned()];
+    let mut current = exit;
+    while let Some(prev) = previous.get(current) {
+        path.push((*prev).to_owned());
+        current = prev;
+    }
+    path.reverse();
+    Some(path)
+}
+
+/// Graphviz color for a system node, by security class.
+fn node_color(system: &str) -> &'static str {
+    match ALL_SYSTEMS.get(system).map(|data| data.classification()) {
+        Some(SystemClassification::HighSec) => "green",
+        Some(SystemClassification::LowSec) => "gold",
+        Some(SystemClassification::NullSec) => "red",
+        Some(SystemClassification::WSpace(_)) => "magenta",
+        None => "gray",
+    }
+}
+
+/// Graphviz color for a wormhole edge, by its life and mass.
+fn edge_color(wormhole: &SignatureWormhole) -> &'static str {
+    if wormhole.mass == WormholeMass::Critical {
+        "red"
+    } else if wormhole.life == WormholeLife::EndOfLife {
+        "orange"
+    } else {
+        "black"
+    }
+}
+
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Render the chain as a Graphviz DOT digraph.
+pub fn to_dot(system_data: &HashMap<String, Vec<Signature>>) -> String {
+    let edges = chain_edges(system_data);
+    let mut nodes: Vec<&str> = system_data.keys().map(String::as_str).collect();
+    nodes.extend(edges.iter().map(|edge| edge.to));
+    nodes.sort();
+    nodes.dedup();
+
+    let mut dot = String::from("digraph chain {\n");
+    for node in nodes {
+        let _ = writeln!(dot, "    {} [color={}];", quote(node), node_color(node));
+    }
+    for edge in edges {
+        let label = format!(
+            "{} {}{}\n{}/{}",
+            edge.signature.identifier,
+            edge.wormhole.wh_type.as_deref().unwrap_or("?"),
+            match edge.wormhole.connection_kind {
+                ConnectionKind::Unknown => String::new(),
+                kind => format!(" {}", kind.badge()),
+            },
+            edge.wormhole.life.as_str(),
+            edge.wormhole.mass.as_str()
+        );
+        let _ = writeln!(
+            dot,
+            "    {} -> {} [label={}, color={}];",
+            quote(edge.from),
+            quote(edge.to),
+            quote(&label).replace('\n', "\\n"),
+            edge_color(edge.wormhole)
+        );
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        best_exit, chain_edges, collapse_stale, find_pair, inbound_edges, map_line, map_tree,
+        node_badges, node_counter, to_dot, MapNode, NodeBadges,
+    };
+    use crate::config::BadgeGlyphs;
+    use crate::eve_data::{
+        Signature, SignatureId, SignatureType, SignatureWormhole, WormholeLife, WormholeMass,
+    };
+    use crate::routes::HubRoute;
+    use std::collections::HashMap;
+
+    fn sample() -> HashMap<String, Vec<Signature>> {
+        let mut data = HashMap::new();
+        data.insert(
+            "J173213".to_owned(),
+            vec![
+                Signature::new(
+                    "ABC",
+                    "123",
+                    SignatureType::Wormhole(SignatureWormhole {
+                        wh_type: Some("B274".to_owned()),
+                        destination: Some("Jita".to_owned()),
+                        ..Default::default()
+                    }),
+                ),
+                Signature::new(
+                    "DEF",
+                    "456",
+                    SignatureType::Wormhole(SignatureWormhole {
+                        destination: Some("Thera".to_owned()),
+                        life: WormholeLife::EndOfLife,
+                        mass: WormholeMass::Critical,
+                        ..Default::default()
+                    }),
+                ),
+                Signature::new(
+                    "GHI",
+                    "789",
+                    SignatureType::Wormhole(SignatureWormhole::default()),
+                ),
+                Signature::new("JKL", "012", SignatureType::Relic(None)),
+            ],
+        );
+        data
+    }
+
+    #[test]
+    fn test_chain_edges() {
+        let data = sample();
+        let edges = chain_edges(&data);
+
+        assert_eq!(edges.len(), 2);
+        assert_eq!(edges[0].from, "J173213");
+        assert_eq!(edges[0].to, "Jita");
+        assert_eq!(edges[1].to, "Thera");
+    }
+
+    #[test]
+    fn test_inbound_edges() {
+        let data = sample();
+
+        let inbound = inbound_edges(&data, "Jita");
+
+        assert_eq!(inbound.len(), 1);
+        assert_eq!(inbound[0].from, "J173213");
+        assert_eq!(inbound[0].signature.identifier.to_string(), "ABC-123");
+        assert!(inbound_edges(&data, "J173213").is_empty());
+    }
+
+    #[test]
+    fn test_map_tree() {
+        let mut data = sample();
+        data.insert(
+            "Amamake".to_owned(),
+            vec![Signature::new(
+                "XYZ",
+                "999",
+                SignatureType::Wormhole(SignatureWormhole {
+                    destination: Some("Jita".to_owned()),
+                    ..Default::default()
+                }),
+            )],
+        );
+
+        let tree = map_tree(&data, "J173213");
+
+        let node = |system: &str, depth| MapNode {
+            system: system.to_owned(),
+            depth,
+            collapsed: Vec::new(),
+        };
+        assert_eq!(
+            tree,
+            vec![
+                node("J173213", 0),
+                node("Jita", 1),
+                node("Amamake", 2),
+                node("Thera", 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_collapse_stale() {
+        let node = |system: &str, depth| MapNode {
+            system: system.to_owned(),
+            depth,
+            collapsed: Vec::new(),
+        };
+        let nodes = vec![
+            node("J173213", 0),
+            node("Jita", 1),
+            node("Amamake", 2),
+            node("Rens", 2),
+            node("Thera", 1),
+            node("Hek", 2),
+        ];
+        let stale = |system: &str| system != "Hek";
+
+        let collapsed = collapse_stale(nodes.clone(), stale, &[]);
+        assert_eq!(collapsed.len(), 4);
+        assert_eq!(collapsed[0].system, "J173213");
+        assert_eq!(collapsed[1].collapsed, vec!["Jita", "Amamake", "Rens"]);
+        assert_eq!(collapsed[2], node("Thera", 1));
+        assert_eq!(collapsed[3], node("Hek", 2));
+
+        let expanded = collapse_stale(nodes.clone(), stale, &["Jita".to_owned()]);
+        assert_eq!(expanded.len(), 6);
+        assert_eq!(collapse_stale(nodes, |_| true, &[]).len(), 3);
+    }
+
+    #[test]
+    fn test_map_line() {
+        let data = sample();
+        let node = |system: &str, depth, collapsed: Vec<String>| MapNode {
+            system: system.to_owned(),
+            depth,
+            collapsed,
+        };
+
+        assert_eq!(map_line(&data, &node("J173213", 0, vec![])), "J173213  4/0");
+        assert_eq!(map_line(&data, &node("Jita", 1, vec![])), "  Jita");
+        assert_eq!(
+            map_line(
+                &data,
+                &node("Thera", 1, vec!["Thera".to_owned(), "Hek".to_owned()])
+            ),
+            "  Thera  stale ×2"
+        );
+    }
+
+    #[test]
+    fn test_node_counter() {
+        let mut data = sample();
+        data.get_mut("J173213")
+            .unwrap()
+            .push(Signature::new("MNO", "345", SignatureType::Unknown));
+        data.insert("Thera".to_owned(), Vec::new());
+
+        assert_eq!(node_counter(&data, "J173213"), Some((5, 1)));
+        assert_eq!(node_counter(&data, "Thera"), None);
+        assert_eq!(node_counter(&data, "Jita"), None);
+    }
+
+    #[test]
+    fn test_node_badges() {
+        let data = sample();
+        let mut kills = HashMap::new();
+        kills.insert("Jita".to_owned(), 3);
+        kills.insert("Amamake".to_owned(), 0);
+
+        let badges = node_badges(&data, &kills, None, "J173213");
+        assert_eq!(
+            badges,
+            NodeBadges {
+                eol: true,
+                crit: true,
+                kills: false,
+                hub: None,
+            }
+        );
+        assert_eq!(badges.render(BadgeGlyphs::Ascii), "E!");
+        assert_eq!(badges.render(BadgeGlyphs::Unicode), "⌛⚠");
+
+        let thera = node_badges(&data, &kills, None, "Thera");
+        assert!(thera.eol && thera.crit && !thera.kills);
+
+        let jita = node_badges(&data, &kills, None, "Jita");
+        assert_eq!(jita.render(BadgeGlyphs::Ascii), "K");
+        assert_eq!(
+            node_badges(&data, &kills, None, "Amamake"),
+            NodeBadges::default()
+        );
+
+        let routes = [(
+            "Jita",
+            HubRoute {
+                shortest: Some(0),
+                secure: Some(0),
+            },
+        )];
+        let jita = node_badges(&data, &kills, Some(&routes), "Jita");
+        assert_eq!(jita.render(BadgeGlyphs::Unicode), "☠ Jita 0");
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let dot = to_dot(&sample());
+
+        assert!(dot.starts_with("digraph chain {\n"));
+        assert!(dot.ends_with("}\n"));
+        assert_eq!(dot.matches('{').count(), dot.matches('}').count());
+        assert_eq!(dot.matches(" -> ").count(), 2);
+        assert!(dot.contains("\"J173213\" [color=magenta];"));
+        assert!(dot.contains("\"Jita\" [color=green];"));
+        assert!(dot.contains(
+            "\"J173213\" -> \"Jita\" [label=\"ABC-123 B274\\nStable/Stable\", color=black];"
+        ));
+        assert!(dot
+            .contains("\"J173213\" -> \"Thera\" [label=\"DEF-456 ?\\nEOL/Critical\", color=red];"));
+    }
+
+    #[test]
+    fn test_find_pair() {
+        let mut data = sample();
+        data.insert(
+            "Jita".to_owned(),
+            vec![Signature::new(
+                "XYZ",
+                "999",
+                SignatureType::Wormhole(SignatureWormhole {
+                    destination: Some("J173213".to_owned()),
+                    ..Default::default()
+                }),
+            )],
+        );
+
+        assert_eq!(
+            find_pair(&data, "J173213", &SignatureId::new("ABC", "123")),
+            Some(("Jita".to_owned(), SignatureId::new("XYZ", "999")))
+        );
+        assert_eq!(
+            find_pair(&data, "Jita", &SignatureId::new("XYZ", "999")),
+            Some(("J173213".to_owned(), SignatureId::new("ABC", "123")))
+        );
+        assert!(find_pair(&data, "J173213", &SignatureId::new("DEF", "456")).is_none());
+    }
+
+    fn wormhole_to(id: &str, destination: &str, life: WormholeLife) -> Signature {
+        Signature::new(
+            id,
+            "000",
+            SignatureType::Wormhole(SignatureWormhole {
+                destination: Some(destination.to_owned()),
+                life,
+                ..Default::default()
+            }),
+        )
+    }
+
+    #[test]
+    fn test_best_exit() {
+        let mut data = HashMap::new();
+        data.insert(
+            "J173213".to_owned(),
+            vec![
+                wormhole_to("AAA", "Thera", WormholeLife::Stable),
+                wormhole_to("BBB", "Jita", WormholeLife::EndOfLife),
+            ],
+        );
+        // reachable the other way through Thera's hole
+        data.insert(
+            "Amamake".to_owned(),
+            vec![wormhole_to("CCC", "Thera", WormholeLife::Stable)],
+        );
+        data.insert(
+            "Thera".to_owned(),
+            vec![wormhole_to("DDD", "Dodixie", WormholeLife::Stable)],
+        );
+
+        assert_eq!(
+            best_exit(&data, "J173213", false).unwrap(),
+            vec!["J173213", "Jita"]
+        );
+        // ties go to high-sec
+        assert_eq!(
+            best_exit(&data, "J173213", true).unwrap(),
+            vec!["J173213", "Thera", "Dodixie"]
+        );
+        assert!(best_exit(&data, "Nowhere", false).is_none());
+    }
+}
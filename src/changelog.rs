@@ -0,0 +1,103 @@
+/// Release notes, newest version first, as "## <version>" sections.
+const CHANGELOG: &str = include_str!("../CHANGELOG.md");
+
+/// Version of this build.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Numeric parts of a version like "1.2.3", ignoring any pre-release suffix.
+fn parse_version(version: &str) -> Vec<u64> {
+    version
+        .split('-')
+        .next()
+        .unwrap_or_default()
+        .split('.')
+        .map(|part| part.trim().parse().unwrap_or(0))
+        .collect()
+}
+
+/// Whether `current` is newer than the last version seen.
+///
+/// Saves from before the last seen version was recorded count as older than every version.
+pub fn is_upgrade(last_seen: Option<&str>, current: &str) -> bool {
+    match last_seen {
+        Some(last_seen) => parse_version(last_seen) < parse_version(current),
+        None => true,
+    }
+}
+
+/// Changelog sections for versions after the last one seen, up to `current`.
+fn notes_between(changelog: &str, last_seen: Option<&str>, current: &str) -> String {
+    changelog
+        .split("\n## ")
+        .skip(1)
+        .filter(|section| {
+            let version = section.lines().next().unwrap_or_default();
+            is_upgrade(last_seen, version) && !is_upgrade(Some(current), version)
+        })
+        .map(|section| format!("## {}", section.trim_end()))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// What's new since the last version seen, if this build is an upgrade from it.
+pub fn whats_new(last_seen: Option<&str>) -> Option<String> {
+    if !is_upgrade(last_seen, VERSION) {
+        return None;
+    }
+    let notes = notes_between(CHANGELOG, last_seen, VERSION);
+    Some(if notes.is_empty() {
+        format!("Updated to {VERSION}")
+    } else {
+        notes
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_upgrade, notes_between, whats_new, VERSION};
+
+    const CHANGELOG: &str = "# Changelog
+
+## 0.3.0
+
+- Third
+
+## 0.2.0
+
+- Second
+
+## 0.1.0
+
+- First
+";
+
+    #[test]
+    fn test_is_upgrade() {
+        assert!(is_upgrade(Some("0.1.0"), "0.2.0"));
+        assert!(is_upgrade(Some("0.9.0"), "0.10.0"));
+        assert!(is_upgrade(Some("1.2"), "1.2.1"));
+        assert!(is_upgrade(None, "0.1.0"));
+        assert!(!is_upgrade(Some("0.2.0"), "0.2.0"));
+        assert!(!is_upgrade(Some("0.3.0"), "0.2.0"));
+        assert!(!is_upgrade(Some("0.2.0"), "0.2.0-beta"));
+    }
+
+    #[test]
+    fn test_whats_new() {
+        assert_eq!(whats_new(Some(VERSION)), None);
+        assert!(whats_new(Some("0.0.1")).is_some());
+
+        assert_eq!(
+            notes_between(CHANGELOG, Some("0.1.0"), "0.2.0"),
+            "## 0.2.0\n\n- Second"
+        );
+        assert_eq!(
+            notes_between(CHANGELOG, Some("0.1.0"), "0.3.0"),
+            "## 0.3.0\n\n- Third\n\n## 0.2.0\n\n- Second"
+        );
+        assert_eq!(
+            notes_between(CHANGELOG, None, "0.1.0"),
+            "## 0.1.0\n\n- First"
+        );
+    }
+}
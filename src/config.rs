@@ -1,18 +1,621 @@
-use anyhow::Result;
+use crate::eve_data::{SignatureWormhole, WormholeLife, WormholeMass};
+use anyhow::{anyhow, Result};
 use serde::Deserialize;
-use std::fs;
+use std::{collections::HashMap, fs};
 
-#[derive(Debug, Deserialize)]
+/// How to get the user's attention on important events.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertMode {
+    #[default]
+    Off,
+    Bell,
+    Flash,
+}
+
+/// How signature ages and other timestamps are shown.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimestampFormat {
+    /// e.g. "5m ago"
+    #[default]
+    Relative,
+    /// Local date and time
+    Absolute,
+}
+
+/// Life and mass given to newly-added wormholes.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NewWormholeState {
+    #[default]
+    Stable,
+    /// Unknown until explicitly confirmed
+    Unknown,
+}
+
+impl NewWormholeState {
+    /// Template for new wormhole signatures.
+    pub fn wormhole(&self) -> SignatureWormhole {
+        match self {
+            NewWormholeState::Stable => SignatureWormhole::default(),
+            NewWormholeState::Unknown => SignatureWormhole {
+                life: WormholeLife::Unknown,
+                mass: WormholeMass::Unknown,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// Glyphs used for status badges on the map.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BadgeGlyphs {
+    #[default]
+    Unicode,
+    /// For terminals that can't render the Unicode set
+    Ascii,
+}
+
+impl BadgeGlyphs {
+    /// Glyph for an end-of-life connection.
+    pub fn eol(&self) -> &'static str {
+        match self {
+            BadgeGlyphs::Unicode => "⌛",
+            BadgeGlyphs::Ascii => "E",
+        }
+    }
+
+    /// Glyph for a mass-critical connection.
+    pub fn crit(&self) -> &'static str {
+        match self {
+            BadgeGlyphs::Unicode => "⚠",
+            BadgeGlyphs::Ascii => "!",
+        }
+    }
+
+    /// Glyph for recent kills.
+    pub fn kills(&self) -> &'static str {
+        match self {
+            BadgeGlyphs::Unicode => "☠",
+            BadgeGlyphs::Ascii => "K",
+        }
+    }
+}
+
+/// Format of signature exports copied to the clipboard.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// One line per signature
+    #[default]
+    Text,
+    /// A markdown table
+    Markdown,
+    /// "ID<tab>group<tab>name" lines, which other mappers accept
+    Scanner,
+}
+
+/// Which systems go in the markdown chain report.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ReportOptions {
+    /// Leave out systems without any signatures
+    pub skip_empty: bool,
+    /// Include high-, low- and null-sec systems
+    pub include_kspace: bool,
+}
+
+impl Default for ReportOptions {
+    fn default() -> Self {
+        Self {
+            skip_empty: false,
+            include_kspace: true,
+        }
+    }
+}
+
+/// Which destructive actions ask for a yes first.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Confirmations {
+    /// Deleting signatures
+    pub delete: bool,
+    /// Removing systems from the map, such as cleaning up empty ones
+    pub remove_systems: bool,
+    /// Marking every wormhole in a system EOL, or restoring their lives
+    pub mark_all_eol: bool,
+}
+
+impl Default for Confirmations {
+    fn default() -> Self {
+        Self {
+            delete: true,
+            remove_systems: true,
+            mark_all_eol: true,
+        }
+    }
+}
+
+/// Format of scheduled chain exports.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScheduledFormat {
+    /// The map state as JSON
+    Json,
+    /// The markdown chain report
+    Markdown,
+    /// A Graphviz DOT graph
+    Dot,
+}
+
+/// Export of the chain written on a timer.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ScheduledExport {
+    pub format: ScheduledFormat,
+    /// File to write, with strftime-style fields filled from the UTC time, e.g. "chain-%H%M.md".
+    pub path: String,
+    pub interval_minutes: u64,
+}
+
+/// Local TCP listener that other tools can send commands to.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ListenerConfig {
+    /// Port on 127.0.0.1 to listen on.
+    pub port: u16,
+    /// Shared secret that every command must include, if set.
+    pub token: Option<String>,
+}
+
+/// A registered EVE SSO application.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SsoApp {
+    pub label: Option<String>,
+    pub client_id: String,
+    pub client_secret: String,
+    pub callback_url: String,
+    /// ESI base URL override, for servers other than Tranquility.
+    pub base_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
 pub struct Config {
+    #[serde(default)]
     pub sso_client_id: String,
+    #[serde(default)]
     pub sso_client_secret: String,
+    #[serde(default)]
     pub sso_callback_url: String,
+    /// Additional SSO applications, beyond the top-level one.
+    #[serde(default)]
+    pub sso_apps: Vec<SsoApp>,
+    /// Character name to the label of the SSO app that issued its token.
+    #[serde(default)]
+    pub characters: HashMap<String, String>,
+    /// Name edits are attributed to, instead of a configured character's.
+    pub handle: Option<String>,
+    #[serde(default)]
+    pub alert_mode: AlertMode,
+    #[serde(default)]
+    pub timestamp_format: TimestampFormat,
+    #[serde(default)]
+    pub new_wormhole_state: NewWormholeState,
+    /// Add placeholders for a w-space system's statics when first going to it.
+    #[serde(default)]
+    pub auto_static_placeholders: bool,
+    #[serde(default)]
+    pub badge_glyphs: BadgeGlyphs,
+    #[serde(default)]
+    pub export_format: ExportFormat,
+    /// Show a column estimating the probe cycles left for each signature.
+    #[serde(default)]
+    pub show_scan_estimate: bool,
+    /// Abbreviate signature types in the list (C/WH/O/D/R/G), for narrow terminals.
+    #[serde(default)]
+    pub short_type_labels: bool,
+    /// Color wormhole signatures by where they lead, so the same destination stands out across systems.
+    #[serde(default)]
+    pub color_by_destination: bool,
+    /// Highlight the first new or unresolved signature after pasting.
+    #[serde(default)]
+    pub focus_follows_paste: bool,
+    /// Start with new scanner results on the clipboard pasted automatically.
+    #[serde(default)]
+    pub clipboard_watch: bool,
+    /// Gzip the state file and its backups.
+    #[serde(default)]
+    pub compress_state: bool,
+    /// View the map without being able to change it, e.g. when following a shared map.
+    #[serde(default)]
+    pub read_only: bool,
+    /// Check for input less often after a while without any, to save battery.
+    #[serde(default)]
+    pub low_power: bool,
+    /// Check GitHub for a newer release on startup, at most once a day.
+    #[serde(default)]
+    pub check_for_updates: bool,
+    /// Base URL `update-data` downloads the static data files from, instead of the project's mirror.
+    ///
+    /// It must serve `systems.json`, `wormhole_types.json` and `data_version.txt`.
+    pub data_url: Option<String>,
+    /// Alert when a paste reveals a site of at least this value tier (1-3).
+    pub valuable_site_tier: Option<u8>,
+    /// Scan strength in percent a signature must reach before its scanned type is used.
+    pub min_scan_strength: Option<f32>,
+    /// Signatures older than this many hours are pruned on startup.
+    pub retention_hours: Option<u64>,
+    /// System the chain is mapped out from, never swept as empty.
+    pub home_system: Option<String>,
+    /// Remove empty systems nothing points at whenever the state is saved.
+    #[serde(default)]
+    pub sweep_empty_systems: bool,
+    /// Map branches with no activity for this many hours are collapsed.
+    pub stale_branch_hours: Option<u64>,
+    #[serde(default)]
+    pub report: ReportOptions,
+    #[serde(default)]
+    pub confirm: Confirmations,
+    pub scheduled_export: Option<ScheduledExport>,
+    /// Directory to watch for scan results saved as text files.
+    pub scan_dir: Option<String>,
+    pub listener: Option<ListenerConfig>,
 }
 
 impl Config {
     pub fn load() -> Result<Self> {
-        let text = fs::read_to_string("config.toml")?;
-        let data = toml::from_str(&text)?;
+        Self::parse(&fs::read_to_string("config.toml")?)
+    }
+
+    /// Parse and validate config text.
+    pub fn parse(text: &str) -> Result<Self> {
+        let data: Self = toml::from_str(text)?;
+        data.validate()?;
         Ok(data)
     }
+
+    /// Take the settings from the new config that can change while running.
+    ///
+    /// Settings only read at startup are left as they were. Returns the names
+    /// of any of those that differ, since they need a restart.
+    pub fn apply_reload(&mut self, new: Config) -> Vec<&'static str> {
+        let mut restart = Vec::new();
+        if self.apps() != new.apps() || self.characters != new.characters {
+            restart.push("SSO apps");
+        }
+        if self.retention_hours != new.retention_hours {
+            restart.push("retention_hours");
+        }
+        if self.scheduled_export != new.scheduled_export {
+            restart.push("scheduled_export");
+        }
+        if self.scan_dir != new.scan_dir {
+            restart.push("scan_dir");
+        }
+        if self.listener != new.listener {
+            restart.push("listener");
+        }
+        self.alert_mode = new.alert_mode;
+        self.timestamp_format = new.timestamp_format;
+        self.new_wormhole_state = new.new_wormhole_state;
+        self.auto_static_placeholders = new.auto_static_placeholders;
+        self.badge_glyphs = new.badge_glyphs;
+        self.export_format = new.export_format;
+        self.show_scan_estimate = new.show_scan_estimate;
+        self.short_type_labels = new.short_type_labels;
+        self.color_by_destination = new.color_by_destination;
+        self.focus_follows_paste = new.focus_follows_paste;
+        self.handle = new.handle;
+        self.read_only = new.read_only;
+        self.low_power = new.low_power;
+        self.compress_state = new.compress_state;
+        self.valuable_site_tier = new.valuable_site_tier;
+        self.min_scan_strength = new.min_scan_strength;
+        self.home_system = new.home_system;
+        self.sweep_empty_systems = new.sweep_empty_systems;
+        self.stale_branch_hours = new.stale_branch_hours;
+        self.report = new.report;
+        self.confirm = new.confirm;
+        restart
+    }
+
+    /// Check the config for inconsistencies that serde can't catch.
+    pub fn validate(&self) -> Result<()> {
+        let mut labels = Vec::new();
+        for app in &self.sso_apps {
+            if let Some(label) = &app.label {
+                if labels.contains(&label) {
+                    return Err(anyhow!("Duplicate SSO app label '{label}'"));
+                }
+                labels.push(label);
+            }
+        }
+        for (character, label) in &self.characters {
+            if !labels.contains(&label) {
+                return Err(anyhow!(
+                    "Character '{character}' uses unknown SSO app '{label}'"
+                ));
+            }
+        }
+        if let Some(tier) = self.valuable_site_tier {
+            if !(1..=3).contains(&tier) {
+                return Err(anyhow!("Valuable site tier must be 1-3, not {tier}"));
+            }
+        }
+        if let Some(strength) = self.min_scan_strength {
+            if !(0.0..=100.0).contains(&strength) {
+                return Err(anyhow!(
+                    "Minimum scan strength must be 0-100, not {strength}"
+                ));
+            }
+        }
+        if let Some(export) = &self.scheduled_export {
+            if export.interval_minutes == 0 {
+                return Err(anyhow!(
+                    "Scheduled export interval must be at least a minute"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Name local edits are attributed to: the handle, or else the first character by name.
+    pub fn identity(&self) -> String {
+        if let Some(handle) = self.handle.as_ref().filter(|h| !h.trim().is_empty()) {
+            return handle.trim().to_owned();
+        }
+        let mut characters: Vec<_> = self.characters.keys().collect();
+        characters.sort();
+        characters
+            .first()
+            .map(|character| character.to_string())
+            .unwrap_or_else(|| "local".to_owned())
+    }
+
+    /// All configured SSO apps, starting with the top-level one if it's set.
+    pub fn apps(&self) -> Vec<SsoApp> {
+        let mut apps = Vec::new();
+        if !self.sso_client_id.is_empty() {
+            apps.push(SsoApp {
+                label: None,
+                client_id: self.sso_client_id.clone(),
+                client_secret: self.sso_client_secret.clone(),
+                callback_url: self.sso_callback_url.clone(),
+                base_url: None,
+            });
+        }
+        apps.extend(self.sso_apps.iter().cloned());
+        apps
+    }
+
+    /// Whether any SSO app is configured, so ESI can be used.
+    pub fn has_credentials(&self) -> bool {
+        !self.apps().is_empty()
+    }
+
+    /// The SSO app to use for the character, falling back to the first app.
+    pub fn app_for_character(&self, character: &str) -> Option<SsoApp> {
+        let apps = self.apps();
+        match self.characters.get(character) {
+            Some(label) => apps
+                .into_iter()
+                .find(|app| app.label.as_ref() == Some(label)),
+            None => apps.into_iter().next(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        BadgeGlyphs, Config, Confirmations, NewWormholeState, ReportOptions, ScheduledFormat,
+    };
+    use crate::eve_data::{WormholeLife, WormholeMass};
+
+    const SINGLE_APP: &str = r#"
+sso_client_id = "abc"
+sso_client_secret = "def"
+sso_callback_url = "http://localhost:8080/callback"
+"#;
+
+    const MULTI_APP: &str = r#"
+[[sso_apps]]
+label = "main"
+client_id = "abc"
+client_secret = "def"
+callback_url = "http://localhost:8080/callback"
+
+[[sso_apps]]
+label = "test"
+client_id = "ghi"
+client_secret = "jkl"
+callback_url = "http://localhost:8080/callback"
+base_url = "https://esi.test/"
+
+[characters]
+"Some Scout" = "test"
+"#;
+
+    #[test]
+    fn test_config_single_app() {
+        let config: Config = toml::from_str(SINGLE_APP).unwrap();
+        config.validate().unwrap();
+
+        let apps = config.apps();
+        assert_eq!(apps.len(), 1);
+        assert_eq!(apps[0].client_id, "abc");
+        assert_eq!(config.app_for_character("Anyone").unwrap(), apps[0]);
+    }
+
+    #[test]
+    fn test_config_no_credentials() {
+        let config: Config = toml::from_str("alert_mode = \"bell\"").unwrap();
+        config.validate().unwrap();
+        assert!(!config.has_credentials());
+        assert!(!Config::default().has_credentials());
+
+        let config: Config = toml::from_str(SINGLE_APP).unwrap();
+        assert!(config.has_credentials());
+    }
+
+    #[test]
+    fn test_config_new_wormhole_state() {
+        let config: Config = toml::from_str(SINGLE_APP).unwrap();
+        assert_eq!(config.new_wormhole_state, NewWormholeState::Stable);
+        assert_eq!(
+            config.new_wormhole_state.wormhole().life,
+            WormholeLife::Stable
+        );
+
+        let text = format!("{SINGLE_APP}new_wormhole_state = \"unknown\"\n");
+        let config: Config = toml::from_str(&text).unwrap();
+        let wh = config.new_wormhole_state.wormhole();
+        assert_eq!(wh.life, WormholeLife::Unknown);
+        assert_eq!(wh.mass, WormholeMass::Unknown);
+    }
+
+    #[test]
+    fn test_config_badge_glyphs() {
+        let config: Config = toml::from_str(SINGLE_APP).unwrap();
+        assert_eq!(config.badge_glyphs, BadgeGlyphs::Unicode);
+
+        let text = format!("{SINGLE_APP}badge_glyphs = \"ascii\"\n");
+        let config: Config = toml::from_str(&text).unwrap();
+        assert_eq!(config.badge_glyphs, BadgeGlyphs::Ascii);
+        assert_eq!(config.badge_glyphs.eol(), "E");
+    }
+
+    #[test]
+    fn test_config_valuable_site_tier() {
+        let config: Config = toml::from_str(SINGLE_APP).unwrap();
+        assert_eq!(config.valuable_site_tier, None);
+
+        let text = format!("{SINGLE_APP}valuable_site_tier = 3\n");
+        let config: Config = toml::from_str(&text).unwrap();
+        config.validate().unwrap();
+        assert_eq!(config.valuable_site_tier, Some(3));
+
+        let text = format!("{SINGLE_APP}valuable_site_tier = 4\n");
+        let config: Config = toml::from_str(&text).unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_config_identity() {
+        let mut config: Config = toml::from_str(SINGLE_APP).unwrap();
+        assert_eq!(config.identity(), "local");
+
+        config
+            .characters
+            .insert("Zed".to_owned(), "main".to_owned());
+        config
+            .characters
+            .insert("Alice".to_owned(), "main".to_owned());
+        assert_eq!(config.identity(), "Alice");
+
+        config.handle = Some("scout1".to_owned());
+        assert_eq!(config.identity(), "scout1");
+    }
+
+    #[test]
+    fn test_config_min_scan_strength() {
+        let text = format!("{SINGLE_APP}min_scan_strength = 75.0\n");
+        let config: Config = toml::from_str(&text).unwrap();
+        config.validate().unwrap();
+        assert_eq!(config.min_scan_strength, Some(75.0));
+
+        let text = format!("{SINGLE_APP}min_scan_strength = 120.0\n");
+        let config: Config = toml::from_str(&text).unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_config_report_options() {
+        let config: Config = toml::from_str(SINGLE_APP).unwrap();
+        assert_eq!(config.report, ReportOptions::default());
+
+        let text = format!("{SINGLE_APP}\n[report]\nskip_empty = true\n");
+        let config: Config = toml::from_str(&text).unwrap();
+        assert!(config.report.skip_empty);
+        assert!(config.report.include_kspace);
+    }
+
+    #[test]
+    fn test_config_confirmations() {
+        let config: Config = toml::from_str(SINGLE_APP).unwrap();
+        assert_eq!(config.confirm, Confirmations::default());
+
+        let text = format!("{SINGLE_APP}\n[confirm]\ndelete = false\n");
+        let config: Config = toml::from_str(&text).unwrap();
+        assert!(!config.confirm.delete);
+        assert!(config.confirm.remove_systems);
+    }
+
+    #[test]
+    fn test_config_scheduled_export() {
+        let config: Config = toml::from_str(SINGLE_APP).unwrap();
+        assert_eq!(config.scheduled_export, None);
+
+        let text = format!(
+            "{SINGLE_APP}\n[scheduled_export]\nformat = \"markdown\"\npath = \"chain-%H%M.md\"\ninterval_minutes = 15\n"
+        );
+        let config: Config = toml::from_str(&text).unwrap();
+        config.validate().unwrap();
+        let export = config.scheduled_export.unwrap();
+        assert_eq!(export.format, ScheduledFormat::Markdown);
+        assert_eq!(export.interval_minutes, 15);
+
+        let text = text.replace("= 15", "= 0");
+        let config: Config = toml::from_str(&text).unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_config_apply_reload() {
+        let mut config = Config::parse(SINGLE_APP).unwrap();
+
+        let text = format!("{SINGLE_APP}badge_glyphs = \"ascii\"\nretention_hours = 24\n");
+        let restart = config.apply_reload(Config::parse(&text).unwrap());
+        assert_eq!(config.badge_glyphs, BadgeGlyphs::Ascii);
+        assert_eq!(config.retention_hours, None);
+        assert_eq!(restart, vec!["retention_hours"]);
+
+        let text = SINGLE_APP.replace("abc", "xyz");
+        let restart = config.apply_reload(Config::parse(&text).unwrap());
+        assert_eq!(config.badge_glyphs, BadgeGlyphs::Unicode);
+        assert_eq!(config.apps()[0].client_id, "abc");
+        assert_eq!(restart, vec!["SSO apps"]);
+
+        let text = format!("{SINGLE_APP}valuable_site_tier = 9\n");
+        assert!(Config::parse(&text).is_err());
+    }
+
+    #[test]
+    fn test_config_multi_app() {
+        let config: Config = toml::from_str(MULTI_APP).unwrap();
+        config.validate().unwrap();
+
+        assert_eq!(config.apps().len(), 2);
+        let app = config.app_for_character("Some Scout").unwrap();
+        assert_eq!(app.client_id, "ghi");
+        assert_eq!(app.base_url, Some("https://esi.test/".to_owned()));
+        let app = config.app_for_character("Someone Else").unwrap();
+        assert_eq!(app.client_id, "abc");
+    }
+
+    #[test]
+    fn test_config_duplicate_label() {
+        let text = MULTI_APP.replace(r#"label = "test""#, r#"label = "main""#);
+        let config: Config = toml::from_str(&text).unwrap();
+
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_config_character_missing_app() {
+        let text = MULTI_APP.replace(r#""Some Scout" = "test""#, r#""Some Scout" = "nope""#);
+        let config: Config = toml::from_str(&text).unwrap();
+
+        assert!(config.validate().is_err());
+    }
 }
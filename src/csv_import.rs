@@ -0,0 +1,343 @@
+use crate::eve_data::{find_system, ClipboardItem, Signature, SignatureType};
+use anyhow::{anyhow, Result};
+
+/// What a column of an imported table holds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Column {
+    Id,
+    Type,
+    Name,
+    Destination,
+    Ignored,
+}
+
+impl Column {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Column::Id => "ID",
+            Column::Type => "Type",
+            Column::Name => "Name",
+            Column::Destination => "Destination",
+            Column::Ignored => "-",
+        }
+    }
+
+    /// The next choice when adjusting the mapping.
+    pub fn next(&self) -> Self {
+        match self {
+            Column::Id => Column::Type,
+            Column::Type => Column::Name,
+            Column::Name => Column::Destination,
+            Column::Destination => Column::Ignored,
+            Column::Ignored => Column::Id,
+        }
+    }
+
+    /// The column a header names, e.g. "Sig ID" or "Leads to".
+    fn from_header(header: &str) -> Option<Self> {
+        match header.trim().to_lowercase().as_str() {
+            "id" | "sig" | "sig id" | "signature" | "signature id" => Some(Column::Id),
+            "type" | "group" | "kind" => Some(Column::Type),
+            "name" | "site" | "site name" => Some(Column::Name),
+            "destination" | "dest" | "leads to" | "to" => Some(Column::Destination),
+            _ => None,
+        }
+    }
+}
+
+/// Rows of a comma- or tab-separated table, such as a spreadsheet paste.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Table {
+    /// The header row, if the first row named any known columns.
+    pub headers: Option<Vec<String>>,
+    pub rows: Vec<Vec<String>>,
+}
+
+/// Split delimited text into rows of fields.
+///
+/// Fields may be quoted, with `""` for a quote and delimiters or line breaks
+/// inside the quotes. A leading byte order mark and blank lines are skipped.
+fn split_rows(text: &str, delimiter: char) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.trim_start_matches('\u{feff}').chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() => quoted = true,
+            '\r' => {}
+            c if quoted => field.push(c),
+            c if c == delimiter => row.push(std::mem::take(&mut field)),
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            c => field.push(c),
+        }
+    }
+    row.push(field);
+    rows.push(row);
+    rows.retain(|row| row.iter().any(|field| !field.trim().is_empty()));
+    rows
+}
+
+/// Parse comma- or tab-separated text, whichever the first line uses.
+pub fn parse(text: &str) -> Result<Table> {
+    let text = text.trim_start_matches('\u{feff}');
+    let first = text
+        .lines()
+        .find(|line| !line.trim().is_empty())
+        .ok_or_else(|| anyhow!("Nothing to import"))?;
+    let delimiter = if first.contains('\t') { '\t' } else { ',' };
+    let mut rows = split_rows(text, delimiter);
+    let headers = if rows[0]
+        .iter()
+        .any(|field| Column::from_header(field).is_some())
+    {
+        Some(rows.remove(0))
+    } else {
+        None
+    };
+    Ok(Table { headers, rows })
+}
+
+/// Whether pasted text looks like a table with a header naming an ID column.
+///
+/// Scanner results are tab-separated too, but don't have a header.
+pub fn is_table(text: &str) -> bool {
+    parse(text).is_ok_and(|table| {
+        table.headers.is_some_and(|headers| {
+            headers
+                .iter()
+                .any(|header| Column::from_header(header) == Some(Column::Id))
+        })
+    })
+}
+
+/// Whether a field looks like a signature ID, e.g. "ABC-123".
+fn is_id(field: &str) -> bool {
+    field
+        .trim()
+        .split_once('-')
+        .is_some_and(|(prefix, number)| {
+            !prefix.is_empty()
+                && !number.is_empty()
+                && prefix.chars().all(|c| c.is_ascii_alphabetic())
+                && number.chars().all(|c| c.is_ascii_digit())
+        })
+}
+
+/// Guess what each column holds from the headers, or find the IDs by their format.
+pub fn detect_columns(table: &Table) -> Vec<Column> {
+    let width = table
+        .headers
+        .iter()
+        .chain(&table.rows)
+        .map(Vec::len)
+        .max()
+        .unwrap_or_default();
+    let mut columns = vec![Column::Ignored; width];
+    match &table.headers {
+        Some(headers) => {
+            for (column, header) in columns.iter_mut().zip(headers) {
+                if let Some(detected) = Column::from_header(header) {
+                    *column = detected;
+                }
+            }
+        }
+        None => {
+            let id_column = (0..width).find(|&index| {
+                table
+                    .rows
+                    .iter()
+                    .all(|row| row.get(index).is_some_and(|field| is_id(field)))
+            });
+            if let Some(index) = id_column {
+                columns[index] = Column::Id;
+            }
+        }
+    }
+    columns
+}
+
+/// The signature type's name in the scanner's words, from a spreadsheet's, e.g. "WH" or "relic site".
+fn scanner_type(field: &str) -> &'static str {
+    let lower = field.trim().to_lowercase();
+    match lower.trim_end_matches(" site") {
+        "wormhole" | "wh" => "Wormhole",
+        "combat" | "c" => "Combat",
+        "ore" | "o" => "Ore",
+        "data" | "d" => "Data",
+        "relic" | "r" => "Relic",
+        "gas" | "g" => "Gas",
+        _ => "",
+    }
+}
+
+/// Convert rows to signatures with the given column mapping.
+///
+/// Returns the signatures and how many rows were skipped for not having a signature ID.
+pub fn to_signatures(table: &Table, columns: &[Column]) -> (Vec<Signature>, usize) {
+    let field = |row: &[String], wanted: Column| -> String {
+        columns
+            .iter()
+            .position(|column| *column == wanted)
+            .and_then(|index| row.get(index))
+            .map(|field| field.trim().to_owned())
+            .unwrap_or_default()
+    };
+    let mut signatures = Vec::new();
+    let mut skipped = 0;
+    for row in &table.rows {
+        let id = field(row, Column::Id).to_uppercase();
+        if !is_id(&id) {
+            skipped += 1;
+            continue;
+        }
+        let item = ClipboardItem::new(
+            id,
+            scanner_type(&field(row, Column::Type)),
+            field(row, Column::Name),
+        );
+        let (identifier, mut signature_type) = (&item).into();
+        if let SignatureType::Wormhole(wh) = &mut signature_type {
+            wh.destination = find_system(&field(row, Column::Destination)).map(str::to_owned);
+        }
+        signatures.push(Signature::new(
+            &identifier.id,
+            &identifier.number,
+            signature_type,
+        ));
+    }
+    (signatures, skipped)
+}
+
+/// A table being imported, with its column mapping being adjusted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvImport {
+    pub table: Table,
+    pub columns: Vec<Column>,
+    /// Index of the column being adjusted.
+    pub selected: usize,
+}
+
+impl CsvImport {
+    pub fn new(table: Table) -> Self {
+        let columns = detect_columns(&table);
+        Self {
+            table,
+            columns,
+            selected: 0,
+        }
+    }
+
+    /// Lines describing the mapping and what importing with it would give.
+    pub fn preview(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        for (index, column) in self.columns.iter().enumerate() {
+            let name = match &self.table.headers {
+                Some(headers) => headers.get(index).cloned().unwrap_or_default(),
+                None => format!("Column {}", index + 1),
+            };
+            let marker = if index == self.selected { ">>" } else { "  " };
+            lines.push(format!("{marker} {name}: {}", column.label()));
+        }
+        let (signatures, skipped) = to_signatures(&self.table, &self.columns);
+        lines.push(String::new());
+        lines.push(format!(
+            "{} signature(s) to import, {skipped} row(s) without an ID skipped",
+            signatures.len()
+        ));
+        lines.extend(signatures.iter().take(5).map(|sig| sig.to_string()));
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{detect_columns, is_table, parse, to_signatures, Column, CsvImport};
+    use crate::eve_data::{SignatureType, SignatureWormhole};
+
+    const SHEETS_EXPORT: &str = include_str!("../tests/fixtures/sheets_export.csv");
+
+    #[test]
+    fn test_parse_sheets_export() {
+        let table = parse(SHEETS_EXPORT).unwrap();
+        assert_eq!(
+            table.headers,
+            Some(vec![
+                "Sig ID".to_owned(),
+                "Notes".to_owned(),
+                "Type".to_owned(),
+                "Name".to_owned(),
+                "Leads to".to_owned(),
+            ])
+        );
+        // blank lines are skipped, quoted delimiters and line breaks kept
+        assert_eq!(table.rows.len(), 4);
+        assert_eq!(table.rows[0][1], "frig hole, \"careful\"");
+        assert_eq!(table.rows[2][1], "two\nlines");
+
+        let columns = detect_columns(&table);
+        assert_eq!(
+            columns,
+            vec![
+                Column::Id,
+                Column::Ignored,
+                Column::Type,
+                Column::Name,
+                Column::Destination
+            ]
+        );
+        let (sigs, skipped) = to_signatures(&table, &columns);
+        assert_eq!(skipped, 1);
+        assert_eq!(sigs.len(), 3);
+        assert_eq!(sigs[0].identifier.to_string(), "ABC-123");
+        assert_eq!(
+            sigs[0].signature_type,
+            SignatureType::Wormhole(SignatureWormhole {
+                destination: Some("Jita".to_owned()),
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            sigs[1].signature_type,
+            SignatureType::Relic(Some("Ruined Angel Temple Site".to_owned()))
+        );
+        assert_eq!(sigs[2].signature_type, SignatureType::Unknown);
+    }
+
+    #[test]
+    fn test_parse_without_headers() {
+        let table = parse("Data Site\tdef-456\r\nGas\tGHI-789\r\n").unwrap();
+        assert_eq!(table.headers, None);
+        let mut import = CsvImport::new(table);
+        assert_eq!(import.columns, vec![Column::Ignored, Column::Id]);
+
+        // the user marks the first column as the type
+        import.columns[0] = Column::Ignored.next().next();
+        let (sigs, _) = to_signatures(&import.table, &import.columns);
+        assert_eq!(sigs[0].identifier.to_string(), "DEF-456");
+        assert_eq!(sigs[1].signature_type, SignatureType::Gas(None));
+        assert!(import
+            .preview()
+            .iter()
+            .any(|line| line == "2 signature(s) to import, 0 row(s) without an ID skipped"));
+    }
+
+    #[test]
+    fn test_is_table() {
+        assert!(is_table(SHEETS_EXPORT));
+        assert!(is_table("ID,Type\nABC-123,Wormhole"));
+        assert!(!is_table(
+            "ABC-123\tCosmic Signature\tWormhole\tUnstable Wormhole\t100.0%\t1 AU"
+        ));
+        assert!(!is_table(""));
+    }
+}
@@ -0,0 +1,294 @@
+use crate::{
+    eve_data::{SystemData, WormholeInfo, ALL_SYSTEMS, WORMHOLE_TYPES},
+    state::write_atomic,
+};
+use anyhow::{anyhow, Result};
+use chrono::NaiveDate;
+use log::{debug, info, warn};
+use serde::de::DeserializeOwned;
+use std::{collections::HashMap, fs, path::Path, time::Duration};
+
+/// Directory updated static data files are saved to, preferred to the bundled ones.
+pub const DATA_DIR: &str = "data";
+/// Where `update-data` downloads the data files from unless `data_url` is set in the config.
+///
+/// This is the project's own mirror of the data: the `static` directory on the main branch,
+/// which is regenerated from Tripwire's exports and dated in `data_version.txt`.
+pub const DEFAULT_DATA_URL: &str = "https://raw.githubusercontent.com/celeo/evemapping/main/static";
+/// Seconds to wait for each download.
+const DOWNLOAD_TIMEOUT: u64 = 30;
+/// The data files `update-data` saves.
+const DATA_FILES: [&str; 2] = ["systems.json", "wormhole_types.json"];
+/// File published alongside the data files giving the date of the data, e.g. "2024-05-01".
+const VERSION_FILE: &str = "data_version.txt";
+
+fn parse_version(text: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(text.trim(), "%Y-%m-%d")
+        .map_err(|e| anyhow!("{VERSION_FILE} isn't a date: {e}"))
+}
+
+/// Date of the bundled data, from `static/data_version.txt`.
+fn embedded_version() -> NaiveDate {
+    parse_version(env!("EMBEDDED_DATA_VERSION")).unwrap_or(NaiveDate::MIN)
+}
+
+/// Date of the data in the directory, or `None` if it isn't dated.
+fn downloaded_version(dir: &Path) -> Option<NaiveDate> {
+    parse_version(&fs::read_to_string(dir.join(VERSION_FILE)).ok()?).ok()
+}
+
+/// Whether the data in the directory is dated later than the bundled data.
+fn is_newer(dir: &Path, embedded: NaiveDate) -> bool {
+    downloaded_version(dir).is_some_and(|version| version > embedded)
+}
+
+/// Data from an updated file, if it's newer than the bundled data and can be read.
+fn load_newer<T: DeserializeOwned>(
+    dir: &Path,
+    file: &str,
+    embedded: NaiveDate,
+) -> Option<HashMap<String, T>> {
+    let path = dir.join(file);
+    if !path.exists() {
+        return None;
+    }
+    if !is_newer(dir, embedded) {
+        debug!("{} isn't newer than the bundled data", path.display());
+        return None;
+    }
+    let text = fs::read_to_string(&path).ok()?;
+    match serde_json::from_str(&text) {
+        Ok(data) => {
+            info!("Using static data from {}", path.display());
+            Some(data)
+        }
+        Err(e) => {
+            warn!("Could not read {}, using bundled data: {e}", path.display());
+            None
+        }
+    }
+}
+
+/// Data from the updated file of the name, e.g. "systems.json", if there's a usable one.
+pub fn user_data<T: DeserializeOwned>(file: &str) -> Option<HashMap<String, T>> {
+    load_newer(Path::new(DATA_DIR), file, embedded_version())
+}
+
+/// Data files in the data directory that aren't used because they aren't newer than the bundled data.
+pub fn outdated_user_data() -> Vec<String> {
+    let dir = Path::new(DATA_DIR);
+    if is_newer(dir, embedded_version()) {
+        return Vec::new();
+    }
+    DATA_FILES
+        .iter()
+        .map(|file| dir.join(file))
+        .filter(|path| path.exists())
+        .map(|path| path.display().to_string())
+        .collect()
+}
+
+/// Which static data is in use and the date of it, e.g. "bundled, dated 2024-05-01".
+pub fn data_version() -> String {
+    let dir = Path::new(DATA_DIR);
+    match downloaded_version(dir) {
+        Some(version) if version > embedded_version() => {
+            format!("from {DATA_DIR}, dated {version}")
+        }
+        _ => format!("bundled, dated {}", embedded_version()),
+    }
+}
+
+/// Entries added, changed and removed between two versions of a data file.
+#[derive(Debug, Default, PartialEq)]
+pub struct DataDiff {
+    pub added: usize,
+    pub changed: usize,
+    pub removed: usize,
+}
+
+impl DataDiff {
+    pub fn new<T: PartialEq>(old: &HashMap<String, T>, new: &HashMap<String, T>) -> Self {
+        let mut diff = Self::default();
+        for (key, value) in new {
+            match old.get(key) {
+                None => diff.added += 1,
+                Some(existing) if existing != value => diff.changed += 1,
+                Some(_) => {}
+            }
+        }
+        diff.removed = old.keys().filter(|key| !new.contains_key(*key)).count();
+        diff
+    }
+
+    /// Summary for the update report, e.g. "2 added, 5 changed, 0 removed".
+    pub fn label(&self) -> String {
+        format!(
+            "{} added, {} changed, {} removed",
+            self.added, self.changed, self.removed
+        )
+    }
+}
+
+/// Check downloaded data can be used: nothing's empty and every static is a known wormhole type.
+pub fn validate(
+    systems: &HashMap<String, SystemData>,
+    wormholes: &HashMap<String, WormholeInfo>,
+) -> Result<()> {
+    if systems.is_empty() || wormholes.is_empty() {
+        return Err(anyhow!("Downloaded data is empty"));
+    }
+    let mut unknown: Vec<String> = systems
+        .iter()
+        .flat_map(|(system, data)| {
+            data.statics
+                .iter()
+                .filter(|wh_type| !wormholes.contains_key(*wh_type))
+                .map(move |wh_type| format!("{wh_type} ({system})"))
+        })
+        .collect();
+    if !unknown.is_empty() {
+        unknown.sort();
+        return Err(anyhow!(
+            "Statics missing from the wormhole types: {}",
+            unknown.join(", ")
+        ));
+    }
+    Ok(())
+}
+
+async fn download(client: &reqwest::Client, url: &str) -> Result<String> {
+    debug!("Downloading {url}");
+    let response = client.get(url).send().await?.error_for_status()?;
+    Ok(response.text().await?)
+}
+
+fn parse<T: DeserializeOwned>(file: &str, text: &str) -> Result<HashMap<String, T>> {
+    serde_json::from_str(text).map_err(|e| anyhow!("{file} isn't in the expected format: {e}"))
+}
+
+/// Download fresh data files, check them, and save them to the data directory.
+///
+/// Nothing is saved unless both files download and pass the checks. Returns
+/// a summary of the changes from the data in use, one line per file.
+pub async fn update(base_url: &str) -> Result<Vec<String>> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(DOWNLOAD_TIMEOUT))
+        .build()?;
+    let base_url = base_url.trim_end_matches('/');
+    let version_text = download(&client, &format!("{base_url}/{VERSION_FILE}")).await?;
+    let version = parse_version(&version_text)?;
+    if version <= embedded_version() {
+        return Err(anyhow!(
+            "The data at {base_url} is dated {version}, no newer than the bundled data"
+        ));
+    }
+    let systems_text = download(&client, &format!("{base_url}/systems.json")).await?;
+    let wormholes_text = download(&client, &format!("{base_url}/wormhole_types.json")).await?;
+    let systems: HashMap<String, SystemData> = parse("systems.json", &systems_text)?;
+    let wormholes: HashMap<String, WormholeInfo> = parse("wormhole_types.json", &wormholes_text)?;
+    validate(&systems, &wormholes)?;
+
+    let report = vec![
+        format!("Data dated {version}"),
+        format!(
+            "systems.json: {}",
+            DataDiff::new(&ALL_SYSTEMS, &systems).label()
+        ),
+        format!(
+            "wormhole_types.json: {}",
+            DataDiff::new(&WORMHOLE_TYPES, &wormholes).label()
+        ),
+    ];
+    let dir = Path::new(DATA_DIR);
+    fs::create_dir_all(dir)?;
+    write_atomic(&dir.join("systems.json"), systems_text.as_bytes())?;
+    write_atomic(&dir.join("wormhole_types.json"), wormholes_text.as_bytes())?;
+    // written last, so the new files are only used once they're all there
+    write_atomic(&dir.join(VERSION_FILE), version.to_string().as_bytes())?;
+    info!("Updated static data in {DATA_DIR}");
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{embedded_version, is_newer, load_newer, parse_version, validate, DataDiff};
+    use crate::eve_data::{SystemData, WormholeInfo};
+    use chrono::NaiveDate;
+    use std::collections::HashMap;
+
+    fn system(statics: &[&str]) -> SystemData {
+        SystemData {
+            security: -1.0,
+            class: Some(2),
+            effect: None,
+            statics: statics.iter().map(|s| s.to_string()).collect(),
+            region: None,
+            constellation: None,
+        }
+    }
+
+    fn wormhole() -> WormholeInfo {
+        WormholeInfo {
+            life: "16 Hours".to_owned(),
+            from: Vec::new(),
+            leads_to: "High-Sec".to_owned(),
+            mass: 2_000_000_000,
+            jump: 300_000_000,
+        }
+    }
+
+    #[test]
+    fn test_data_diff() {
+        let old = HashMap::from([("a", 1), ("b", 2), ("c", 3)].map(|(k, v)| (k.to_owned(), v)));
+        let new = HashMap::from([("a", 1), ("b", 5), ("d", 4)].map(|(k, v)| (k.to_owned(), v)));
+        assert_eq!(
+            DataDiff::new(&old, &new).label(),
+            "1 added, 1 changed, 1 removed"
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        let wormholes = HashMap::from([("B274".to_owned(), wormhole())]);
+        let mut systems = HashMap::from([("J173213".to_owned(), system(&["B274"]))]);
+        assert!(validate(&systems, &wormholes).is_ok());
+        assert!(validate(&systems, &HashMap::new()).is_err());
+
+        systems.insert("J105443".to_owned(), system(&["B274", "Z999"]));
+        let e = validate(&systems, &wormholes).unwrap_err().to_string();
+        assert!(e.ends_with("Z999 (J105443)"));
+    }
+
+    #[test]
+    fn test_load_newer() {
+        let dir = std::env::temp_dir().join(format!("evemapping-data-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("wormhole_types.json");
+        std::fs::write(&path, r#"{"B274": {"life": "24 Hours", "from": [], "leadsTo": "High-Sec", "mass": 2000000000, "jump": 300000000}}"#).unwrap();
+        let file = "wormhole_types.json";
+        let before = NaiveDate::from_ymd_opt(2024, 4, 30).unwrap();
+        let after = NaiveDate::from_ymd_opt(2024, 5, 2).unwrap();
+
+        // undated data isn't used, however new the file is
+        assert!(!is_newer(&dir, before));
+        assert!(load_newer::<WormholeInfo>(&dir, file, before).is_none());
+
+        std::fs::write(dir.join("data_version.txt"), "2024-05-01\n").unwrap();
+        let data = load_newer::<WormholeInfo>(&dir, file, before).unwrap();
+        assert_eq!(data["B274"].life, "24 Hours");
+        assert!(load_newer::<WormholeInfo>(&dir, file, after).is_none());
+        assert!(load_newer::<WormholeInfo>(&dir, "systems.json", before).is_none());
+
+        std::fs::write(&path, "not json").unwrap();
+        assert!(load_newer::<WormholeInfo>(&dir, file, before).is_none());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_data_version() {
+        assert!(parse_version(env!("EMBEDDED_DATA_VERSION")).is_ok());
+        assert!(embedded_version() > NaiveDate::MIN);
+        assert!(parse_version("downloaded 2024-05-01").is_err());
+    }
+}
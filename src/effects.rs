@@ -0,0 +1,68 @@
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Strength of system effects in each class, C1 through C6, relative to C6.
+const CLASS_SCALE: [f64; 6] = [0.3, 0.44, 0.58, 0.72, 0.86, 1.0];
+
+/// A single modifier applied by a system effect.
+#[derive(Deserialize)]
+pub struct EffectModifier {
+    pub modifier: String,
+    /// Percentage change in a C6 system
+    pub max: i32,
+}
+
+/// Known system effect names to their modifiers.
+pub static EFFECT_MODIFIERS: Lazy<HashMap<String, Vec<EffectModifier>>> = Lazy::new(|| {
+    let raw = include_str!("../static/system_effects.json");
+    serde_json::from_str(raw).unwrap()
+});
+
+/// The effect's modifiers as percentages scaled for a system of the class.
+///
+/// Classes past C6 (Thera, shattered, and drifter systems) get full strength.
+pub fn scaled_modifiers(effect: &str, class: u8) -> Vec<(&'static str, i32)> {
+    let Some(modifiers) = EFFECT_MODIFIERS.get(effect) else {
+        return Vec::new();
+    };
+    let scale = CLASS_SCALE[(class.clamp(1, 6) - 1) as usize];
+    modifiers
+        .iter()
+        .map(|m| {
+            let value = (m.max as f64 * scale).round() as i32;
+            (m.modifier.as_str(), value)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{scaled_modifiers, EFFECT_MODIFIERS};
+    use crate::eve_data::ALL_SYSTEMS;
+
+    #[test]
+    fn test_effect_modifiers_consistent() {
+        for data in ALL_SYSTEMS.values() {
+            if let Some(effect) = &data.effect {
+                assert!(EFFECT_MODIFIERS.contains_key(effect), "{effect}");
+            }
+        }
+        for (effect, modifiers) in EFFECT_MODIFIERS.iter() {
+            assert!(!modifiers.is_empty(), "{effect}");
+            assert!(modifiers.iter().all(|m| m.max != 0), "{effect}");
+        }
+    }
+
+    #[test]
+    fn test_scaled_modifiers() {
+        let c1 = scaled_modifiers("Pulsar", 1);
+        let c5 = scaled_modifiers("Pulsar", 5);
+
+        assert!(c1.contains(&("Shield HP", 30)));
+        assert!(c1.contains(&("Armor resistances", -15)));
+        assert!(c5.contains(&("Shield HP", 86)));
+        assert!(c5.contains(&("Armor resistances", -43)));
+        assert!(scaled_modifiers("Not An Effect", 3).is_empty());
+    }
+}
@@ -1,15 +1,22 @@
 #![allow(unused)]
 
+use crate::{
+    data_update::user_data,
+    sites::{danger_warning, gas_value_marker, value_marker},
+};
 use anyhow::Result;
-use log::info;
+use chrono::{DateTime, Utc};
+use log::{debug, info};
 use once_cell::sync::Lazy;
-use serde::Deserialize;
-use std::{collections::HashMap, fmt};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt, time::Instant};
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum WormholeLife {
     Stable,
     EndOfLife,
+    /// Not yet checked
+    Unknown,
 }
 
 impl WormholeLife {
@@ -17,15 +24,18 @@ impl WormholeLife {
         match self {
             WormholeLife::Stable => "Stable",
             WormholeLife::EndOfLife => "EOL",
+            WormholeLife::Unknown => "?",
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum WormholeMass {
     Stable,
     Destab,
     Critical,
+    /// Not yet checked
+    Unknown,
 }
 
 impl WormholeMass {
@@ -34,11 +44,12 @@ impl WormholeMass {
             WormholeMass::Stable => "Stable",
             WormholeMass::Destab => "Destab",
             WormholeMass::Critical => "Critical",
+            WormholeMass::Unknown => "?",
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Default)]
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
 pub struct SignatureId {
     pub id: String,
     pub number: String,
@@ -59,12 +70,76 @@ impl SignatureId {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+/// What a wormhole is to the system it's in.
+///
+/// Statics respawn as soon as they're rolled; other holes don't.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum ConnectionKind {
+    #[default]
+    Unknown,
+    Static,
+    Wandering,
+    IncomingK162,
+}
+
+impl ConnectionKind {
+    /// Short badge for lists, or an empty string when unknown.
+    pub fn badge(&self) -> &'static str {
+        match self {
+            ConnectionKind::Unknown => "",
+            ConnectionKind::Static => "[S]",
+            ConnectionKind::Wandering => "[W]",
+            ConnectionKind::IncomingK162 => "[K]",
+        }
+    }
+
+    /// Likely kind of a hole of the type in a system with the statics.
+    pub fn suggest(wh_type: &str, statics: &[String]) -> Self {
+        if wh_type == "K162" {
+            ConnectionKind::IncomingK162
+        } else if statics.iter().any(|s| s == wh_type) {
+            ConnectionKind::Static
+        } else {
+            ConnectionKind::Wandering
+        }
+    }
+}
+
+/// Which side of a wormhole has been found.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WormholeDirection {
+    /// Found from this side, leading out
+    Outbound,
+    /// A K162: the exit of a hole opened from the other side
+    Inbound,
+    /// Linked with its signature on the other side
+    Bidirectional,
+}
+
+impl WormholeDirection {
+    pub fn glyph(&self) -> &'static str {
+        match self {
+            WormholeDirection::Outbound => "→",
+            WormholeDirection::Inbound => "←",
+            WormholeDirection::Bidirectional => "↔",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SignatureWormhole {
     pub wh_type: Option<String>,
     pub destination: Option<String>,
     pub life: WormholeLife,
     pub mass: WormholeMass,
+    /// For a K162, the wormhole type on the other side once it's been scanned.
+    #[serde(default)]
+    pub origin_type: Option<String>,
+    #[serde(default)]
+    pub connection_kind: ConnectionKind,
+    /// Mass logged through the hole while rolling it, in kg.
+    #[serde(default)]
+    pub mass_used: u64,
 }
 
 impl Default for SignatureWormhole {
@@ -74,11 +149,27 @@ impl Default for SignatureWormhole {
             destination: None,
             life: WormholeLife::Stable,
             mass: WormholeMass::Stable,
+            origin_type: None,
+            connection_kind: ConnectionKind::Unknown,
+            mass_used: 0,
         }
     }
 }
 
 impl SignatureWormhole {
+    /// Direction of the hole, given whether it's linked with its other side.
+    pub fn direction(&self, linked: bool) -> WormholeDirection {
+        if linked {
+            WormholeDirection::Bidirectional
+        } else if self.wh_type.as_deref() == Some("K162")
+            || self.connection_kind == ConnectionKind::IncomingK162
+        {
+            WormholeDirection::Inbound
+        } else {
+            WormholeDirection::Outbound
+        }
+    }
+
     pub fn new(
         wh_type: Option<String>,
         destination: Option<String>,
@@ -90,11 +181,53 @@ impl SignatureWormhole {
             destination,
             life,
             mass,
+            origin_type: None,
+            connection_kind: ConnectionKind::Unknown,
+            mass_used: 0,
+        }
+    }
+
+    pub fn is_k162(&self) -> bool {
+        self.wh_type.as_deref() == Some("K162")
+    }
+
+    /// Static info for the hole, using the resolved origin type for a K162.
+    pub fn info(&self) -> Option<&'static WormholeInfo> {
+        let wh_type = if self.is_k162() {
+            self.origin_type.as_ref()?
+        } else {
+            self.wh_type.as_ref()?
+        };
+        WORMHOLE_TYPES.get(wh_type)
+    }
+
+    /// Record the type of the other side of a K162.
+    ///
+    /// Returns false if this isn't a K162 or the type isn't known.
+    pub fn resolve_origin(&mut self, origin_type: &str) -> bool {
+        if !self.is_k162() || !WORMHOLE_TYPES.contains_key(origin_type) {
+            return false;
+        }
+        self.origin_type = Some(origin_type.to_owned());
+        true
+    }
+
+    /// Wormhole type for display, including what's known about a K162's origin.
+    pub fn type_label(&self) -> String {
+        let Some(wh_type) = &self.wh_type else {
+            return "?".to_owned();
+        };
+        let size = self.info().map(|info| info.size_class());
+        match (self.is_k162(), &self.origin_type, size) {
+            (true, None, _) => "K162 → ? (unknown origin)".to_owned(),
+            (true, Some(origin), Some(size)) => format!("K162 ← {origin} ({size})"),
+            (false, _, Some(size)) => format!("{wh_type} ({size})"),
+            _ => wh_type.clone(),
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Default)]
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
 pub enum SignatureType {
     #[default]
     Unknown,
@@ -111,53 +244,106 @@ pub enum SignatureType {
     Gas(Option<String>),
 }
 
+/// Plain description, e.g. "Combat Perimeter Camp", without any padding.
 impl fmt::Display for SignatureType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Self::Unknown => {
-                write!(f, "Unknown")
-            }
-            Self::Combat(name) => match name {
-                Some(n) => write!(f, "Combat   {n}"),
-                None => write!(f, "Combat"),
-            },
-            Self::Wormhole(data) => {
-                write!(
-                    f,
-                    "WH       {} -> {}      {}      {}",
-                    match data.wh_type.as_ref() {
-                        Some(s) => s,
-                        None => "?",
-                    },
-                    match data.destination.as_ref() {
-                        Some(d) => d,
-                        None => "?",
-                    },
-                    data.life.as_str(),
-                    data.mass.as_str(),
-                )
-            }
-            Self::Ore(name) => match name {
-                Some(n) => write!(f, "Ore      {n}"),
-                None => write!(f, "Ore"),
-            },
-            Self::Data(name) => match name {
-                Some(n) => write!(f, "Data     {n}"),
-                None => write!(f, "Data"),
-            },
-            Self::Relic(name) => match name {
-                Some(n) => write!(f, "Relic    {n}"),
-                None => write!(f, "Relic"),
-            },
-            Self::Gas(name) => match name {
-                Some(n) => write!(f, "Gas      {n}"),
-                None => write!(f, "Gas"),
-            },
+        match self.details() {
+            Some(details) => write!(f, "{} {details}", self.kind()),
+            None => write!(f, "{}", self.kind()),
+        }
+    }
+}
+
+/// Widest label returned by `SignatureType::kind`.
+pub const KIND_WIDTH: usize = 7;
+
+/// A signature type with its kind padded, so details line up in a column.
+pub struct AlignedType<'a>(&'a SignatureType);
+
+impl fmt::Display for AlignedType<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0.details() {
+            Some(details) => write!(f, "{:<KIND_WIDTH$} {details}", self.0.kind()),
+            None => write!(f, "{}", self.0.kind()),
         }
     }
 }
 
 impl SignatureType {
+    /// Short label for the kind of signature.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Unknown => "Unknown",
+            Self::Combat(_) => "Combat",
+            Self::Wormhole(_) => "WH",
+            Self::Ore(_) => "Ore",
+            Self::Data(_) => "Data",
+            Self::Relic(_) => "Relic",
+            Self::Gas(_) => "Gas",
+        }
+    }
+
+    /// Full name of the kind of signature, for the signature list.
+    pub fn full_label(&self) -> &'static str {
+        match self {
+            Self::Wormhole(_) => "Wormhole",
+            _ => self.kind(),
+        }
+    }
+
+    /// Abbreviated kind of signature, for narrow terminals.
+    pub fn short_label(&self) -> &'static str {
+        match self {
+            Self::Unknown => "?",
+            Self::Combat(_) => "C",
+            Self::Wormhole(_) => "WH",
+            Self::Ore(_) => "O",
+            Self::Data(_) => "D",
+            Self::Relic(_) => "R",
+            Self::Gas(_) => "G",
+        }
+    }
+
+    /// Everything known beyond the kind: the site name, or where a wormhole leads.
+    fn details(&self) -> Option<String> {
+        match self {
+            Self::Unknown => None,
+            Self::Wormhole(data) => Some(format!(
+                "{} -> {} {}/{}",
+                data.type_label(),
+                data.destination.as_deref().unwrap_or("?"),
+                data.life.as_str(),
+                data.mass.as_str(),
+            )),
+            _ => self.name().map(str::to_owned),
+        }
+    }
+
+    /// Display with the kind padded to `KIND_WIDTH`, for aligned columns.
+    pub fn aligned(&self) -> AlignedType<'_> {
+        AlignedType(self)
+    }
+
+    /// The site name, for the site types that have one.
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            Self::Unknown | Self::Wormhole(_) => None,
+            Self::Combat(name)
+            | Self::Ore(name)
+            | Self::Data(name)
+            | Self::Relic(name)
+            | Self::Gas(name) => name.as_deref(),
+        }
+    }
+
+    /// Warning for dangerous combat sites in a system of the class.
+    pub fn danger_warning(&self, class: Option<u8>) -> Option<&'static str> {
+        match self {
+            Self::Combat(Some(name)) => danger_warning(name, class),
+            _ => None,
+        }
+    }
+
     pub fn has_name(&self) -> bool {
         match self {
             Self::Unknown => false,
@@ -171,11 +357,73 @@ impl SignatureType {
     }
 }
 
+/// Widest a site name or destination can be in the signature list.
+pub const NAME_WIDTH: usize = 40;
+
+/// Shorten the text to fit in the width, marking that it was cut off.
+pub fn truncate_with_ellipsis(s: &str, width: usize) -> String {
+    if s.chars().count() <= width {
+        return s.to_owned();
+    }
+    if width == 0 {
+        return String::new();
+    }
+    let mut truncated: String = s.chars().take(width - 1).collect();
+    truncated.push('…');
+    truncated
+}
+
 /// Represents a scannable item in space.
-#[derive(Debug, Clone, PartialEq, Default)]
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
 pub struct Signature {
     pub identifier: SignatureId,
     pub signature_type: SignatureType,
+    /// When the signature was first recorded.
+    #[serde(default = "Utc::now")]
+    pub created: DateTime<Utc>,
+    /// Known junk, hidden from the list but still matched when pasting.
+    #[serde(default)]
+    pub ignored: bool,
+    /// Scan strength in percent from each paste where it changed, oldest first.
+    #[serde(default)]
+    pub scan_strengths: Vec<f32>,
+    /// A cosmic anomaly, which can be warped to without probing.
+    #[serde(default)]
+    pub anomaly: bool,
+    /// Who last changed the signature, and when.
+    #[serde(default)]
+    pub last_edit: Option<Attribution>,
+    /// Where the signature came from; `None` for ones saved before this was tracked.
+    #[serde(default)]
+    pub provenance: Option<Provenance>,
+}
+
+/// Where a signature came from, for judging whether it's safe to act on.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Provenance {
+    /// In one of my own scanner pastes.
+    Scanned,
+    /// From a teammate or another mapper.
+    Imported,
+    /// Added by hand, such as a placeholder.
+    Manual,
+}
+
+impl Provenance {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Provenance::Scanned => "scanned by me",
+            Provenance::Imported => "imported",
+            Provenance::Manual => "added by hand",
+        }
+    }
+}
+
+/// Who made a change, and when.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Attribution {
+    pub by: String,
+    pub at: DateTime<Utc>,
 }
 
 impl fmt::Display for Signature {
@@ -183,26 +431,118 @@ impl fmt::Display for Signature {
         write!(
             f,
             "{}-{}      {}",
-            self.identifier.id, self.identifier.number, self.signature_type
+            self.identifier.id,
+            self.identifier.number,
+            self.signature_type.aligned()
         )
     }
 }
 
+/// Which signatures to include in an export.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SignatureFilter {
+    All,
+    /// Wormhole connections, for sharing the chain
+    Wormholes,
+    /// Relic and data sites, for a looting run
+    Loot,
+}
+
+impl SignatureFilter {
+    pub fn matches(&self, signature_type: &SignatureType) -> bool {
+        match self {
+            SignatureFilter::All => true,
+            SignatureFilter::Wormholes => matches!(signature_type, SignatureType::Wormhole(_)),
+            SignatureFilter::Loot => matches!(
+                signature_type,
+                SignatureType::Relic(_) | SignatureType::Data(_)
+            ),
+        }
+    }
+}
+
+/// ID given to signatures that stand in for ones not scanned yet.
+pub const PLACEHOLDER_ID: &str = "???";
+
 impl Signature {
+    /// Whether this is a stand-in for a signature that hasn't been scanned yet.
+    pub fn is_placeholder(&self) -> bool {
+        self.identifier.id == PLACEHOLDER_ID
+    }
+
     pub fn new(id: &str, number: &str, ty: SignatureType) -> Self {
         Self {
             identifier: SignatureId::new(id, number),
             signature_type: ty,
+            created: Utc::now(),
+            ignored: false,
+            scan_strengths: Vec::new(),
+            anomaly: false,
+            last_edit: None,
+            provenance: None,
         }
     }
 
+    /// Whether the signature still has to be probed to find out what it is.
+    pub fn needs_scan(&self) -> bool {
+        !self.anomaly && self.signature_type == SignatureType::Unknown
+    }
+
+    /// Record the scan strength from a paste, unless it hasn't changed.
+    pub fn record_strength(&mut self, strength: f32) {
+        if self.scan_strengths.last() != Some(&strength) {
+            self.scan_strengths.push(strength);
+        }
+    }
+
+    /// Estimate of the probe cycles left to scan the signature down, e.g. "~2 more" or "done".
+    ///
+    /// Empty if there isn't enough history to tell.
+    pub fn scan_estimate(&self) -> String {
+        match cycles_remaining(&self.scan_strengths) {
+            Some(0) => "done".to_owned(),
+            Some(cycles) => format!("~{cycles} more"),
+            None => String::new(),
+        }
+    }
+
+    /// One-line summary for pasting into chat, e.g. "J173213 DEF-456 K162 → Jita EOL/Destab".
+    pub fn share_text(&self, system: &str) -> String {
+        let details = match &self.signature_type {
+            SignatureType::Unknown => "Unknown".to_owned(),
+            SignatureType::Wormhole(wh) => format!(
+                "{} → {} {}/{}",
+                wh.wh_type.as_deref().unwrap_or("Wormhole"),
+                wh.destination.as_deref().unwrap_or("?"),
+                wh.life.as_str(),
+                wh.mass.as_str()
+            ),
+            SignatureType::Combat(name) => format!("Combat {}", name.as_deref().unwrap_or("?")),
+            SignatureType::Ore(name) => format!("Ore {}", name.as_deref().unwrap_or("?")),
+            SignatureType::Data(name) => format!("Data {}", name.as_deref().unwrap_or("?")),
+            SignatureType::Relic(name) => format!("Relic {}", name.as_deref().unwrap_or("?")),
+            SignatureType::Gas(name) => format!("Gas {}", name.as_deref().unwrap_or("?")),
+        };
+        format!("{system} {} {details}", self.identifier)
+    }
+
     pub fn to_row(&self) -> Vec<String> {
+        self.to_row_labelled(false)
+    }
+
+    /// Like `to_row`, with the type abbreviated if `short`.
+    pub fn to_row_labelled(&self, short: bool) -> Vec<String> {
+        let label = if short {
+            self.signature_type.short_label()
+        } else {
+            self.signature_type.full_label()
+        };
         let empty = String::new();
-        match &self.signature_type {
+        let mut row = match &self.signature_type {
             SignatureType::Unknown => {
                 vec![
                     self.identifier.to_string(),
-                    "Unknown".to_owned(),
+                    label.to_owned(),
                     empty.clone(),
                     empty.clone(),
                 ]
@@ -213,10 +553,17 @@ impl Signature {
                     None => "",
                 };
                 let life_and_mass = format!("{}/{}", data.life.as_str(), data.mass.as_str());
+                let mut wh_type = match data.wh_type {
+                    Some(_) => format!("{label} {}", data.type_label()),
+                    None => label.to_owned(),
+                };
+                if data.connection_kind != ConnectionKind::Unknown {
+                    wh_type = format!("{wh_type} {}", data.connection_kind.badge());
+                }
                 vec![
                     self.identifier.to_string(),
-                    "Wormhole".to_owned(),
-                    destination.to_owned(),
+                    wh_type,
+                    truncate_with_ellipsis(destination, NAME_WIDTH),
                     life_and_mass,
                 ]
             }
@@ -227,8 +574,8 @@ impl Signature {
                 };
                 vec![
                     self.identifier.to_string(),
-                    "Combat".to_owned(),
-                    name.to_owned(),
+                    label.to_owned(),
+                    truncate_with_ellipsis(name, NAME_WIDTH),
                     String::new(),
                 ]
             }
@@ -239,8 +586,8 @@ impl Signature {
                 };
                 vec![
                     self.identifier.to_string(),
-                    "Ore".to_owned(),
-                    name.to_owned(),
+                    label.to_owned(),
+                    truncate_with_ellipsis(name, NAME_WIDTH),
                     String::new(),
                 ]
             }
@@ -251,9 +598,9 @@ impl Signature {
                 };
                 vec![
                     self.identifier.to_string(),
-                    "Data".to_owned(),
-                    name.to_owned(),
-                    String::new(),
+                    label.to_owned(),
+                    truncate_with_ellipsis(name, NAME_WIDTH),
+                    value_marker(name),
                 ]
             }
             SignatureType::Relic(name) => {
@@ -263,9 +610,9 @@ impl Signature {
                 };
                 vec![
                     self.identifier.to_string(),
-                    "Relic".to_owned(),
-                    name.to_owned(),
-                    String::new(),
+                    label.to_owned(),
+                    truncate_with_ellipsis(name, NAME_WIDTH),
+                    value_marker(name),
                 ]
             }
             SignatureType::Gas(name) => {
@@ -275,16 +622,21 @@ impl Signature {
                 };
                 vec![
                     self.identifier.to_string(),
-                    "Gas".to_owned(),
-                    name.to_owned(),
-                    String::new(),
+                    label.to_owned(),
+                    truncate_with_ellipsis(name, NAME_WIDTH),
+                    gas_value_marker(name),
                 ]
             }
+        };
+        if self.anomaly {
+            row[1] = format!("{} (anomaly)", row[1]);
         }
+        row
     }
 }
 
-#[derive(Deserialize)]
+// the build script mirrors this for the bincode conversion
+#[derive(Debug, PartialEq, Deserialize)]
 pub struct WormholeInfo {
     pub life: String,
     pub from: Vec<String>,
@@ -294,19 +646,67 @@ pub struct WormholeInfo {
     pub jump: u64,
 }
 
+impl WormholeInfo {
+    /// Largest ship size that fits through, from the max jump mass.
+    pub fn size_class(&self) -> &'static str {
+        if self.jump <= 5_000_000 {
+            "Small"
+        } else if self.jump <= 62_000_000 {
+            "Medium"
+        } else if self.jump <= 410_000_000 {
+            "Large"
+        } else {
+            "XL"
+        }
+    }
+}
+
+/// A bundled static data file by name, as converted to bincode by the build script.
+#[cfg(not(feature = "json-data"))]
+macro_rules! static_data {
+    ($name:literal) => {
+        bincode::deserialize(include_bytes!(concat!(env!("OUT_DIR"), "/", $name, ".bin"))).unwrap()
+    };
+}
+
+/// A bundled static data file by name, parsed from its JSON.
+#[cfg(feature = "json-data")]
+macro_rules! static_data {
+    ($name:literal) => {
+        serde_json::from_str(include_str!(concat!("../static/", $name, ".json"))).unwrap()
+    };
+}
+
+/// Load static data, logging how long it took.
+///
+/// Files saved by `update-data` are used instead of the bundled data when they're newer.
+fn timed_load<T>(what: &str, load: impl FnOnce() -> HashMap<String, T>) -> HashMap<String, T> {
+    let start = Instant::now();
+    let data = load();
+    debug!("Loaded {} {what} in {:?}", data.len(), start.elapsed());
+    data
+}
+
 /// All wormhole types in a map of identifier to data.
 pub static WORMHOLE_TYPES: Lazy<HashMap<String, WormholeInfo>> = Lazy::new(|| {
-    let raw = include_str!("../static/wormhole_types.json");
-    serde_json::from_str(raw).unwrap()
+    timed_load("wormhole types", || {
+        user_data("wormhole_types.json").unwrap_or_else(|| static_data!("wormhole_types"))
+    })
 });
 
 /// Data about a single system.
-#[derive(Deserialize)]
+// the build script mirrors this for the bincode conversion
+#[derive(Debug, PartialEq, Deserialize)]
 pub struct SystemData {
     pub security: f32,
     pub class: Option<u8>,
     pub effect: Option<String>,
     pub statics: Vec<String>,
+    /// Not in the bundled data yet; systems are grouped by class or security without it.
+    #[serde(default)]
+    pub region: Option<String>,
+    #[serde(default)]
+    pub constellation: Option<String>,
 }
 
 pub enum SystemClassification {
@@ -342,17 +742,135 @@ impl SystemData {
     }
 }
 
+/// Wormholes found in a system compared with its statics.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StaticCount {
+    pub found: usize,
+    pub expected: usize,
+    /// Wormholes beyond the statics, which must be incoming connections.
+    pub incoming: usize,
+}
+
+impl StaticCount {
+    /// Count the scanned wormholes against the system's statics.
+    ///
+    /// Placeholders for unscanned statics and ignored signatures don't count.
+    pub fn new(statics: &[String], signatures: &[Signature]) -> Self {
+        let wormholes = signatures
+            .iter()
+            .filter(|sig| !sig.ignored && !sig.is_placeholder())
+            .filter(|sig| matches!(sig.signature_type, SignatureType::Wormhole(_)))
+            .count();
+        let expected = statics.len();
+        Self {
+            found: wormholes.min(expected),
+            expected,
+            incoming: wormholes.saturating_sub(expected),
+        }
+    }
+
+    /// Summary for the system panel, e.g. "statics: 1/2 found, +1 incoming".
+    pub fn label(&self) -> String {
+        let mut label = format!("statics: {}/{} found", self.found, self.expected);
+        if self.incoming > 0 {
+            label.push_str(&format!(", +{} incoming", self.incoming));
+        }
+        label
+    }
+}
+
+/// Destination class as the wormhole data names it, from shorthand like "C3" or "HS".
+pub fn destination_class(input: &str) -> Option<String> {
+    let input = input.trim().to_lowercase().replace([' ', '-'], "");
+    let class = match input.as_str() {
+        "hs" | "highsec" | "high" => "High-Sec".to_owned(),
+        "ls" | "lowsec" | "low" => "Low-Sec".to_owned(),
+        "ns" | "nullsec" | "null" => "Null-Sec".to_owned(),
+        "thera" => "Thera".to_owned(),
+        _ => {
+            let number = input
+                .strip_prefix("class")
+                .or_else(|| input.strip_prefix('c'))?;
+            format!("Class-{}", number.parse::<u8>().ok()?)
+        }
+    };
+    Some(class)
+}
+
+/// Statics of a system that lead to a destination class.
+#[derive(Debug, PartialEq)]
+pub enum StaticMatch {
+    Unique(String),
+    Ambiguous(Vec<String>),
+    NoMatch,
+}
+
+/// Find the static leading to a destination class, e.g. "Class-3".
+pub fn static_for_class(statics: &[String], class: &str) -> StaticMatch {
+    let mut matching: Vec<String> = statics
+        .iter()
+        .filter(|wh_type| {
+            WORMHOLE_TYPES
+                .get(*wh_type)
+                .is_some_and(|info| info.leads_to == class)
+        })
+        .cloned()
+        .collect();
+    match matching.len() {
+        0 => StaticMatch::NoMatch,
+        1 => StaticMatch::Unique(matching.remove(0)),
+        _ => StaticMatch::Ambiguous(matching),
+    }
+}
+
 /// All systems in the game, K-space and W-space.
 pub static ALL_SYSTEMS: Lazy<HashMap<String, SystemData>> = Lazy::new(|| {
-    let raw = include_str!("../static/systems.json");
-    serde_json::from_str(raw).unwrap()
+    timed_load("systems", || {
+        user_data("systems.json").unwrap_or_else(|| static_data!("systems"))
+    })
 });
 
+/// Canonical name of the system with exactly this name, ignoring case.
+fn find_system_exact(name: &str) -> Option<&'static str> {
+    if let Some((system, _)) = ALL_SYSTEMS.get_key_value(name) {
+        return Some(system);
+    }
+    ALL_SYSTEMS
+        .keys()
+        .find(|system| system.eq_ignore_ascii_case(name))
+        .map(String::as_str)
+}
+
+/// Canonical name of a system typed by the user, ignoring case.
+///
+/// If no system has the name, a prefix that only one system starts with is accepted.
+pub fn find_system(name: &str) -> Option<&'static str> {
+    let name = name.trim();
+    if name.is_empty() {
+        return None;
+    }
+    if let Some(system) = find_system_exact(name) {
+        return Some(system);
+    }
+    let lower = name.to_lowercase();
+    let mut matches = ALL_SYSTEMS
+        .keys()
+        .filter(|system| system.to_lowercase().starts_with(&lower));
+    match (matches.next(), matches.next()) {
+        (Some(system), None) => Some(system),
+        _ => None,
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub struct ClipboardItem {
     pub id: String,
     pub sig_type: String,
     pub sig_name: String,
+    /// Scan strength in percent, if the line had one.
+    pub strength: Option<f32>,
+    /// From the Cosmic Anomaly group rather than Cosmic Signature.
+    pub anomaly: bool,
 }
 
 impl ClipboardItem {
@@ -365,14 +883,26 @@ impl ClipboardItem {
             id: id.into(),
             sig_type: sig_type.into(),
             sig_name: sig_name.into(),
+            strength: None,
+            anomaly: false,
         }
     }
+
+    /// Whether the line's scan strength is known and under the minimum.
+    pub fn below_strength(&self, min: Option<f32>) -> bool {
+        matches!((self.strength, min), (Some(strength), Some(min)) if strength < min)
+    }
+
+    pub fn with_strength(mut self, strength: f32) -> Self {
+        self.strength = Some(strength);
+        self
+    }
 }
 
 impl From<&ClipboardItem> for (SignatureId, SignatureType) {
     fn from(val: &ClipboardItem) -> Self {
-        let mut id_parts = val.id.split('-');
-        let id = SignatureId::new(id_parts.next().unwrap(), id_parts.next().unwrap());
+        let (prefix, number) = val.id.split_once('-').unwrap_or((&val.id, ""));
+        let id = SignatureId::new(prefix, number);
 
         let name = if val.sig_name.is_empty() {
             None
@@ -390,6 +920,8 @@ impl From<&ClipboardItem> for (SignatureId, SignatureType) {
             SignatureType::Data(name)
         } else if val.sig_type == "Combat" {
             SignatureType::Combat(name)
+        } else if val.sig_type == "Ore" {
+            SignatureType::Ore(name)
         } else {
             SignatureType::Unknown
         };
@@ -398,6 +930,52 @@ impl From<&ClipboardItem> for (SignatureId, SignatureType) {
     }
 }
 
+/// Whether the text has scanner results grouped under system name lines.
+pub fn is_grouped_paste(text: &str) -> bool {
+    text.lines()
+        .any(|line| !line.trim().is_empty() && find_system_exact(line.trim()).is_some())
+}
+
+/// Parse scanner results grouped under system name lines, e.g. "J123456\n<sigs>\nJ654321\n<sigs>".
+///
+/// Results before the first system name are grouped under `None`.
+pub fn parse_grouped_paste(text: &str) -> Vec<(Option<String>, Vec<ClipboardItem>)> {
+    let mut groups: Vec<(Option<String>, String)> = vec![(None, String::new())];
+    for line in text.lines() {
+        let trimmed = line.trim();
+        match find_system_exact(trimmed).filter(|_| !trimmed.is_empty()) {
+            Some(system) => groups.push((Some(system.to_owned()), String::new())),
+            None => {
+                let (_, lines) = groups.last_mut().unwrap();
+                lines.push_str(line);
+                lines.push('\n');
+            }
+        }
+    }
+    groups
+        .into_iter()
+        .map(|(system, lines)| (system, parse_paste(&lines)))
+        .filter(|(system, items)| system.is_some() || !items.is_empty())
+        .collect()
+}
+
+/// Probe cycles still needed to reach 100% scan strength, from the strength of each cycle so far.
+///
+/// Assumes the next cycles gain as much as the latest one did. `None` if
+/// there's only one sample or the strength isn't going up.
+pub fn cycles_remaining(samples: &[f32]) -> Option<u32> {
+    let last = *samples.last()?;
+    if last >= 100.0 {
+        return Some(0);
+    }
+    let previous = *samples.get(samples.len().checked_sub(2)?)?;
+    let gain = last - previous;
+    if gain <= 0.0 {
+        return None;
+    }
+    Some(((100.0 - last) / gain).ceil() as u32)
+}
+
 /// Parse clipboard data to extract any cosmic signature data.
 pub fn parse_paste(text: &str) -> Vec<ClipboardItem> {
     if text.trim().is_empty() {
@@ -405,33 +983,64 @@ pub fn parse_paste(text: &str) -> Vec<ClipboardItem> {
     }
     let mut findings: Vec<ClipboardItem> = Vec::new();
     for line in text.split_terminator('\n') {
-        let id: String = line.chars().take(7).collect();
+        // the ID runs up to the first whitespace, whatever its length
+        let id = line
+            .trim_start()
+            .split(char::is_whitespace)
+            .next()
+            .unwrap_or_default()
+            .to_owned();
         let parts = line.split('\t').skip(2).collect::<Vec<_>>();
         if parts.is_empty() {
             continue;
         }
-        if parts[0] == "Wormhole" {
-            findings.push(ClipboardItem::new(id, "Wormhole", ""));
+        // anomalies don't need probing, so they're always fully scanned
+        let anomaly = line
+            .split('\t')
+            .nth(1)
+            .is_some_and(|group| group.trim() == "Cosmic Anomaly");
+        let strength = if anomaly {
+            Some(100.0)
+        } else {
+            parts
+                .get(2)
+                .and_then(|s| s.trim().trim_end_matches('%').parse::<f32>().ok())
+        };
+        let item = if parts[0] == "Wormhole" {
+            ClipboardItem::new(id, "Wormhole", "")
         } else if parts[0] == "Gas Site"
             || parts[0] == "Relic Site"
             || parts[0] == "Data Site"
             || parts[0] == "Combat Site"
+            || parts[0] == "Ore Site"
         {
             let name = match parts.get(1) {
                 Some(s) => s,
                 None => "",
             };
-            findings.push(ClipboardItem::new(id, parts[0].replace(" Site", ""), name));
+            ClipboardItem::new(id, parts[0].replace(" Site", ""), name)
         } else {
-            findings.push(ClipboardItem::new(id, "", ""));
-        }
+            ClipboardItem::new(id, "", "")
+        };
+        findings.push(ClipboardItem {
+            strength,
+            anomaly,
+            ..item
+        });
     }
     findings
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{parse_paste, ClipboardItem};
+    use super::{
+        cycles_remaining, destination_class, find_system, is_grouped_paste, parse_grouped_paste,
+        parse_paste, static_for_class, truncate_with_ellipsis, ClipboardItem, ConnectionKind,
+        Signature, SignatureId, SignatureType, SignatureWormhole, StaticCount, StaticMatch,
+        SystemData, WormholeDirection, WormholeInfo, WormholeLife, WormholeMass, ALL_SYSTEMS,
+        KIND_WIDTH, WORMHOLE_TYPES,
+    };
+    use std::collections::HashMap;
 
     const SAMPLE_PASTE: &str = r#"UWG-400	Cosmic Signature	Wormhole	Unstable Wormhole	100.0%	33.21 AU
 SVC-432	Cosmic Signature	Data Site	Unsecured Frontier Receiver	100.0%	11.13 AU
@@ -460,24 +1069,373 @@ LHB-560	Cosmic Signature			8.8%	2.95 AU
 WYT-700	Cosmic Signature	Gas Site		5.2%	4.02 AU"#;
         let results = parse_paste(text);
         let expected: Vec<ClipboardItem> = vec![
-            ClipboardItem::new("OEB-892", "Wormhole", ""),
-            ClipboardItem::new("YQS-184", "Wormhole", ""),
-            ClipboardItem::new("OVD-328", "Wormhole", ""),
-            ClipboardItem::new("WIV-940", "Relic", "Ruined Blood Raider Temple Site"),
-            ClipboardItem::new("ROZ-580", "Relic", "Ruined Angel Temple Site"),
-            ClipboardItem::new("MJK-752", "Wormhole", ""),
-            ClipboardItem::new("ZYP-580", "", ""),
-            ClipboardItem::new("LHB-560", "", ""),
-            ClipboardItem::new("WYT-700", "Gas", ""),
+            ClipboardItem::new("OEB-892", "Wormhole", "").with_strength(100.0),
+            ClipboardItem::new("YQS-184", "Wormhole", "").with_strength(100.0),
+            ClipboardItem::new("OVD-328", "Wormhole", "").with_strength(100.0),
+            ClipboardItem::new("WIV-940", "Relic", "Ruined Blood Raider Temple Site")
+                .with_strength(100.0),
+            ClipboardItem::new("ROZ-580", "Relic", "Ruined Angel Temple Site").with_strength(100.0),
+            ClipboardItem::new("MJK-752", "Wormhole", "").with_strength(100.0),
+            ClipboardItem::new("ZYP-580", "", "").with_strength(10.4),
+            ClipboardItem::new("LHB-560", "", "").with_strength(8.8),
+            ClipboardItem::new("WYT-700", "Gas", "").with_strength(5.2),
         ];
 
         assert_eq!(results, expected);
     }
 
+    #[test]
+    fn test_cycles_remaining() {
+        let samples = [8.8, 21.5, 40.0, 62.5, 87.0, 100.0];
+        let estimates: Vec<_> = (1..=samples.len())
+            .map(|n| cycles_remaining(&samples[..n]))
+            .collect();
+
+        assert_eq!(
+            estimates,
+            vec![None, Some(7), Some(4), Some(2), Some(1), Some(0)]
+        );
+        assert_eq!(cycles_remaining(&[]), None);
+        assert_eq!(cycles_remaining(&[30.0, 30.0]), None);
+    }
+
+    #[test]
+    fn test_signature_scan_estimate() {
+        let mut sig = Signature::new("ABC", "123", SignatureType::Unknown);
+        assert_eq!(sig.scan_estimate(), "");
+
+        for strength in [20.0, 20.0, 45.0, 70.0] {
+            sig.record_strength(strength);
+        }
+        assert_eq!(sig.scan_strengths, vec![20.0, 45.0, 70.0]);
+        assert_eq!(sig.scan_estimate(), "~2 more");
+
+        sig.record_strength(100.0);
+        assert_eq!(sig.scan_estimate(), "done");
+    }
+
+    #[test]
+    fn test_parse_paste_id_lengths() {
+        let text = "ABC-123\tCosmic Signature\tWormhole\tUnstable Wormhole\t100.0%\t1 AU
+ABCD-1234\tCosmic Signature\tGas Site\t\t0.0%\t2 AU
+XY-12\tCosmic Signature\t\t\t0.0%\t3 AU";
+        let ids: Vec<_> = parse_paste(text).into_iter().map(|item| item.id).collect();
+        assert_eq!(ids, vec!["ABC-123", "ABCD-1234", "XY-12"]);
+
+        let item = ClipboardItem::new("ABCD-1234", "Gas", "");
+        let (id, _) = <(SignatureId, SignatureType)>::from(&item);
+        assert_eq!(id, SignatureId::new("ABCD", "1234"));
+    }
+
+    #[test]
+    fn test_short_labels() {
+        let types = [
+            SignatureType::Unknown,
+            SignatureType::Combat(None),
+            SignatureType::Wormhole(SignatureWormhole::default()),
+            SignatureType::Ore(None),
+            SignatureType::Data(None),
+            SignatureType::Relic(None),
+            SignatureType::Gas(None),
+        ];
+        let labels: Vec<_> = types.iter().map(SignatureType::short_label).collect();
+        assert_eq!(labels, vec!["?", "C", "WH", "O", "D", "R", "G"]);
+
+        let sig = Signature::new("ABC", "123", SignatureType::Relic(Some("A".to_owned())));
+        assert_eq!(sig.to_row()[1], "Relic");
+        assert_eq!(sig.to_row_labelled(true)[1], "R");
+    }
+
     #[test]
     fn test_parse_paste_invalid() {
         let text = "some random nonsense";
         let results = parse_paste(text);
         assert!(results.is_empty());
     }
+
+    #[test]
+    fn test_truncate_with_ellipsis() {
+        let name = "Unsecured Frontier Digital Nexus";
+        assert_eq!(truncate_with_ellipsis(name, 40), name);
+        assert_eq!(truncate_with_ellipsis(name, name.len()), name);
+        assert_eq!(
+            truncate_with_ellipsis(name, name.len() - 1),
+            "Unsecured Frontier Digital Nex…"
+        );
+        assert_eq!(truncate_with_ellipsis(name, 1), "…");
+        assert_eq!(truncate_with_ellipsis(name, 0), "");
+        assert_eq!(truncate_with_ellipsis("", 0), "");
+    }
+
+    #[test]
+    fn test_wormhole_direction() {
+        let outbound = SignatureWormhole {
+            wh_type: Some("B274".to_owned()),
+            ..Default::default()
+        };
+        let k162 = SignatureWormhole {
+            wh_type: Some("K162".to_owned()),
+            ..Default::default()
+        };
+        let imported_k162 = SignatureWormhole {
+            connection_kind: ConnectionKind::IncomingK162,
+            ..Default::default()
+        };
+
+        assert_eq!(outbound.direction(false), WormholeDirection::Outbound);
+        assert_eq!(
+            SignatureWormhole::default().direction(false),
+            WormholeDirection::Outbound
+        );
+        assert_eq!(k162.direction(false), WormholeDirection::Inbound);
+        assert_eq!(imported_k162.direction(false), WormholeDirection::Inbound);
+        assert_eq!(outbound.direction(true), WormholeDirection::Bidirectional);
+        assert_eq!(k162.direction(true), WormholeDirection::Bidirectional);
+        assert_eq!(WormholeDirection::Inbound.glyph(), "←");
+    }
+
+    #[test]
+    fn test_wormhole_k162_unknown_origin() {
+        let wh = SignatureWormhole {
+            wh_type: Some("K162".to_owned()),
+            ..Default::default()
+        };
+
+        assert_eq!(wh.type_label(), "K162 → ? (unknown origin)");
+        assert!(wh.info().is_none());
+    }
+
+    #[test]
+    fn test_wormhole_k162_resolve_origin() {
+        let mut wh = SignatureWormhole {
+            wh_type: Some("K162".to_owned()),
+            ..Default::default()
+        };
+
+        assert!(!wh.resolve_origin("NOPE"));
+        assert!(wh.resolve_origin("C247"));
+        assert_eq!(wh.wh_type, Some("K162".to_owned()));
+        assert_eq!(wh.info().unwrap().size_class(), "Large");
+        assert_eq!(wh.type_label(), "K162 ← C247 (Large)");
+    }
+
+    #[test]
+    fn test_wormhole_resolve_origin_not_k162() {
+        let mut wh = SignatureWormhole {
+            wh_type: Some("B274".to_owned()),
+            ..Default::default()
+        };
+
+        assert!(!wh.resolve_origin("C247"));
+        assert_eq!(wh.type_label(), "B274 (Large)");
+    }
+
+    #[test]
+    fn test_parse_paste_ore_site() {
+        let text =
+            "ORE-123\tCosmic Signature\tOre Site\tOrdinary Perimeter Deposit\t100.0%\t3.21 AU";
+        let results = parse_paste(text);
+
+        assert_eq!(
+            results,
+            vec![
+                ClipboardItem::new("ORE-123", "Ore", "Ordinary Perimeter Deposit")
+                    .with_strength(100.0)
+            ]
+        );
+        let (id, sig_type): (SignatureId, SignatureType) = (&results[0]).into();
+        assert_eq!(id, SignatureId::new("ORE", "123"));
+        assert_eq!(
+            sig_type,
+            SignatureType::Ore(Some("Ordinary Perimeter Deposit".to_owned()))
+        );
+    }
+
+    #[test]
+    fn test_share_text() {
+        let wh = Signature::new(
+            "DEF",
+            "456",
+            SignatureType::Wormhole(SignatureWormhole::new(
+                Some("K162".to_owned()),
+                Some("J105412".to_owned()),
+                WormholeLife::EndOfLife,
+                WormholeMass::Destab,
+            )),
+        );
+        assert_eq!(
+            wh.share_text("J173213"),
+            "J173213 DEF-456 K162 → J105412 EOL/Destab"
+        );
+
+        let site = Signature::new(
+            "ABC",
+            "123",
+            SignatureType::Relic(Some("Ruined Angel Temple Site".to_owned())),
+        );
+        assert_eq!(
+            site.share_text("J173213"),
+            "J173213 ABC-123 Relic Ruined Angel Temple Site"
+        );
+        let unknown = Signature::new("GHI", "789", SignatureType::Unknown);
+        assert_eq!(unknown.share_text("Jita"), "Jita GHI-789 Unknown");
+    }
+
+    #[test]
+    fn test_find_system() {
+        assert_eq!(find_system("Jita"), Some("Jita"));
+        assert_eq!(find_system(" j173213 "), Some("J173213"));
+        assert_eq!(find_system("Nowhere"), None);
+        assert_eq!(find_system("j17321"), Some("J173213"));
+        // ambiguous
+        assert_eq!(find_system("J1"), None);
+    }
+
+    #[test]
+    fn test_parse_paste_anomalies() {
+        let items = parse_paste(include_str!("../tests/fixtures/anomaly_paste.txt"));
+        let parsed: Vec<_> = items
+            .iter()
+            .map(|item| (item.sig_type.as_str(), item.anomaly, item.strength))
+            .collect();
+
+        assert_eq!(
+            parsed,
+            vec![
+                ("Wormhole", false, Some(100.0)),
+                ("Combat", true, Some(100.0)),
+                ("Ore", true, Some(100.0)),
+                ("", false, Some(12.5)),
+            ]
+        );
+        assert_eq!(items[1].sig_name, "Sansha Hideaway");
+    }
+
+    #[test]
+    fn test_parse_grouped_paste() {
+        let text = "ABC-123\tCosmic Signature\tWormhole\tUnstable Wormhole\t100.0%\t4.99 AU
+J173213
+DEF-456\tCosmic Signature\tGas Site\tBarren Perimeter Reservoir\t100.0%\t4.99 AU
+GHI-789\tCosmic Signature\t\t\t0.0%\t4.99 AU
+thera
+JKL-012\tCosmic Signature\t\t\t0.0%\t4.99 AU
+";
+        assert!(is_grouped_paste(text));
+        assert!(!is_grouped_paste(SAMPLE_PASTE));
+
+        let groups = parse_grouped_paste(text);
+
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups[0].0, None);
+        assert_eq!(
+            groups[0].1,
+            vec![ClipboardItem::new("ABC-123", "Wormhole", "").with_strength(100.0)]
+        );
+        assert_eq!(groups[1].0.as_deref(), Some("J173213"));
+        assert_eq!(groups[1].1.len(), 2);
+        assert_eq!(groups[2].0.as_deref(), Some("Thera"));
+        assert_eq!(
+            groups[2].1,
+            vec![ClipboardItem::new("JKL-012", "", "").with_strength(0.0)]
+        );
+    }
+
+    #[test]
+    fn test_signature_type_aligned() {
+        let types = [
+            SignatureType::Combat(Some("Perimeter Camp".to_owned())),
+            SignatureType::Wormhole(SignatureWormhole {
+                wh_type: Some("B274".to_owned()),
+                destination: Some("Jita".to_owned()),
+                ..Default::default()
+            }),
+            SignatureType::Ore(Some("Ordinary Perimeter Deposit".to_owned())),
+            SignatureType::Data(Some("Unsecured Frontier Receiver".to_owned())),
+            SignatureType::Relic(Some("Ruined Angel Temple Site".to_owned())),
+            SignatureType::Gas(Some("Barren Perimeter Reservoir".to_owned())),
+        ];
+        for sig_type in &types {
+            let aligned = sig_type.aligned().to_string();
+            let (kind, details) = aligned.split_at(KIND_WIDTH + 1);
+            assert_eq!(kind.trim_end(), sig_type.kind());
+            assert!(!details.starts_with(' '), "{aligned:?}");
+        }
+
+        assert_eq!(types[0].to_string(), "Combat Perimeter Camp");
+        assert_eq!(
+            types[1].to_string(),
+            "WH B274 (Large) -> Jita Stable/Stable"
+        );
+        assert_eq!(SignatureType::Unknown.aligned().to_string(), "Unknown");
+        assert_eq!(SignatureType::Gas(None).to_string(), "Gas");
+    }
+
+    #[test]
+    fn test_static_count() {
+        let statics = vec!["B274".to_owned(), "Z647".to_owned()];
+        let wormhole = |id: &str| {
+            Signature::new(
+                id,
+                "123",
+                SignatureType::Wormhole(SignatureWormhole::default()),
+            )
+        };
+        let site = Signature::new("SIT", "123", SignatureType::Gas(None));
+
+        let under = StaticCount::new(&statics, &[wormhole("AAA"), wormhole("???"), site.clone()]);
+        assert_eq!(under.label(), "statics: 1/2 found");
+
+        let exact = StaticCount::new(&statics, &[wormhole("AAA"), wormhole("BBB"), site]);
+        assert_eq!(exact.label(), "statics: 2/2 found");
+
+        let over = StaticCount::new(
+            &statics,
+            &[wormhole("AAA"), wormhole("BBB"), wormhole("CCC")],
+        );
+        assert_eq!(
+            over,
+            StaticCount {
+                found: 2,
+                expected: 2,
+                incoming: 1
+            }
+        );
+        assert_eq!(over.label(), "statics: 2/2 found, +1 incoming");
+    }
+
+    #[test]
+    fn test_static_data_matches_json() {
+        let systems: HashMap<String, SystemData> =
+            serde_json::from_str(include_str!("../static/systems.json")).unwrap();
+        assert_eq!(*ALL_SYSTEMS, systems);
+        let wormholes: HashMap<String, WormholeInfo> =
+            serde_json::from_str(include_str!("../static/wormhole_types.json")).unwrap();
+        assert_eq!(*WORMHOLE_TYPES, wormholes);
+    }
+
+    #[test]
+    fn test_destination_class() {
+        assert_eq!(destination_class("C3").as_deref(), Some("Class-3"));
+        assert_eq!(destination_class("class 13").as_deref(), Some("Class-13"));
+        assert_eq!(destination_class(" hs ").as_deref(), Some("High-Sec"));
+        assert_eq!(destination_class("Null-Sec").as_deref(), Some("Null-Sec"));
+        assert_eq!(destination_class("B274"), None);
+        assert_eq!(destination_class("C"), None);
+    }
+
+    #[test]
+    fn test_static_for_class() {
+        let statics = vec!["B274".to_owned(), "Z647".to_owned()];
+        assert_eq!(
+            static_for_class(&statics, "High-Sec"),
+            StaticMatch::Unique("B274".to_owned())
+        );
+        assert_eq!(static_for_class(&statics, "Class-3"), StaticMatch::NoMatch);
+
+        let statics = vec!["O477".to_owned(), "X702".to_owned(), "B274".to_owned()];
+        assert_eq!(
+            static_for_class(&statics, "Class-3"),
+            StaticMatch::Ambiguous(vec!["O477".to_owned(), "X702".to_owned()])
+        );
+    }
 }
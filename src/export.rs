@@ -0,0 +1,348 @@
+use crate::{
+    chain::chain_edges,
+    config::ReportOptions,
+    eve_data::{
+        Signature, SignatureFilter, SignatureType, WormholeLife, WormholeMass, ALL_SYSTEMS,
+        WORMHOLE_TYPES,
+    },
+};
+use anyhow::Result;
+use std::{collections::HashMap, fs};
+
+/// File the chain report is written to from the interface.
+pub const REPORT_FILE: &str = "chain_report.md";
+/// File the whole chain is written to in the scanner layout.
+pub const CHAIN_SIGS_FILE: &str = "chain_sigs.tsv";
+
+/// Column headings for exported signature tables, matching the signature list.
+pub const HEADER: [&str; 4] = ["ID", "Type", "Leads to", "Life/Mass/Value"];
+
+/// Signatures worth exporting: those that match, without ignored ones or placeholders.
+fn exported(signatures: &[Signature], filter: SignatureFilter) -> impl Iterator<Item = &Signature> {
+    signatures.iter().filter(move |sig| {
+        !sig.ignored && !sig.is_placeholder() && filter.matches(&sig.signature_type)
+    })
+}
+
+/// The system's signatures as one line each, for pasting into chat.
+pub fn export_system(system: &str, signatures: &[Signature], filter: SignatureFilter) -> String {
+    exported(signatures, filter)
+        .map(|sig| sig.share_text(system))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The signature as "ID<tab>group<tab>name", the scanner's layout that other mappers accept.
+///
+/// Wormholes give their type and destination as the name, e.g. "K162 → J123456 (EOL)".
+pub fn scanner_line(sig: &Signature) -> String {
+    let site =
+        |group: &str, name: &Option<String>| (group.to_owned(), name.clone().unwrap_or_default());
+    let (group, name) = match &sig.signature_type {
+        SignatureType::Unknown => (String::new(), String::new()),
+        SignatureType::Wormhole(wh) => {
+            let mut name = match (&wh.wh_type, &wh.destination) {
+                (None, None) => String::new(),
+                (wh_type, destination) => format!(
+                    "{} → {}",
+                    wh_type.as_deref().unwrap_or("?"),
+                    destination.as_deref().unwrap_or("?")
+                ),
+            };
+            let mut notes = Vec::new();
+            if wh.life == WormholeLife::EndOfLife {
+                notes.push("EOL");
+            }
+            match wh.mass {
+                WormholeMass::Stable | WormholeMass::Unknown => {}
+                WormholeMass::Destab => notes.push("destab"),
+                WormholeMass::Critical => notes.push("crit"),
+            }
+            if !notes.is_empty() {
+                name = format!("{name} ({})", notes.join(", ")).trim().to_owned();
+            }
+            ("Wormhole".to_owned(), name)
+        }
+        SignatureType::Combat(name) => site("Combat Site", name),
+        SignatureType::Ore(name) => site("Ore Site", name),
+        SignatureType::Data(name) => site("Data Site", name),
+        SignatureType::Relic(name) => site("Relic Site", name),
+        SignatureType::Gas(name) => site("Gas Site", name),
+    };
+    format!("{}\t{group}\t{name}", sig.identifier)
+}
+
+/// The system's signatures in the scanner layout, one per line.
+pub fn export_scanner(signatures: &[Signature], filter: SignatureFilter) -> String {
+    exported(signatures, filter)
+        .map(scanner_line)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Every system's signatures in the scanner layout, each under its system's name.
+///
+/// Systems without signatures are left out. This pastes back in as a grouped paste.
+pub fn export_scanner_chain(system_data: &HashMap<String, Vec<Signature>>) -> String {
+    let mut systems: Vec<_> = system_data.keys().collect();
+    systems.sort();
+    systems
+        .into_iter()
+        .filter_map(|system| {
+            let lines = export_scanner(&system_data[system], SignatureFilter::All);
+            (!lines.is_empty()).then(|| format!("{system}\n{lines}\n"))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Write the whole chain in the scanner layout to `CHAIN_SIGS_FILE`, returning what was written.
+pub fn write_scanner_chain(system_data: &HashMap<String, Vec<Signature>>) -> Result<String> {
+    let text = export_scanner_chain(system_data);
+    fs::write(CHAIN_SIGS_FILE, &text)?;
+    Ok(text)
+}
+
+/// The system's signatures as a markdown table under a heading.
+pub fn export_markdown(system: &str, signatures: &[Signature], filter: SignatureFilter) -> String {
+    format!("## {system}\n\n{}", markdown_table(signatures, filter))
+}
+
+fn markdown_table(signatures: &[Signature], filter: SignatureFilter) -> String {
+    let mut out = markdown_row(HEADER.iter().map(|h| h.to_string()));
+    out.push_str(&markdown_row(HEADER.iter().map(|_| "---".to_owned())));
+    for sig in exported(signatures, filter) {
+        out.push_str(&markdown_row(sig.to_row()));
+    }
+    out
+}
+
+/// Classification, effect and statics of a known system, as markdown list items.
+fn system_details(system: &str) -> String {
+    let Some(data) = ALL_SYSTEMS.get(system) else {
+        return String::new();
+    };
+    let mut out = format!("- Class: {}\n", data.classification().as_str());
+    if let Some(effect) = &data.effect {
+        out.push_str(&format!("- Effect: {effect}\n"));
+    }
+    if !data.statics.is_empty() {
+        let statics: Vec<_> = data
+            .statics
+            .iter()
+            .map(|wh_type| match WORMHOLE_TYPES.get(wh_type) {
+                Some(info) => format!("{wh_type} → {}", info.leads_to),
+                None => wh_type.clone(),
+            })
+            .collect();
+        out.push_str(&format!("- Statics: {}\n", statics.join(", ")));
+    }
+    out
+}
+
+/// The whole chain as a markdown document: a section for each system
+/// with its details and signatures, then a summary of the connections.
+pub fn chain_report(
+    system_data: &HashMap<String, Vec<Signature>>,
+    options: ReportOptions,
+) -> String {
+    let mut systems: Vec<_> = system_data
+        .iter()
+        .filter(|(system, signatures)| {
+            let is_kspace = ALL_SYSTEMS
+                .get(system.as_str())
+                .is_some_and(|data| data.class.is_none());
+            (options.include_kspace || !is_kspace)
+                && !(options.skip_empty
+                    && exported(signatures, SignatureFilter::All).next().is_none())
+        })
+        .collect();
+    systems.sort_by_key(|(system, _)| system.as_str());
+
+    let mut out = String::from("# Chain report\n");
+    for (system, signatures) in &systems {
+        out.push_str(&format!("\n## {system}\n\n"));
+        let details = system_details(system);
+        if !details.is_empty() {
+            out.push_str(&details);
+            out.push('\n');
+        }
+        out.push_str(&markdown_table(signatures, SignatureFilter::All));
+    }
+
+    out.push_str("\n## Connections\n\n");
+    let edges: Vec<_> = chain_edges(system_data)
+        .into_iter()
+        .filter(|edge| {
+            systems
+                .iter()
+                .any(|(system, _)| system.as_str() == edge.from)
+        })
+        .collect();
+    if edges.is_empty() {
+        out.push_str("No known connections\n");
+    }
+    for edge in edges {
+        let wh_type = edge.wormhole.wh_type.as_deref().unwrap_or("?");
+        out.push_str(&format!(
+            "- {} {} → {} ({wh_type}, life {}, mass {})\n",
+            edge.from,
+            edge.signature.identifier,
+            edge.to,
+            edge.wormhole.life.as_str(),
+            edge.wormhole.mass.as_str(),
+        ));
+    }
+    out
+}
+
+/// Write the chain report to `REPORT_FILE`.
+pub fn write_report(
+    system_data: &HashMap<String, Vec<Signature>>,
+    options: ReportOptions,
+) -> Result<()> {
+    fs::write(REPORT_FILE, chain_report(system_data, options))?;
+    Ok(())
+}
+
+fn markdown_row(cells: impl IntoIterator<Item = String>) -> String {
+    let cells: Vec<_> = cells
+        .into_iter()
+        .map(|cell| cell.replace('|', "\\|"))
+        .collect();
+    format!("| {} |\n", cells.join(" | "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        chain_report, export_markdown, export_scanner, export_scanner_chain, export_system,
+    };
+    use crate::{
+        config::ReportOptions,
+        eve_data::{Signature, SignatureFilter, SignatureType, SignatureWormhole, WormholeLife},
+        state::{migrate, App},
+    };
+
+    fn report_chain() -> App {
+        let raw = include_str!("../tests/fixtures/report_chain.json");
+        migrate(serde_json::from_str(raw).unwrap()).unwrap()
+    }
+
+    fn signatures() -> Vec<Signature> {
+        let mut ignored = Signature::new("MNO", "345", SignatureType::Data(None));
+        ignored.ignored = true;
+        vec![
+            Signature::new(
+                "ABC",
+                "123",
+                SignatureType::Wormhole(SignatureWormhole {
+                    wh_type: Some("B274".to_owned()),
+                    destination: Some("Jita".to_owned()),
+                    ..Default::default()
+                }),
+            ),
+            Signature::new(
+                "DEF",
+                "456",
+                SignatureType::Relic(Some("Ruined Angel Temple Site".to_owned())),
+            ),
+            Signature::new("GHI", "789", SignatureType::Gas(None)),
+            Signature::new("JKL", "012", SignatureType::Data(None)),
+            ignored,
+        ]
+    }
+
+    #[test]
+    fn test_export_system_filtered() {
+        let sigs = signatures();
+
+        assert_eq!(
+            export_system("J173213", &sigs, SignatureFilter::Wormholes),
+            "J173213 ABC-123 B274 → Jita Stable/Stable"
+        );
+        assert_eq!(
+            export_system("J173213", &sigs, SignatureFilter::Loot),
+            "J173213 DEF-456 Relic Ruined Angel Temple Site\nJ173213 JKL-012 Data ?"
+        );
+        assert_eq!(
+            export_system("J173213", &sigs, SignatureFilter::All)
+                .lines()
+                .count(),
+            4
+        );
+    }
+
+    #[test]
+    fn test_export_scanner() {
+        assert_eq!(
+            export_scanner(&signatures(), SignatureFilter::All),
+            "ABC-123\tWormhole\tB274 → Jita
+DEF-456\tRelic Site\tRuined Angel Temple Site
+GHI-789\tGas Site\t
+JKL-012\tData Site\t"
+        );
+        let unknown = [
+            Signature::new("PQR", "678", SignatureType::Unknown),
+            Signature::new(
+                "STU",
+                "901",
+                SignatureType::Wormhole(SignatureWormhole {
+                    life: WormholeLife::EndOfLife,
+                    ..Default::default()
+                }),
+            ),
+        ];
+        assert_eq!(
+            export_scanner(&unknown, SignatureFilter::All),
+            "PQR-678\t\t\nSTU-901\tWormhole\t(EOL)"
+        );
+    }
+
+    #[test]
+    fn test_export_scanner_chain() {
+        let app = report_chain();
+        assert_eq!(
+            export_scanner_chain(&app.system_data),
+            include_str!("../tests/fixtures/report_chain.tsv")
+        );
+    }
+
+    #[test]
+    fn test_export_markdown_filtered() {
+        let markdown = export_markdown("J173213", &signatures(), SignatureFilter::Wormholes);
+        let lines: Vec<_> = markdown.lines().collect();
+
+        assert_eq!(lines[0], "## J173213");
+        assert_eq!(lines[2], "| ID | Type | Leads to | Life/Mass/Value |");
+        assert_eq!(lines[3], "| --- | --- | --- | --- |");
+        assert!(lines[4].starts_with("| ABC-123 | Wormhole B274"));
+        assert_eq!(lines.len(), 5);
+        assert!(!markdown.contains("DEF-456"));
+    }
+
+    #[test]
+    fn test_chain_report_golden() {
+        let app = report_chain();
+        assert_eq!(
+            chain_report(&app.system_data, ReportOptions::default()),
+            include_str!("../tests/fixtures/report_chain.md")
+        );
+    }
+
+    #[test]
+    fn test_chain_report_sections() {
+        let app = report_chain();
+        let options = ReportOptions {
+            skip_empty: true,
+            include_kspace: false,
+        };
+        let report = chain_report(&app.system_data, options);
+
+        assert!(report.contains("## J173213"));
+        assert!(!report.contains("## J105443"));
+        assert!(!report.contains("## Jita"));
+        assert!(report.contains("- J173213 DEF-456 → Jita"));
+        assert!(!report.contains("- Jita JKL-012"));
+    }
+}
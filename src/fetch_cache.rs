@@ -0,0 +1,120 @@
+use std::{collections::HashMap, future::Future};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+/// Data for a system, fetched on a background task.
+pub struct Fetched<T> {
+    pub system: String,
+    /// `None` if the fetch failed.
+    pub value: Option<T>,
+    /// ESI didn't know the system.
+    pub unknown_to_esi: bool,
+}
+
+/// Data for each system, fetched from ESI on background tasks and kept for the session.
+///
+/// Failed fetches are cached too, so they aren't retried.
+pub struct FetchCache<T> {
+    values: HashMap<String, Option<T>>,
+    /// Systems with a fetch still running.
+    fetching: Vec<String>,
+    unknown_to_esi: Option<String>,
+    sender: UnboundedSender<Fetched<T>>,
+    receiver: UnboundedReceiver<Fetched<T>>,
+}
+
+impl<T> Default for FetchCache<T> {
+    fn default() -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        Self {
+            values: HashMap::new(),
+            fetching: Vec::new(),
+            unknown_to_esi: None,
+            sender,
+            receiver,
+        }
+    }
+}
+
+impl<T: Send + 'static> FetchCache<T> {
+    /// Cached data for the system, if it's been fetched successfully.
+    pub fn get(&self, system: &str) -> Option<&T> {
+        self.values.get(system)?.as_ref()
+    }
+
+    /// Whether the system's data has been fetched or is being fetched.
+    pub fn has_tried(&self, system: &str) -> bool {
+        self.values.contains_key(system) || self.fetching.iter().any(|s| s == system)
+    }
+
+    /// Run the fetch for the system on a background task.
+    ///
+    /// Its result is stored by `receive` once it arrives.
+    pub fn spawn(
+        &mut self,
+        system: &str,
+        fetch: impl Future<Output = Fetched<T>> + Send + 'static,
+    ) {
+        self.fetching.push(system.to_owned());
+        let sender = self.sender.clone();
+        tokio::spawn(async move {
+            let _ = sender.send(fetch.await);
+        });
+    }
+
+    /// Store data fetched since this was last called.
+    pub fn receive(&mut self) {
+        while let Ok(fetched) = self.receiver.try_recv() {
+            self.fetching.retain(|system| *system != fetched.system);
+            if fetched.unknown_to_esi {
+                self.unknown_to_esi = Some(fetched.system.clone());
+            }
+            self.values.insert(fetched.system, fetched.value);
+        }
+    }
+
+    /// A system ESI didn't know when last fetching, if there was one since this was last called.
+    pub fn take_unknown_to_esi(&mut self) -> Option<String> {
+        self.unknown_to_esi.take()
+    }
+
+    /// Cache data for the system as if it had been fetched.
+    #[cfg(test)]
+    pub fn insert(&mut self, system: &str, value: T) {
+        self.values.insert(system.to_owned(), Some(value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FetchCache, Fetched};
+
+    #[test]
+    fn test_receive_fetched() {
+        let mut cache = FetchCache::default();
+        cache.fetching.push("Jita".to_owned());
+        cache.fetching.push("Thera".to_owned());
+        assert!(cache.has_tried("Jita"));
+        assert!(!cache.has_tried("Amarr"));
+
+        for (system, value, unknown_to_esi) in [("Jita", Some(5), false), ("Thera", None, true)] {
+            cache
+                .sender
+                .send(Fetched {
+                    system: system.to_owned(),
+                    value,
+                    unknown_to_esi,
+                })
+                .unwrap();
+        }
+        assert!(cache.get("Jita").is_none());
+        cache.receive();
+
+        assert_eq!(cache.get("Jita"), Some(&5));
+        // failures are kept so they aren't fetched again
+        assert!(cache.get("Thera").is_none());
+        assert!(cache.has_tried("Thera"));
+        assert!(cache.fetching.is_empty());
+        assert_eq!(cache.take_unknown_to_esi().as_deref(), Some("Thera"));
+        assert!(cache.take_unknown_to_esi().is_none());
+    }
+}
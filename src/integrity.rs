@@ -0,0 +1,246 @@
+use crate::{
+    eve_data::{find_system, Signature, SignatureId, SignatureType},
+    state::App,
+};
+use chrono::{DateTime, Duration, Utc};
+use log::info;
+
+/// Something wrong in the saved map, such as after a crash or editing the file by hand.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Problem {
+    /// A connection end for a signature that isn't in the map.
+    DanglingConnection { system: String, id: SignatureId },
+    /// A wormhole leading to a system that doesn't exist.
+    UnknownDestination {
+        system: String,
+        id: SignatureId,
+        destination: String,
+    },
+    /// The same signature ID more than once in a system.
+    DuplicateSignature { system: String, id: SignatureId },
+    /// A signature recorded or edited after now.
+    FutureTimestamp { system: String, id: SignatureId },
+}
+
+impl Problem {
+    /// One-line description for the report.
+    pub fn label(&self) -> String {
+        match self {
+            Self::DanglingConnection { system, id } => {
+                format!("Connection to {id} in {system}, which isn't in the map")
+            }
+            Self::UnknownDestination {
+                system,
+                id,
+                destination,
+            } => format!("{id} in {system} leads to unknown system '{destination}'"),
+            Self::DuplicateSignature { system, id } => {
+                format!("{id} is in {system} more than once")
+            }
+            Self::FutureTimestamp { system, id } => {
+                format!("{id} in {system} has a time in the future")
+            }
+        }
+    }
+
+    /// Whether `repair` fixes it; the rest need someone to look at them.
+    pub fn repairable(&self) -> bool {
+        !matches!(self, Self::UnknownDestination { .. })
+    }
+}
+
+/// Leeway for clocks that are slightly out.
+fn is_future(at: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+    at > now + Duration::minutes(5)
+}
+
+/// Look for problems in the map, by system.
+pub fn check(app: &App) -> Vec<Problem> {
+    let now = Utc::now();
+    let mut systems: Vec<_> = app.system_data.keys().collect();
+    systems.sort();
+    let mut problems = Vec::new();
+    for system in systems {
+        let mut seen: Vec<&SignatureId> = Vec::new();
+        let mut duplicated: Vec<&SignatureId> = Vec::new();
+        for sig in &app.system_data[system] {
+            let id = &sig.identifier;
+            if !seen.contains(&id) {
+                seen.push(id);
+            } else if !duplicated.contains(&id) {
+                duplicated.push(id);
+                problems.push(Problem::DuplicateSignature {
+                    system: system.clone(),
+                    id: id.clone(),
+                });
+            }
+            if let SignatureType::Wormhole(wh) = &sig.signature_type {
+                if let Some(destination) = &wh.destination {
+                    if find_system(destination).is_none() {
+                        problems.push(Problem::UnknownDestination {
+                            system: system.clone(),
+                            id: id.clone(),
+                            destination: destination.clone(),
+                        });
+                    }
+                }
+            }
+            let edited = sig.last_edit.as_ref().map(|edit| edit.at);
+            if is_future(sig.created, now) || edited.is_some_and(|at| is_future(at, now)) {
+                problems.push(Problem::FutureTimestamp {
+                    system: system.clone(),
+                    id: id.clone(),
+                });
+            }
+        }
+    }
+    for connection in &app.connections {
+        for (system, id) in &connection.ends {
+            if !app.has_signature(system, id) {
+                problems.push(Problem::DanglingConnection {
+                    system: system.clone(),
+                    id: id.clone(),
+                });
+            }
+        }
+    }
+    problems
+}
+
+/// Fix the problems that are safe to fix, returning what was done.
+///
+/// Dangling connection ends are dropped, duplicates keep the newest entry,
+/// and times in the future are set to now. Each repair is logged.
+pub fn repair(app: &mut App) -> Vec<String> {
+    let now = Utc::now();
+    let mut done = Vec::new();
+    for (system, signatures) in app.system_data.iter_mut() {
+        let mut kept: Vec<Signature> = Vec::with_capacity(signatures.len());
+        for sig in signatures.drain(..) {
+            match kept.iter_mut().find(|s| s.identifier == sig.identifier) {
+                Some(existing) => {
+                    done.push(format!(
+                        "Removed an older copy of {} in {system}",
+                        sig.identifier
+                    ));
+                    if sig.created > existing.created {
+                        *existing = sig;
+                    }
+                }
+                None => kept.push(sig),
+            }
+        }
+        for sig in kept.iter_mut() {
+            let edit_in_future = sig
+                .last_edit
+                .as_ref()
+                .is_some_and(|edit| is_future(edit.at, now));
+            if is_future(sig.created, now) || edit_in_future {
+                sig.created = sig.created.min(now);
+                if let Some(edit) = sig.last_edit.as_mut() {
+                    edit.at = edit.at.min(now);
+                }
+                done.push(format!(
+                    "Set the future time on {} in {system} to now",
+                    sig.identifier
+                ));
+            }
+        }
+        *signatures = kept;
+    }
+    let system_data = &app.system_data;
+    for connection in app.connections.iter_mut() {
+        connection.ends.retain(|(system, id)| {
+            let present = system_data
+                .get(system)
+                .is_some_and(|sigs| sigs.iter().any(|sig| &sig.identifier == id));
+            if !present {
+                done.push(format!("Dropped the connection end for {id} in {system}"));
+            }
+            present
+        });
+    }
+    app.connections.retain(|c| !c.ends.is_empty());
+    done.sort();
+    for repair in &done {
+        info!("Repaired state: {repair}");
+    }
+    done
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check, repair, Problem};
+    use crate::{
+        chain::Connection,
+        eve_data::{Signature, SignatureId, SignatureType, SignatureWormhole},
+        state::App,
+    };
+    use chrono::{Duration, Utc};
+
+    fn broken_app() -> App {
+        let mut app = App::new();
+        let sigs = app.system_data.get_mut("J173213").unwrap();
+        let mut old = Signature::new("ABC", "123", SignatureType::Unknown);
+        old.created = Utc::now() - Duration::hours(1);
+        sigs.push(old);
+        let mut future = Signature::new(
+            "GHI",
+            "789",
+            SignatureType::Wormhole(SignatureWormhole {
+                destination: Some("Nowhere".to_owned()),
+                ..Default::default()
+            }),
+        );
+        future.created = Utc::now() + Duration::days(1);
+        sigs.push(future);
+        app.connections.push(Connection {
+            ends: vec![
+                ("J173213".to_owned(), SignatureId::new("DEF", "456")),
+                ("Thera".to_owned(), SignatureId::new("XYZ", "000")),
+            ],
+            note: None,
+        });
+        app
+    }
+
+    #[test]
+    fn test_check() {
+        assert!(check(&App::new()).is_empty());
+
+        let problems = check(&broken_app());
+        let labels: Vec<_> = problems.iter().map(Problem::label).collect();
+        assert_eq!(
+            labels,
+            vec![
+                "ABC-123 is in J173213 more than once",
+                "GHI-789 in J173213 leads to unknown system 'Nowhere'",
+                "GHI-789 in J173213 has a time in the future",
+                "Connection to XYZ-000 in Thera, which isn't in the map",
+            ]
+        );
+        assert!(!problems[1].repairable());
+    }
+
+    #[test]
+    fn test_repair() {
+        let mut app = broken_app();
+        let done = repair(&mut app);
+
+        assert_eq!(done.len(), 3);
+        let sigs = &app.system_data["J173213"];
+        assert_eq!(sigs.len(), 3);
+        // the newer copy is kept
+        assert_eq!(
+            sigs[0].signature_type,
+            SignatureType::Combat(Some("Some Combat Site".to_owned()))
+        );
+        assert!(sigs[2].created <= Utc::now());
+        assert_eq!(app.connections[0].ends.len(), 1);
+
+        let remaining = check(&app);
+        assert_eq!(remaining.len(), 1);
+        assert!(!remaining[0].repairable());
+        assert!(repair(&mut app).is_empty());
+    }
+}
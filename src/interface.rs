@@ -1,35 +1,146 @@
 use crate::{
-    eve_data::{parse_paste, Signature, SignatureType, ALL_SYSTEMS, WORMHOLE_TYPES},
-    state::{App, ViewMode},
+    actions,
+    activity::hourly_appearances,
+    alert::Alerter,
+    autopaste::ClipboardWatch,
+    chain::{self, map_line, node_badges, node_counter},
+    changelog,
+    config::{Config, ExportFormat, TimestampFormat},
+    csv_import::{self, CsvImport},
+    data_update,
+    effects::scaled_modifiers,
+    eve_data::{
+        find_system, ConnectionKind, Signature, SignatureFilter, SignatureType, SignatureWormhole,
+        StaticCount, StaticMatch, WormholeInfo, WormholeLife, WormholeMass, ALL_SYSTEMS,
+        WORMHOLE_TYPES,
+    },
+    export::{self, export_markdown, export_scanner, export_system, CHAIN_SIGS_FILE, REPORT_FILE},
+    integrity,
+    kills::KillCache,
+    listener,
+    merge::Resolution,
+    paging::page,
+    planets::{planets_line, PlanetCache},
+    regions::{tree_rows, TreeTarget, REGIONS},
+    rolling::{self, ShipMass, SHIP_MASSES},
+    routes::{HubRoute, RouteCache},
+    saver::{self, FileWriter, Saver},
+    schedule::Scheduler,
+    sites::site_details,
+    snapshot,
+    state::{App, ConfirmAction, PasteMode, PromptKind, ViewMode, STATE_FILE},
+    timestamps::format_timestamp,
+    update_check::{self, Release},
+    watcher::{self, ScanFile, REJECTED_DIR},
 };
 use anyhow::Result;
+use chrono::Utc;
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyModifiers,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use log::debug;
+use log::{debug, error, warn};
 use rfesi::prelude::Esi;
 use std::{
-    cell::Cell,
+    fs,
+    path::PathBuf,
     time::{Duration, Instant},
 };
+use tokio::sync::mpsc::UnboundedReceiver;
 use tui::{
     backend::CrosstermBackend,
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Span, Spans},
     widgets::{
-        Block, Borders, Clear, List, ListItem, ListState, Paragraph, Row, Table, TableState,
+        Block, Borders, Clear, List, ListItem, ListState, Paragraph, Row, Sparkline, Table,
+        TableState, Wrap,
     },
     Terminal,
 };
 
 const EVENT_POLL_RATE: u64 = 5;
+/// Seconds between checks for input in low-power mode once idle.
+const IDLE_POLL_RATE: u64 = 30;
+/// Time without input before low-power mode slows down.
+const IDLE_AFTER: Duration = Duration::from_secs(120);
 const API_POLL_RATE: u64 = 15;
+/// How often to check for listener requests while waiting for input.
+const REQUEST_CHECK_RATE: Duration = Duration::from_millis(50);
+/// File the chain is exported to as a Graphviz graph.
+const DOT_FILE: &str = "chain.dot";
+/// Least time between writes of the state file.
+const SAVE_DEBOUNCE: Duration = Duration::from_secs(1);
 
 /// Run the TUI.
-pub async fn run(_esi: Esi) -> Result<()> {
+pub async fn run(esi: Option<Esi>, config: &Config) -> Result<()> {
+    let mut config = config.clone();
+    let mut app = App::load()?;
+    apply_config(&mut app, &config);
+    app.reset_edit_baseline();
+    app.saver = Some(saver::spawn(
+        FileWriter(PathBuf::from(STATE_FILE)),
+        SAVE_DEBOUNCE,
+    ));
+    let problems = integrity::check(&app);
+    if !problems.is_empty() {
+        for problem in &problems {
+            warn!("State problem: {}", problem.label());
+        }
+        app.status_message = Some(format!(
+            "{} problem(s) in the saved map; press K to review",
+            problems.len()
+        ));
+    }
+    let outdated = data_update::outdated_user_data();
+    if !outdated.is_empty() {
+        for file in &outdated {
+            warn!("{file} is older than the bundled static data, so isn't used");
+        }
+        app.status_message.get_or_insert_with(|| {
+            "Updated static data is older than the bundled data; run update-data".to_owned()
+        });
+    }
+    if let Some(hours) = config.retention_hours {
+        let pruned = app.prune_older_than(chrono::Duration::hours(hours as i64));
+        if pruned > 0 {
+            save(&mut app);
+        }
+    }
+    if let Some(notes) = changelog::whats_new(app.last_seen_version.as_deref()) {
+        app.view = ViewMode::WhatsNew(notes);
+    }
+    if app.last_seen_version.as_deref() != Some(changelog::VERSION) {
+        app.last_seen_version = Some(changelog::VERSION.to_owned());
+        save(&mut app);
+    }
+    let mut alerter = Alerter::new(config.alert_mode);
+    let mut clipboard_watch = ClipboardWatch::new(config.clipboard_watch && !config.read_only);
+    let mut routes = RouteCache::default();
+    let mut planets = PlanetCache::default();
+    let mut kills = KillCache::default();
+    let mut scheduler = config
+        .scheduled_export
+        .clone()
+        .map(|export| Scheduler::new(export, config.report));
+    let mut scan_files = config
+        .scan_dir
+        .as_ref()
+        .map(|dir| watcher::spawn(PathBuf::from(dir)));
+    let mut releases = config.check_for_updates.then(update_check::spawn);
+    let mut new_release: Option<Release> = None;
+    let mut commands = match &config.listener {
+        Some(listener) => Some(
+            listener::spawn(listener.port, listener.token.clone())
+                .await?
+                .1,
+        ),
+        None => None,
+    };
+
     // configure terminal
     let mut stdout = std::io::stdout();
     execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
@@ -38,23 +149,111 @@ pub async fn run(_esi: Esi) -> Result<()> {
     enable_raw_mode()?;
     terminal.hide_cursor()?;
 
-    let mut app = App::new();
     // delay first ESI query
     let mut last_updated = Instant::now();
+    // last input, for low-power mode
+    let mut last_activity = Instant::now();
+    // signature rows that fit on screen, as of the last draw
+    let mut visible_sig_rows = 0;
+    // rows that fit in the map pane and in list popups, as of the last draw
+    let mut visible_map_rows = 0;
+    let mut visible_popup_rows = 0;
+    let mut palette_key: Option<KeyEvent> = None;
+    // only suggest updating the static data once a session
+    let mut stale_data_hinted = false;
 
     // app loop
     loop {
         // update data every few seconds
         if last_updated.elapsed() >= Duration::from_secs(API_POLL_RATE) {
             debug!("Query ESI");
+            let mut systems: Vec<String> = app.system_data.keys().cloned().collect();
+            systems.extend(
+                chain::chain_edges(&app.system_data)
+                    .into_iter()
+                    .map(|edge| edge.to.to_owned()),
+            );
+            systems.sort();
+            systems.dedup();
+            if let (Some(esi), Some(system)) = (&esi, routes.next_missing(&systems)) {
+                let system = system.to_owned();
+                routes.fetch(esi, &system);
+            }
+            if let (Some(esi), Some(system)) = (&esi, app.current_system.as_ref()) {
+                if !planets.has_tried(system) {
+                    planets.fetch(esi, system);
+                }
+            }
+            if let Some(esi) = &esi {
+                if kills.is_due() {
+                    kills.fetch(esi, systems);
+                }
+            }
             last_updated = Instant::now();
         }
+        routes.receive();
+        planets.receive();
+        if let Some(recent) = kills.receive() {
+            app.recent_kills = recent;
+        }
+        if let Some(system) = routes
+            .take_unknown_to_esi()
+            .or_else(|| planets.take_unknown_to_esi())
+        {
+            warn!("ESI doesn't know {system}; the static data may be out of date");
+            if !stale_data_hinted {
+                app.status_message = Some(format!(
+                    "ESI doesn't know {system}; the static data may be out of date, try update-data"
+                ));
+                stale_data_hinted = true;
+            }
+        }
+        if let Some(scheduler) = scheduler.as_mut() {
+            scheduler.tick(&app);
+        }
+        if clipboard_watch.enabled && app.view == ViewMode::Normal {
+            if let Ok(clipboard) = cli_clipboard::get_contents() {
+                if clipboard_watch.is_new_scan(&clipboard) {
+                    debug!("Pasting new scanner results from the clipboard");
+                    paste(&mut app, &mut alerter, &clipboard, PasteMode::Partial);
+                }
+            }
+        }
+        while let Some(scan) = scan_files.as_mut().and_then(|rx| rx.try_recv().ok()) {
+            merge_scan_file(&mut app, scan);
+        }
+        while let Some(pending) = commands.as_mut().and_then(|rx| rx.try_recv().ok()) {
+            let response = listener::apply(&mut app, pending.command);
+            if response.ok {
+                save(&mut app);
+            }
+            app.status_message = Some(response.message.clone());
+            let _ = pending.reply.send(response);
+        }
+        if let Some(release) = releases.as_mut().and_then(|rx| rx.try_recv().ok()) {
+            app.status_message = Some(format!(
+                "Version {} is available: {}",
+                release.version, release.url
+            ));
+            new_release = Some(release);
+        }
+        if let Some(e) = app.saver.as_mut().and_then(Saver::take_error) {
+            app.status_message = Some(format!("Could not save state, retrying: {e}"));
+        }
         let system_sig_count = app.system_signatures().len();
+        let flashing = alerter.is_flashing(Instant::now());
 
         let _ = terminal.draw(|f| {
             let chunks = Layout::default()
                 .direction(Direction::Vertical)
-                .constraints([Constraint::Percentage(50), Constraint::Min(0)].as_ref())
+                .constraints(
+                    [
+                        Constraint::Percentage(50),
+                        Constraint::Min(0),
+                        Constraint::Length(1),
+                    ]
+                    .as_ref(),
+                )
                 .split(f.size());
 
             let top_chunks = Layout::default()
@@ -68,26 +267,90 @@ pub async fn run(_esi: Esi) -> Result<()> {
                         let block = Block::default()
                             .title(current_system.to_string())
                             .borders(Borders::ALL);
-                        let mut spans = vec![
-                            Spans::from(vec![
+                        let mut spans = vec![Spans::from(vec![
+                            Span::styled("Type: ", Style::default().add_modifier(Modifier::BOLD)),
+                            Span::styled(
+                                data.classification().as_str(),
+                                style_for_system(&data.classification().as_str()),
+                            ),
+                        ])];
+                        spans.push(Spans::from(vec![Span::raw(planets_line(
+                            planets.get(current_system),
+                        ))]));
+                        if let (Some(effect), Some(class)) = (&data.effect, data.class) {
+                            spans.push(Spans::from(vec![
                                 Span::styled(
-                                    "Type: ",
+                                    "Effect: ",
                                     Style::default().add_modifier(Modifier::BOLD),
                                 ),
-                                Span::styled(
-                                    data.classification().as_str(),
-                                    style_for_system(&data.classification().as_str()),
-                                ),
-                            ]),
-                            Spans::from(Vec::new()),
-                            Spans::from(vec![Span::raw("Static connections:")]),
-                        ];
+                                Span::raw(effect.clone()),
+                            ]));
+                            for (modifier, value) in scaled_modifiers(effect, class) {
+                                spans.push(Spans::from(vec![Span::raw(format!(
+                                    "  {modifier} {value:+}%"
+                                ))]));
+                            }
+                        }
+                        if let Some(note) = app.system_note(current_system) {
+                            spans.push(Spans::from(vec![
+                                Span::styled("Note: ", Style::default().add_modifier(Modifier::BOLD)),
+                                Span::raw(note.to_owned()),
+                            ]));
+                        }
+                        spans.push(Spans::from(vec![Span::raw(format_last_scanned(
+                            &app,
+                            current_system,
+                            config.timestamp_format,
+                        ))]));
+                        let inbound = app.inbound_connections();
+                        if !inbound.is_empty() {
+                            spans.push(Spans::from(Vec::new()));
+                            spans.push(Spans::from(vec![Span::raw(
+                                "Inbound connections (b):",
+                            )]));
+                            for (system, sig) in inbound {
+                                spans.push(Spans::from(vec![Span::raw(format!(
+                                    "  {}",
+                                    format_inbound(system, sig)
+                                ))]));
+                            }
+                        }
+                        spans.push(Spans::from(Vec::new()));
+                        spans.push(Spans::from(vec![Span::raw("Static connections:")]));
                         if data.class.is_some() {
                             let statics = format_system_statics(&data.statics);
                             spans.extend(statics);
+                            if !data.statics.is_empty() {
+                                let signatures =
+                                    app.system_data.get(current_system).map(Vec::as_slice);
+                                let count =
+                                    StaticCount::new(&data.statics, signatures.unwrap_or_default());
+                                spans.push(Spans::from(vec![Span::raw(count.label())]));
+                            }
+                        } else {
+                            spans.push(Spans::from(Vec::new()));
+                            spans.extend(format_hub_routes(routes.get(current_system).map(Vec::as_slice)));
                         }
+                        let info_chunks = Layout::default()
+                            .direction(Direction::Vertical)
+                            .constraints([Constraint::Min(0), Constraint::Length(4)].as_ref())
+                            .split(top_chunks[0]);
                         let static_info_p = Paragraph::new(spans).block(block);
-                        f.render_widget(static_info_p, top_chunks[0]);
+                        f.render_widget(static_info_p, info_chunks[0]);
+                        let activity = app
+                            .activity
+                            .get(current_system)
+                            .map(|events| hourly_appearances(events, Utc::now()))
+                            .unwrap_or_default();
+                        let sparkline = Sparkline::default()
+                            .block(
+                                Block::default()
+                                    .title("New signatures, 24h")
+                                    .borders(Borders::ALL),
+                            )
+                            .style(Style::default().fg(Color::Cyan))
+                            .data(&activity);
+                        f.render_widget(sparkline, info_chunks[1]);
                     }
                 }
                 None => {
@@ -103,27 +366,83 @@ pub async fn run(_esi: Esi) -> Result<()> {
             let mut block = Block::default()
                 .title("Scanning data")
                 .borders(Borders::ALL);
-            if app.view == ViewMode::Normal {
+            if flashing {
+                block = block.border_style(Style::default().fg(Color::Red));
+            } else if app.view == ViewMode::Normal {
                 block = block.border_style(Style::default().fg(Color::Yellow));
             }
+            let now = Utc::now();
+            let system_class = app
+                .current_system
+                .as_ref()
+                .and_then(|s| ALL_SYSTEMS.get(s))
+                .and_then(|d| d.class);
+            // borders and header
+            visible_sig_rows = top_chunks[1].height.saturating_sub(3) as usize;
             let table_items = match app.current_system.as_ref() {
-                Some(s) => match app.system_data.get(s) {
-                    Some(d) => d.iter().map(|e| Row::new(e.to_row())).collect(),
-                    None => Vec::new(),
-                },
+                Some(s) => app
+                    .system_signatures()
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, e)| {
+                            let mut row = e.to_row_labelled(config.short_type_labels);
+                            if app.is_favorite(s, &e.identifier) {
+                                row[0] = format!("* {}", row[0]);
+                            }
+                            if app.is_pinned(s, &e.identifier) {
+                                row[0] = format!("^ {}", row[0]);
+                            }
+                            if app.selection.contains(&e.identifier) {
+                                row[0] = format!("+ {}", row[0]);
+                            }
+                            if let Some(direction) = app.wormhole_direction(s, e) {
+                                row[1] = format!("{} {}", direction.glyph(), row[1]);
+                            }
+                            row[0] = match app.row_digit(index, visible_sig_rows) {
+                                Some(digit) => format!("{digit} {}", row[0]),
+                                None => format!("  {}", row[0]),
+                            };
+                            row.push(format_timestamp(e.created, now, config.timestamp_format));
+                            if config.show_scan_estimate {
+                                row.push(e.scan_estimate());
+                            }
+                            if e.ignored {
+                                return Row::new(row).style(Style::default().fg(Color::DarkGray));
+                            }
+                            if e.signature_type.danger_warning(system_class).is_some() {
+                                row[1] = format!("! {}", row[1]);
+                                return Row::new(row).style(Style::default().fg(Color::Red));
+                            }
+                            if let SignatureType::Wormhole(SignatureWormhole {
+                                destination: Some(dest),
+                                ..
+                            }) = &e.signature_type
+                            {
+                                if config.color_by_destination {
+                                    let color = color_for_destination(dest);
+                                    return Row::new(row).style(Style::default().fg(color));
+                                }
+                            }
+                            Row::new(row)
+                        })
+                        .collect(),
                 None => Vec::new(),
             };
+            let mut header = vec!["ID", "Type", "Leads to", "Life/Mass/Value", "Age"];
+            let mut widths = vec![
+                Constraint::Min(14),
+                Constraint::Min(if config.short_type_labels { 12 } else { 30 }),
+                Constraint::Min(40),
+                Constraint::Min(18),
+                Constraint::Min(16),
+            ];
+            if config.show_scan_estimate {
+                header.push("Scan");
+                widths.push(Constraint::Min(9));
+            }
             let sigs = Table::new(table_items)
-                .header(
-                    Row::new(vec!["ID", "Type", "Leads to", "Life/Mass"])
-                        .style(Style::default().fg(Color::Blue)),
-                )
-                .widths(&[
-                    Constraint::Min(10),
-                    Constraint::Min(30),
-                    Constraint::Min(100),
-                    Constraint::Min(18),
-                ])
+                .header(Row::new(header).style(Style::default().fg(Color::Blue)))
+                .widths(&widths)
                 .block(block)
                 .highlight_symbol(">> ");
             let mut sigs_state = TableState::default();
@@ -132,12 +451,412 @@ pub async fn run(_esi: Esi) -> Result<()> {
             }
             f.render_stateful_widget(sigs, top_chunks[1], &mut sigs_state);
 
-            let block = Block::default().title("Map").borders(Borders::ALL);
-            f.render_widget(block, chunks[1]);
+            let map_nodes = app.map_nodes();
+            let map_title = match app.view {
+                ViewMode::Map(selected) => match map_nodes.get(selected) {
+                    Some(node) => format!(
+                        "Map - {}: {}",
+                        node.system,
+                        format_last_scanned(&app, &node.system, config.timestamp_format)
+                    ),
+                    None => "Map".to_owned(),
+                },
+                _ => "Map".to_owned(),
+            };
+            let mut block = Block::default().title(map_title).borders(Borders::ALL);
+            if flashing {
+                block = block.border_style(Style::default().fg(Color::Red));
+            } else if let ViewMode::Map(_) = app.view {
+                block = block.border_style(Style::default().fg(Color::Yellow));
+            }
+            let map_items: Vec<_> = map_nodes
+                .iter()
+                .map(|node| {
+                    let mut line = map_line(&app.system_data, node);
+                    if !node.collapsed.is_empty() {
+                        return ListItem::new(line).style(Style::default().fg(Color::DarkGray));
+                    }
+                    let mut style = Style::default();
+                    if node_counter(&app.system_data, &node.system)
+                        .is_some_and(|(_, unknown)| unknown > 0)
+                    {
+                        style = style.fg(Color::Yellow);
+                    }
+                    let badges = node_badges(
+                        &app.system_data,
+                        &app.recent_kills,
+                        routes.get(&node.system).map(Vec::as_slice),
+                        &node.system,
+                    )
+                    .render(config.badge_glyphs);
+                    if !badges.is_empty() {
+                        line.push_str(&format!("  {badges}"));
+                    }
+                    ListItem::new(line).style(style)
+                })
+                .collect();
+            let map = List::new(map_items)
+                .block(block)
+                .highlight_symbol(">> ");
+            let mut map_state = ListState::default();
+            if let ViewMode::Map(selected) = app.view {
+                map_state.select(Some(selected));
+            }
+            visible_map_rows = chunks[1].height.saturating_sub(2) as usize;
+            f.render_stateful_widget(map, chunks[1], &mut map_state);
+
+            let status = match &app.status_message {
+                Some(message) => Paragraph::new(Span::styled(
+                    message.as_str(),
+                    Style::default().fg(Color::Yellow),
+                )),
+                None => {
+                    let mut hint = app.probe_filter_hint().to_owned();
+                    if app.read_only {
+                        hint = format!("read-only  |  {hint}");
+                    }
+                    if let Some(at) = scheduler.as_ref().and_then(|s| s.last_export) {
+                        hint.push_str(&format!(
+                            "  |  Last export {}",
+                            format_timestamp(at, Utc::now(), config.timestamp_format)
+                        ));
+                    }
+                    Paragraph::new(Span::styled(hint, Style::default().fg(Color::DarkGray)))
+                }
+            };
+            f.render_widget(status, chunks[2]);
 
-            if app.view != ViewMode::Normal {
+            if let ViewMode::Favorites(selected) = app.view {
+                let items: Vec<_> = app
+                    .favorites
+                    .iter()
+                    .map(|(system, id)| ListItem::new(format!("{system}  {id}")))
+                    .collect();
+                let list = List::new(items)
+                    .block(
+                        Block::default()
+                            .border_style(Style::default().fg(Color::Yellow))
+                            .title("Favorites")
+                            .borders(Borders::ALL),
+                    )
+                    .highlight_symbol(">> ");
+                let mut list_state = ListState::default();
+                if !app.favorites.is_empty() {
+                    list_state.select(Some(selected));
+                }
+                let area = centered_rect(40, 40, f.size());
+                visible_popup_rows = area.height.saturating_sub(2) as usize;
+                f.render_widget(Clear, area);
+                f.render_stateful_widget(list, area, &mut list_state);
+            } else if let ViewMode::Palette(query, selected) = &app.view {
+                let items: Vec<_> = actions::search(query)
+                    .into_iter()
+                    .map(|action| {
+                        let item =
+                            ListItem::new(format!("{}  [{}]", action.name, action.binding()));
+                        if app.read_only && action.edits {
+                            item.style(Style::default().fg(Color::DarkGray))
+                        } else {
+                            item
+                        }
+                    })
+                    .collect();
+                let has_matches = !items.is_empty();
+                let list = List::new(items)
+                    .block(
+                        Block::default()
+                            .border_style(Style::default().fg(Color::Yellow))
+                            .title(format!("Action: {query}"))
+                            .borders(Borders::ALL),
+                    )
+                    .highlight_symbol(">> ");
+                let mut list_state = ListState::default();
+                if has_matches {
+                    list_state.select(Some(*selected));
+                }
+                let area = centered_rect(40, 40, f.size());
+                visible_popup_rows = area.height.saturating_sub(2) as usize;
+                f.render_widget(Clear, area);
+                f.render_stateful_widget(list, area, &mut list_state);
+            } else if let ViewMode::Inbound(selected) = app.view {
+                let inbound = app.inbound_connections();
+                let items: Vec<_> = inbound
+                    .iter()
+                    .map(|(system, sig)| ListItem::new(format_inbound(system, sig)))
+                    .collect();
+                let list = List::new(items)
+                    .block(
+                        Block::default()
+                            .border_style(Style::default().fg(Color::Yellow))
+                            .title("Inbound connections")
+                            .borders(Borders::ALL),
+                    )
+                    .highlight_symbol(">> ");
+                let mut list_state = ListState::default();
+                if !inbound.is_empty() {
+                    list_state.select(Some(selected));
+                }
+                let area = centered_rect(40, 40, f.size());
+                visible_popup_rows = area.height.saturating_sub(2) as usize;
+                f.render_widget(Clear, area);
+                f.render_stateful_widget(list, area, &mut list_state);
+            } else if let ViewMode::Prompt(kind, input) = &app.view {
+                let block = Block::default()
+                    .border_style(Style::default().fg(Color::Yellow))
+                    .title(kind.title())
+                    .borders(Borders::ALL);
+                let mut text = format!("{input}_");
+                if *kind == PromptKind::WormholeType {
+                    let statics = app
+                        .current_system
+                        .as_ref()
+                        .and_then(|system| ALL_SYSTEMS.get(system))
+                        .map(|data| {
+                            data.statics
+                                .iter()
+                                .map(|wh_type| match WORMHOLE_TYPES.get(wh_type) {
+                                    Some(info) => format!("{wh_type} → {}", info.leads_to),
+                                    None => wh_type.clone(),
+                                })
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        })
+                        .unwrap_or_default();
+                    if !statics.is_empty() {
+                        text.push_str(&format!(
+                            "\n\nStatics: {statics}\nEnter a class (e.g. C3, HS) to fill in its static"
+                        ));
+                    }
+                }
+                let paragraph = Paragraph::new(text).block(block);
+                let area = centered_rect(40, 20, f.size());
+                f.render_widget(Clear, area);
+                f.render_widget(paragraph, area);
+            } else if let ViewMode::Rolling(ship_index) = app.view {
+                let block = Block::default()
+                    .border_style(Style::default().fg(Color::Yellow))
+                    .title("Rolling")
+                    .borders(Borders::ALL);
+                let wormhole = app
+                    .selected_signature()
+                    .and_then(|sig| match &sig.signature_type {
+                        SignatureType::Wormhole(wh) => wh.info().map(|info| (info, wh.mass_used)),
+                        _ => None,
+                    });
+                let text = match (wormhole, SHIP_MASSES.get(ship_index)) {
+                    (Some((info, used)), Some(ship)) => format_rolling_plan(info, used, ship),
+                    _ => "Hole type unknown".to_owned(),
+                };
+                let paragraph = Paragraph::new(text).block(block).wrap(Wrap { trim: true });
+                let area = centered_rect(50, 40, f.size());
+                f.render_widget(Clear, area);
+                f.render_widget(paragraph, area);
+            } else if let ViewMode::Confirm { message, action } = &app.view {
+                let block = Block::default()
+                    .border_style(Style::default().fg(Color::Yellow))
+                    .title(action.title())
+                    .borders(Borders::ALL);
+                let paragraph = Paragraph::new(message.as_str())
+                    .block(block)
+                    .wrap(Wrap { trim: true });
+                let area = centered_rect(50, 30, f.size());
+                f.render_widget(Clear, area);
+                f.render_widget(paragraph, area);
+            } else if let ViewMode::PasteMode(text) = &app.view {
+                let block = Block::default()
+                    .border_style(Style::default().fg(Color::Yellow))
+                    .title("Paste")
+                    .borders(Borders::ALL);
+                let breakdown = app.paste_breakdown(text);
+                let message = if breakdown.is_empty() {
+                    let missing = app.missing_from_paste(text).len();
+                    format!(
+                        "{missing} signature(s) aren't in this paste.\n\n(f)ull scan: remove them\n(p)artial copy: keep them"
+                    )
+                } else {
+                    let mut message = String::new();
+                    for (system, count) in breakdown {
+                        match system {
+                            Some(system) => {
+                                message.push_str(&format!("{system}: {count} signature(s)\n"))
+                            }
+                            None => message
+                                .push_str(&format!("No system selected: {count} dropped\n")),
+                        }
+                    }
+                    message.push_str("\nImport? (y/n)");
+                    message
+                };
+                let paragraph = Paragraph::new(message)
+                .block(block)
+                .wrap(Wrap { trim: true });
+                let area = centered_rect(40, 25, f.size());
+                f.render_widget(Clear, area);
+                f.render_widget(paragraph, area);
+            } else if let ViewMode::Browse(selected) = app.view {
+                let items: Vec<_> = tree_rows(&REGIONS, &app.browse_expanded)
+                    .into_iter()
+                    .map(|row| ListItem::new(format!("{}{}", "  ".repeat(row.depth), row.label)))
+                    .collect();
+                let list = List::new(items)
+                    .block(
+                        Block::default()
+                            .border_style(Style::default().fg(Color::Yellow))
+                            .title("Systems by class")
+                            .borders(Borders::ALL),
+                    )
+                    .highlight_symbol(">> ");
+                let mut list_state = ListState::default();
+                list_state.select(Some(selected));
+                let area = centered_rect(40, 60, f.size());
+                visible_popup_rows = area.height.saturating_sub(2) as usize;
+                f.render_widget(Clear, area);
+                f.render_stateful_widget(list, area, &mut list_state);
+            } else if let ViewMode::ImportConflicts(selected) = app.view {
+                let items: Vec<_> = app
+                    .import_conflicts
+                    .iter()
+                    .map(|conflict| ListItem::new(conflict.label()))
+                    .collect();
+                let list = List::new(items)
+                    .block(
+                        Block::default()
+                            .border_style(Style::default().fg(Color::Yellow))
+                            .title("Conflicts: (m)ine/(t)heirs, M/T for the whole system")
+                            .borders(Borders::ALL),
+                    )
+                    .highlight_symbol(">> ");
+                let mut list_state = ListState::default();
+                if !app.import_conflicts.is_empty() {
+                    list_state.select(Some(selected));
+                }
+                let area = centered_rect(80, 50, f.size());
+                visible_popup_rows = area.height.saturating_sub(2) as usize;
+                f.render_widget(Clear, area);
+                f.render_stateful_widget(list, area, &mut list_state);
+            } else if let ViewMode::Integrity(problems) = &app.view {
+                let block = Block::default()
+                    .border_style(Style::default().fg(Color::Yellow))
+                    .title("Map problems")
+                    .borders(Borders::ALL);
+                let mut text: Vec<_> = problems.iter().map(|problem| problem.label()).collect();
+                text.push(String::new());
+                if app.read_only || !problems.iter().any(|problem| problem.repairable()) {
+                    text.push("Press any key to close".to_owned());
+                } else {
+                    text.push("r: repair what's safe to, any other key closes".to_owned());
+                }
+                let paragraph = Paragraph::new(text.join("\n"))
+                    .block(block)
+                    .wrap(Wrap { trim: false });
+                let area = centered_rect(70, 60, f.size());
+                f.render_widget(Clear, area);
+                f.render_widget(paragraph, area);
+            } else if let ViewMode::Help(scroll) = app.view {
+                let block = Block::default()
+                    .border_style(Style::default().fg(Color::Yellow))
+                    .title(format!("Keys - evemapping {}", changelog::VERSION))
+                    .borders(Borders::ALL);
+                let mut text = vec![format!("Static data: {}", data_update::data_version())];
+                if let Some(release) = &new_release {
+                    text.push(format!(
+                        "Version {} is available: {}",
+                        release.version, release.url
+                    ));
+                }
+                text.push(String::new());
+                text.extend(actions::help_lines());
+                let area = centered_rect(60, 70, f.size());
+                visible_popup_rows = area.height.saturating_sub(2) as usize;
+                let paragraph = Paragraph::new(text.join("\n"))
+                    .block(block)
+                    .scroll((scroll as u16, 0));
+                f.render_widget(Clear, area);
+                f.render_widget(paragraph, area);
+            } else if let ViewMode::CsvImport(import) = &app.view {
+                let block = Block::default()
+                    .border_style(Style::default().fg(Color::Yellow))
+                    .title("Import table")
+                    .borders(Borders::ALL);
+                let mut text = import.preview();
+                text.push(String::new());
+                text.push("Left/Right: column, Space: change what it holds, Enter: import".to_owned());
+                let paragraph = Paragraph::new(text.join("\n"))
+                    .block(block)
+                    .wrap(Wrap { trim: false });
+                let area = centered_rect(70, 60, f.size());
+                f.render_widget(Clear, area);
+                f.render_widget(paragraph, area);
+            } else if let ViewMode::WhatsNew(notes) = &app.view {
+                let block = Block::default()
+                    .border_style(Style::default().fg(Color::Yellow))
+                    .title(format!("What's new in {}", changelog::VERSION))
+                    .borders(Borders::ALL);
+                let text = format!(
+                    "{notes}\n\nStatic data: {}",
+                    data_update::data_version()
+                );
+                let paragraph = Paragraph::new(text)
+                    .block(block)
+                    .wrap(Wrap { trim: false });
+                let area = centered_rect(60, 60, f.size());
+                f.render_widget(Clear, area);
+                f.render_widget(paragraph, area);
+            } else if let ViewMode::StaticPlaceholder(wh_type) = &app.view {
+                let block = Block::default()
+                    .border_style(Style::default().fg(Color::Yellow))
+                    .title("Static closed")
+                    .borders(Borders::ALL);
+                let paragraph = Paragraph::new(format!(
+                    "The {wh_type} static will respawn.\n\nAdd an unscanned placeholder for it? (y/n)"
+                ))
+                .block(block)
+                .wrap(Wrap { trim: true });
+                let area = centered_rect(40, 20, f.size());
+                f.render_widget(Clear, area);
+                f.render_widget(paragraph, area);
+            } else if let ViewMode::ExitRoute(safe_only) = app.view {
+                let title = if safe_only {
+                    "Route to exit (avoiding EOL/crit)"
+                } else {
+                    "Route to exit"
+                };
+                let block = Block::default()
+                    .border_style(Style::default().fg(Color::Yellow))
+                    .title(title)
+                    .borders(Borders::ALL);
+                let text = match app.exit_route(safe_only) {
+                    Some(route) => format!(
+                        "{}\n\n{} hop(s) to {}\n\ns: toggle avoiding EOL/crit holes",
+                        route.join(" -> "),
+                        route.len() - 1,
+                        route.last().unwrap()
+                    ),
+                    None => "No k-space exit found\n\ns: toggle avoiding EOL/crit holes".to_owned(),
+                };
+                let paragraph = Paragraph::new(text).block(block).wrap(Wrap { trim: true });
+                let area = centered_rect(50, 30, f.size());
+                f.render_widget(Clear, area);
+                f.render_widget(paragraph, area);
+            } else if app.view != ViewMode::Normal {
                 let title = match &app.view {
-                    ViewMode::Normal => "",
+                    ViewMode::Normal
+                    | ViewMode::Favorites(_)
+                    | ViewMode::Prompt(_, _)
+                    | ViewMode::ExitRoute(_)
+                    | ViewMode::Rolling(_)
+                    | ViewMode::Inbound(_)
+                    | ViewMode::Map(_)
+                    | ViewMode::Confirm { .. }
+                    | ViewMode::WhatsNew(_)
+                    | ViewMode::Integrity(_)
+                    | ViewMode::CsvImport(_)
+                    | ViewMode::Help(_)
+                    | ViewMode::ImportConflicts(_)
+                    | ViewMode::Browse(_)
+                    | ViewMode::PasteMode(_)
+                    | ViewMode::Palette(..)
+                    | ViewMode::StaticPlaceholder(_) => "",
                     ViewMode::Adding(_) => "Add",
                     ViewMode::Editing(sig) => &format!("Edit {}", sig.identifier),
                 };
@@ -145,71 +864,969 @@ pub async fn run(_esi: Esi) -> Result<()> {
                     .border_style(Style::default().fg(Color::Yellow))
                     .title(title)
                     .borders(Borders::ALL);
+                let details = match &app.view {
+                    ViewMode::Adding(sig) | ViewMode::Editing(sig) => {
+                        let mut details = sig.to_string();
+                        if let Some(warning) = sig.signature_type.danger_warning(system_class) {
+                            details.push_str(&format!("\n\n! {warning}"));
+                        }
+                        if let Some(note) = app
+                            .current_system
+                            .as_ref()
+                            .and_then(|system| app.connection(system, &sig.identifier))
+                            .and_then(|connection| connection.note.as_ref())
+                        {
+                            details.push_str(&format!("\n\nNote: {note}"));
+                        }
+                        let site_lines = site_details(&sig.signature_type);
+                        if !site_lines.is_empty() {
+                            details.push_str(&format!("\n\n{}", site_lines.join("\n")));
+                        }
+                        if let Some(provenance) = sig.provenance {
+                            details.push_str(&format!("\n\nSource: {}", provenance.label()));
+                        }
+                        if let Some(edit) = &sig.last_edit {
+                            details.push_str(&format!(
+                                "\n\nLast edited by {}, {}",
+                                edit.by,
+                                format_timestamp(edit.at, Utc::now(), config.timestamp_format)
+                            ));
+                        }
+                        details
+                    }
+                    _ => String::new(),
+                };
+                let paragraph = Paragraph::new(details)
+                    .block(block)
+                    .wrap(Wrap { trim: true });
                 let area = centered_rect(40, 40, f.size());
                 f.render_widget(Clear, area);
-                f.render_widget(block, area);
+                f.render_widget(paragraph, area);
             }
         })?;
 
         // keyboard interaction
-        if event::poll(Duration::from_secs(EVENT_POLL_RATE))? {
-            if let Event::Key(key) = event::read()? {
-                // can always close modals to get back to normal view
-                if key.code == KeyCode::Esc {
-                    app.view = ViewMode::Normal;
+        // a key picked from the command palette is handled as if it had been pressed
+        let key = match palette_key.take() {
+            Some(key) => Some(key),
+            None if wait_for_input(
+                poll_interval(last_activity.elapsed(), config.low_power && !flashing),
+                commands.as_ref(),
+            )? =>
+            {
+                last_activity = Instant::now();
+                match event::read()? {
+                    Event::Key(key) => Some(key),
+                    _ => None,
                 }
+            }
+            None => None,
+        };
+        if let Some(key) = key {
+            // any key dismisses the status message
+            app.status_message = None;
 
-                match &app.view {
-                    ViewMode::Normal => {
-                        // normal state
-                        match key.code {
-                            KeyCode::Char('q') => break,
-                            KeyCode::Enter => {
-                                if system_sig_count > 0 {
-                                    if let Some(current_system) = app.current_system.as_ref() {
-                                        if let Some(data) = app.system_data.get(current_system) {
-                                            let sigs_to_edit = data.get(app.data_index).unwrap();
-                                            app.view = ViewMode::Editing(sigs_to_edit.clone());
-                                        }
+            // can always close modals to get back to normal view
+            if key.code == KeyCode::Esc {
+                app.view = ViewMode::Normal;
+            }
+
+            match &app.view {
+                ViewMode::Normal => {
+                    // normal state
+                    if intercept_key(&mut app, &key) {
+                        continue;
+                    }
+                    match key.code {
+                        KeyCode::Char('q') => break,
+                        KeyCode::Enter if system_sig_count > 0 => {
+                            if let Some(sig_to_edit) = app.selected_signature() {
+                                app.view = ViewMode::Editing(sig_to_edit.clone());
+                            }
+                        }
+                        KeyCode::Down => {
+                            if system_sig_count > 1 && app.data_index < system_sig_count - 1 {
+                                app.data_index += 1;
+                            } else {
+                                app.data_index = 0;
+                            }
+                        }
+                        KeyCode::Up => {
+                            if system_sig_count > 1 && app.data_index > 0 {
+                                app.data_index -= 1;
+                            } else {
+                                app.data_index = system_sig_count - 1;
+                            }
+                        }
+                        code @ (KeyCode::PageUp
+                        | KeyCode::PageDown
+                        | KeyCode::Home
+                        | KeyCode::End) => {
+                            if let Some(index) =
+                                page(code, app.data_index, system_sig_count, visible_sig_rows)
+                            {
+                                app.data_index = index;
+                            }
+                        }
+                        KeyCode::Char(c @ '1'..='9') => {
+                            let digit = c.to_digit(10).unwrap() as usize;
+                            app.select_row_digit(digit, visible_sig_rows);
+                        }
+                        KeyCode::Char('n') => {
+                            app.view = ViewMode::Adding(Signature::default());
+                        }
+                        KeyCode::Char('v') => {
+                            if let Ok(clipboard) = cli_clipboard::get_contents() {
+                                if csv_import::is_table(&clipboard) {
+                                    if let Ok(table) = csv_import::parse(&clipboard) {
+                                        app.view = ViewMode::CsvImport(CsvImport::new(table));
+                                    }
+                                } else if app.missing_from_paste(&clipboard).is_empty()
+                                    && app.paste_breakdown(&clipboard).is_empty()
+                                {
+                                    paste(&mut app, &mut alerter, &clipboard, PasteMode::Partial);
+                                } else {
+                                    app.view = ViewMode::PasteMode(clipboard);
+                                }
+                            }
+                        }
+                        KeyCode::Char('p') if system_sig_count > 0 => {
+                            app.toggle_pin_selected();
+                            save(&mut app);
+                        }
+                        KeyCode::Char('h') if app.current_system.is_some() => {
+                            app.quick_add_wormhole();
+                            save(&mut app);
+                            app.view = ViewMode::Prompt(PromptKind::WormholeType, String::new());
+                        }
+                        KeyCode::Char('t') => {
+                            if let Some(id) = app.selected_signature().map(|s| s.identifier.clone())
+                            {
+                                app.status_message = Some(match app.cycle_selected_type() {
+                                    Some(kind) => {
+                                        save(&mut app);
+                                        format!("{id} is now {kind}")
                                     }
+                                    None => format!("{id} is already identified"),
+                                });
+                            }
+                        }
+                        KeyCode::Char('f') => {
+                            if let Some(current_system) = app.current_system.clone() {
+                                if let Some(id) =
+                                    app.selected_signature().map(|s| s.identifier.clone())
+                                {
+                                    app.toggle_favorite(&current_system, &id);
+                                    save(&mut app);
                                 }
                             }
-                            KeyCode::Down => {
-                                if system_sig_count > 1 && app.data_index < system_sig_count - 1 {
-                                    app.data_index += 1;
+                        }
+                        KeyCode::Char('o') => {
+                            app.view = ViewMode::Prompt(PromptKind::WormholeOrigin, String::new());
+                        }
+                        KeyCode::Char('N') => {
+                            if matches!(
+                                app.selected_signature().map(|sig| &sig.signature_type),
+                                Some(SignatureType::Wormhole(_))
+                            ) {
+                                app.view =
+                                    ViewMode::Prompt(PromptKind::ConnectionNote, String::new());
+                            }
+                        }
+                        KeyCode::Char('K') => {
+                            let problems = integrity::check(&app);
+                            if problems.is_empty() {
+                                app.status_message =
+                                    Some("No problems found in the map".to_owned());
+                            } else {
+                                app.view = ViewMode::Integrity(problems);
+                            }
+                        }
+                        KeyCode::Char('O') => {
+                            app.view = ViewMode::Prompt(PromptKind::ImportFile, String::new());
+                        }
+                        KeyCode::Char('U') => {
+                            if app.undo_move() {
+                                save(&mut app);
+                                app.status_message = Some("Undid signature move".to_owned());
+                            } else if app.undo_import() {
+                                save(&mut app);
+                                app.status_message = Some("Undid teammate import".to_owned());
+                            } else {
+                                app.status_message = Some("No move or import to undo".to_owned());
+                            }
+                        }
+                        KeyCode::Char('/') => {
+                            app.view = ViewMode::Prompt(PromptKind::FindSignature, String::new());
+                        }
+                        KeyCode::Char('T') => {
+                            if let Some(system) = &app.current_system {
+                                let note = app.system_note(system).unwrap_or_default().to_owned();
+                                app.view = ViewMode::Prompt(PromptKind::SystemNote, note);
+                            }
+                        }
+                        KeyCode::Char('y') => {
+                            if let (Some(system), Some(sig)) =
+                                (&app.current_system, app.selected_signature())
+                            {
+                                let text = sig.share_text(system);
+                                app.status_message =
+                                    Some(match cli_clipboard::set_contents(text.clone()) {
+                                        Ok(_) => format!("Copied: {text}"),
+                                        Err(e) => {
+                                            error!("Could not copy to clipboard: {e}");
+                                            "Could not copy to clipboard".to_owned()
+                                        }
+                                    });
+                            }
+                        }
+                        KeyCode::Char('Y') => copy_export(&mut app, &config, SignatureFilter::All),
+                        KeyCode::Char('W') => {
+                            copy_export(&mut app, &config, SignatureFilter::Wormholes)
+                        }
+                        KeyCode::Char('b') if !app.inbound_connections().is_empty() => {
+                            app.view = ViewMode::Inbound(0);
+                        }
+                        KeyCode::Tab => {
+                            app.view = ViewMode::Map(0);
+                        }
+                        KeyCode::Char('g') => {
+                            app.view = ViewMode::Prompt(PromptKind::SwitchSystem, String::new());
+                        }
+                        KeyCode::Char('G') => {
+                            app.view = ViewMode::Browse(0);
+                        }
+                        KeyCode::Char('m') if system_sig_count > 0 => {
+                            app.view = ViewMode::Prompt(PromptKind::MoveSignature, String::new());
+                        }
+                        KeyCode::Char('c') if system_sig_count > 0 => {
+                            app.view = ViewMode::Prompt(PromptKind::CopySignature, String::new());
+                        }
+                        KeyCode::Char('w') => {
+                            if let Some(index) = app.next_wormhole(app.data_index) {
+                                app.data_index = index;
+                            }
+                        }
+                        KeyCode::Char('R') => {
+                            let known_hole = app.selected_signature().is_some_and(|sig| {
+                                matches!(
+                                    &sig.signature_type,
+                                    SignatureType::Wormhole(wh) if wh.info().is_some()
+                                )
+                            });
+                            if known_hole {
+                                app.view = ViewMode::Rolling(0);
+                            }
+                        }
+                        KeyCode::Char('e') => {
+                            app.view = ViewMode::ExitRoute(true);
+                        }
+                        KeyCode::Char('E') => {
+                            if let Some(system) = app.current_system.clone() {
+                                let restore = app.can_restore_lives(&system);
+                                let action = ConfirmAction::AllEol { system, restore };
+                                if app.confirm_or_perform(action).is_some() {
+                                    save(&mut app);
+                                }
+                            }
+                        }
+                        KeyCode::Char('x') => {
+                            app.status_message =
+                                Some(match fs::write(DOT_FILE, app.export_dot()) {
+                                    Ok(_) => format!("Exported chain to {DOT_FILE}"),
+                                    Err(e) => {
+                                        error!("Could not export chain: {e}");
+                                        format!("Could not export chain: {e}")
+                                    }
+                                });
+                        }
+                        KeyCode::Char('S') => {
+                            app.status_message =
+                                Some(match snapshot::write(&app, config.badge_glyphs) {
+                                    Ok(file) => format!("Saved map snapshot to {file}"),
+                                    Err(e) => {
+                                        error!("Could not save map snapshot: {e}");
+                                        format!("Could not save map snapshot: {e}")
+                                    }
+                                });
+                        }
+                        KeyCode::Char('?') => {
+                            app.view = ViewMode::Help(0);
+                        }
+                        KeyCode::Char('J') => {
+                            app.status_message =
+                                Some(match export::write_scanner_chain(&app.system_data) {
+                                    Ok(text) => match cli_clipboard::set_contents(text) {
+                                        Ok(_) => format!(
+                                            "Copied chain signatures and saved them to {CHAIN_SIGS_FILE}"
+                                        ),
+                                        Err(e) => {
+                                            error!("Could not copy to clipboard: {e}");
+                                            format!("Saved chain signatures to {CHAIN_SIGS_FILE}")
+                                        }
+                                    },
+                                    Err(e) => {
+                                        error!("Could not save chain signatures: {e}");
+                                        format!("Could not save chain signatures: {e}")
+                                    }
+                                });
+                        }
+                        KeyCode::Char('P') => {
+                            app.status_message = Some(
+                                match export::write_report(&app.system_data, config.report) {
+                                    Ok(()) => format!("Saved chain report to {REPORT_FILE}"),
+                                    Err(e) => {
+                                        error!("Could not save chain report: {e}");
+                                        format!("Could not save chain report: {e}")
+                                    }
+                                },
+                            );
+                        }
+                        KeyCode::Char('V') => {
+                            let clipboard = cli_clipboard::get_contents().unwrap_or_default();
+                            clipboard_watch.toggle(&clipboard);
+                            app.status_message = Some(
+                                if clipboard_watch.enabled {
+                                    "Watching the clipboard for scans"
                                 } else {
-                                    app.data_index = 0;
+                                    "Stopped watching the clipboard"
+                                }
+                                .to_owned(),
+                            );
+                        }
+                        KeyCode::F(5) => {
+                            app.status_message = Some(match Config::load() {
+                                Ok(new) => {
+                                    let restart = config.apply_reload(new);
+                                    apply_config(&mut app, &config);
+                                    alerter = Alerter::new(config.alert_mode);
+                                    if let Some(scheduler) = scheduler.as_mut() {
+                                        scheduler.report = config.report;
+                                    }
+                                    if restart.is_empty() {
+                                        "Reloaded config".to_owned()
+                                    } else {
+                                        format!(
+                                            "Reloaded config; restart to apply changes to {}",
+                                            restart.join(", ")
+                                        )
+                                    }
+                                }
+                                Err(e) => {
+                                    error!("Could not reload config: {e}");
+                                    format!("Could not reload config, keeping the old one: {e}")
+                                }
+                            });
+                        }
+                        KeyCode::Char('F') => {
+                            app.view = ViewMode::Favorites(0);
+                        }
+                        KeyCode::Char('d') => {
+                            let action = ConfirmAction::DeleteSignatures(app.action_targets());
+                            if let Some(removed) = app.confirm_or_perform(action) {
+                                offer_static_placeholder(&mut app, &removed);
+                                save(&mut app);
+                            }
+                        }
+                        KeyCode::Char(' ') => app.toggle_selection(),
+                        KeyCode::Char('C') => {
+                            let systems = app.orphaned_systems();
+                            if systems.is_empty() {
+                                app.status_message =
+                                    Some("No empty systems to clean up".to_owned());
+                            } else if app
+                                .confirm_or_perform(ConfirmAction::RemoveSystems(systems))
+                                .is_some()
+                            {
+                                save(&mut app);
+                            }
+                        }
+                        KeyCode::Char('i') => {
+                            app.toggle_ignored(&app.action_targets());
+                            save(&mut app);
+                        }
+                        KeyCode::Char('I') => {
+                            app.show_ignored = !app.show_ignored;
+                            app.data_index = 0;
+                            app.reconcile_selection();
+                        }
+                        KeyCode::Char('l') => {
+                            app.update_wormholes(&app.action_targets(), |wh| {
+                                wh.life = match wh.life {
+                                    WormholeLife::EndOfLife => WormholeLife::Stable,
+                                    _ => WormholeLife::EndOfLife,
+                                };
+                            });
+                            save(&mut app);
+                        }
+                        KeyCode::Char('M') => {
+                            app.update_wormholes(&app.action_targets(), |wh| {
+                                wh.mass = match wh.mass {
+                                    WormholeMass::Stable => WormholeMass::Destab,
+                                    WormholeMass::Destab => WormholeMass::Critical,
+                                    WormholeMass::Critical | WormholeMass::Unknown => {
+                                        WormholeMass::Stable
+                                    }
+                                };
+                            });
+                            save(&mut app);
+                        }
+                        _ => {}
+                    }
+                }
+                ViewMode::Adding(_new_sig) => {}
+                ViewMode::Editing(_edit_sig) => {}
+                ViewMode::Prompt(kind, input) => {
+                    let kind = kind.clone();
+                    let mut input = input.clone();
+                    match key.code {
+                        KeyCode::Char(c) => input.push(c),
+                        KeyCode::Backspace => {
+                            input.pop();
+                        }
+                        KeyCode::Enter => {
+                            app.view = ViewMode::Normal;
+                            match kind {
+                                PromptKind::SwitchSystem => match find_system(&input) {
+                                    Some(system) => {
+                                        app.switch_system(system);
+                                        save(&mut app);
+                                    }
+                                    None => {
+                                        app.status_message =
+                                            Some(format!("Unknown system '{}'", input.trim()));
+                                    }
+                                },
+                                PromptKind::MoveSignature | PromptKind::CopySignature => {
+                                    let (Some(from), Some(to)) =
+                                        (app.current_system.clone(), find_system(&input))
+                                    else {
+                                        app.status_message =
+                                            Some(format!("Unknown system '{}'", input.trim()));
+                                        continue;
+                                    };
+                                    let done = if kind == PromptKind::MoveSignature {
+                                        app.move_signatures(&app.action_targets(), to) > 0
+                                    } else {
+                                        let index = app.selected_data_index().unwrap_or(0);
+                                        app.copy_signature(&from, index, to)
+                                    };
+                                    if done {
+                                        save(&mut app);
+                                    } else {
+                                        app.status_message =
+                                            Some(format!("{to} already has that signature"));
+                                    }
+                                }
+                                PromptKind::ConnectionNote => {
+                                    if let (Some(system), Some(sig)) = (
+                                        app.current_system.clone(),
+                                        app.selected_signature().cloned(),
+                                    ) {
+                                        app.set_connection_note(&system, &sig.identifier, &input);
+                                        save(&mut app);
+                                    }
+                                }
+                                // left untyped if nothing was entered
+                                PromptKind::WormholeType if !input.trim().is_empty() => {
+                                    if app.set_selected_wormhole_type(&input) {
+                                        save(&mut app);
+                                        continue;
+                                    }
+                                    let class = input.trim();
+                                    app.status_message =
+                                        Some(match app.fill_selected_static(class) {
+                                            Some(StaticMatch::Unique(wh_type)) => {
+                                                save(&mut app);
+                                                format!("Filled in the {class} static, {wh_type}")
+                                            }
+                                            Some(StaticMatch::Ambiguous(types)) => {
+                                                app.view = ViewMode::Prompt(kind, String::new());
+                                                format!(
+                                                    "Several statics lead to {class}: {}",
+                                                    types.join(", ")
+                                                )
+                                            }
+                                            Some(StaticMatch::NoMatch) => {
+                                                format!("No static here leads to {class}")
+                                            }
+                                            None => format!("Unknown wormhole type '{class}'"),
+                                        });
+                                }
+                                PromptKind::WormholeType => {}
+                                PromptKind::ImportFile if is_table_file(&input) => {
+                                    let path = PathBuf::from(input.trim());
+                                    match fs::read_to_string(&path)
+                                        .map_err(anyhow::Error::from)
+                                        .and_then(|text| csv_import::parse(&text))
+                                    {
+                                        Ok(table) => {
+                                            app.view = ViewMode::CsvImport(CsvImport::new(table));
+                                        }
+                                        Err(e) => {
+                                            error!("Could not import {}: {e}", path.display());
+                                            app.status_message = Some(format!(
+                                                "Could not import {}",
+                                                path.display()
+                                            ));
+                                        }
+                                    }
+                                }
+                                PromptKind::ImportFile => {
+                                    let path = PathBuf::from(input.trim());
+                                    match App::read_export(&path) {
+                                        Ok(theirs) => {
+                                            let summary = app.import_teammate(&theirs);
+                                            save(&mut app);
+                                            app.status_message = Some(format!(
+                                                "Imported {}: {}",
+                                                path.display(),
+                                                summary.label()
+                                            ));
+                                            if !app.import_conflicts.is_empty() {
+                                                app.view = ViewMode::ImportConflicts(0);
+                                            }
+                                        }
+                                        Err(e) => {
+                                            error!("Could not import {}: {e}", path.display());
+                                            app.status_message = Some(format!(
+                                                "Could not import {}",
+                                                path.display()
+                                            ));
+                                        }
+                                    }
+                                }
+                                PromptKind::FindSignature => {
+                                    match app.find_signature_global(&input) {
+                                        Some((system, index)) => {
+                                            let sig = &app.system_data[&system][index];
+                                            let id = sig.identifier.clone();
+                                            if sig.ignored {
+                                                app.show_ignored = true;
+                                            }
+                                            app.jump_to_signature(&system, &id);
+                                            app.status_message = Some(format!("{id} in {system}"));
+                                        }
+                                        None => {
+                                            app.status_message = Some(format!(
+                                                "No signature matches '{}'",
+                                                input.trim()
+                                            ));
+                                        }
+                                    }
+                                }
+                                PromptKind::SystemNote => {
+                                    if let Some(system) = app.current_system.clone() {
+                                        app.set_system_note(&system, &input);
+                                        save(&mut app);
+                                    }
+                                }
+                                PromptKind::WormholeOrigin => {
+                                    let origin = input.trim().to_uppercase();
+                                    if app.resolve_selected_origin(&origin) {
+                                        save(&mut app);
+                                    } else {
+                                        app.status_message = Some(format!(
+                                                "Could not set origin '{origin}' on the selected signature"
+                                            ));
+                                    }
                                 }
                             }
-                            KeyCode::Up => {
-                                if system_sig_count > 1 && app.data_index > 0 {
-                                    app.data_index -= 1;
+                            continue;
+                        }
+                        _ => {}
+                    }
+                    app.view = ViewMode::Prompt(kind, input);
+                }
+                ViewMode::Rolling(ship_index) => {
+                    let ship_index = *ship_index;
+                    let count = SHIP_MASSES.len();
+                    match key.code {
+                        KeyCode::Right => {
+                            app.view = ViewMode::Rolling((ship_index + 1) % count);
+                        }
+                        KeyCode::Left => {
+                            app.view = ViewMode::Rolling((ship_index + count - 1) % count);
+                        }
+                        KeyCode::Char(c @ ('j' | 'p' | 'r')) if !app.read_only => {
+                            let ship = &SHIP_MASSES[ship_index];
+                            let mass = match c {
+                                'j' => Some(ship.mass),
+                                'p' => Some(ship.prop_mass),
+                                _ => None,
+                            };
+                            if app.log_selected_mass(mass).is_some() {
+                                save(&mut app);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                ViewMode::Inbound(selected) => {
+                    let selected = *selected;
+                    let inbound: Vec<_> = app
+                        .inbound_connections()
+                        .into_iter()
+                        .map(|(system, sig)| (system.to_owned(), sig.identifier.clone()))
+                        .collect();
+                    let count = inbound.len();
+                    match key.code {
+                        KeyCode::Down if count > 0 => {
+                            app.view = ViewMode::Inbound((selected + 1) % count);
+                        }
+                        KeyCode::Up if count > 0 => {
+                            app.view = ViewMode::Inbound((selected + count - 1) % count);
+                        }
+                        code @ (KeyCode::PageUp
+                        | KeyCode::PageDown
+                        | KeyCode::Home
+                        | KeyCode::End) => {
+                            if let Some(index) = page(code, selected, count, visible_popup_rows) {
+                                app.view = ViewMode::Inbound(index);
+                            }
+                        }
+                        KeyCode::Enter if count > 0 => {
+                            let (system, id) = &inbound[selected.min(count - 1)];
+                            app.jump_to_signature(system, id);
+                            app.view = ViewMode::Normal;
+                            save(&mut app);
+                        }
+                        _ => {}
+                    }
+                }
+                ViewMode::Map(selected) => {
+                    let selected = *selected;
+                    let nodes = app.map_nodes();
+                    let count = nodes.len();
+                    match key.code {
+                        KeyCode::Down if count > 0 => {
+                            app.view = ViewMode::Map((selected + 1) % count);
+                        }
+                        KeyCode::Up if count > 0 => {
+                            app.view = ViewMode::Map((selected + count - 1) % count);
+                        }
+                        code @ (KeyCode::PageUp
+                        | KeyCode::PageDown
+                        | KeyCode::Home
+                        | KeyCode::End) => {
+                            if let Some(index) = page(code, selected, count, visible_map_rows) {
+                                app.view = ViewMode::Map(index);
+                            }
+                        }
+                        KeyCode::Enter if count > 0 => {
+                            let node = &nodes[selected.min(count - 1)];
+                            if node.collapsed.is_empty() {
+                                app.jump_to_first_unknown(&node.system);
+                                app.view = ViewMode::Normal;
+                                save(&mut app);
+                            } else {
+                                app.expand_branch(&node.system);
+                            }
+                        }
+                        KeyCode::Char('D' | 'X') if app.read_only => {
+                            app.status_message =
+                                Some("Read-only: removing systems is disabled".to_owned());
+                        }
+                        KeyCode::Char('D') if count > 0 => {
+                            let node = &nodes[selected.min(count - 1)];
+                            if node.depth > 0 {
+                                let systems = if node.collapsed.is_empty() {
+                                    vec![node.system.clone()]
                                 } else {
-                                    app.data_index = system_sig_count - 1;
+                                    node.collapsed.clone()
+                                };
+                                if app
+                                    .confirm_or_perform(ConfirmAction::RemoveSystems(systems))
+                                    .is_some()
+                                {
+                                    save(&mut app);
                                 }
                             }
-                            KeyCode::Char('n') => {
-                                app.view = ViewMode::Adding(Signature::default());
+                        }
+                        KeyCode::Char('X') => {
+                            let systems = app.stale_systems();
+                            if systems.is_empty() {
+                                app.status_message = Some("No stale branches".to_owned());
+                            } else if app
+                                .confirm_or_perform(ConfirmAction::RemoveSystems(systems))
+                                .is_some()
+                            {
+                                save(&mut app);
+                            }
+                        }
+                        KeyCode::Tab => app.view = ViewMode::Normal,
+                        _ => {}
+                    }
+                }
+                ViewMode::Confirm { action, .. } => {
+                    let action = action.clone();
+                    app.view = ViewMode::Normal;
+                    if key.code == KeyCode::Char('y') {
+                        let removed = app.perform(action);
+                        offer_static_placeholder(&mut app, &removed);
+                        save(&mut app);
+                    }
+                }
+                ViewMode::Palette(query, selected) => {
+                    let mut query = query.clone();
+                    let mut selected = *selected;
+                    let matches = actions::search(&query);
+                    match key.code {
+                        KeyCode::Enter => {
+                            app.view = ViewMode::Normal;
+                            palette_key = matches.get(selected).map(|action| action.key_event());
+                            continue;
+                        }
+                        KeyCode::Down if !matches.is_empty() => {
+                            selected = (selected + 1) % matches.len();
+                        }
+                        KeyCode::Up if !matches.is_empty() => {
+                            selected = (selected + matches.len() - 1) % matches.len();
+                        }
+                        code @ (KeyCode::PageUp | KeyCode::PageDown) => {
+                            if let Some(index) =
+                                page(code, selected, matches.len(), visible_popup_rows)
+                            {
+                                selected = index;
+                            }
+                        }
+                        KeyCode::Char(c) => {
+                            query.push(c);
+                            selected = 0;
+                        }
+                        KeyCode::Backspace => {
+                            query.pop();
+                            selected = 0;
+                        }
+                        _ => {}
+                    }
+                    app.view = ViewMode::Palette(query, selected);
+                }
+                ViewMode::PasteMode(text) => {
+                    let text = text.clone();
+                    let grouped = !app.paste_breakdown(&text).is_empty();
+                    let mode = match key.code {
+                        KeyCode::Char('f') if !grouped => Some(PasteMode::Full),
+                        KeyCode::Char('p') if !grouped => Some(PasteMode::Partial),
+                        KeyCode::Char('y') | KeyCode::Enter if grouped => Some(PasteMode::Partial),
+                        KeyCode::Char('n') if grouped => {
+                            app.view = ViewMode::Normal;
+                            None
+                        }
+                        _ => None,
+                    };
+                    if let Some(mode) = mode {
+                        app.view = ViewMode::Normal;
+                        paste(&mut app, &mut alerter, &text, mode);
+                    }
+                }
+                ViewMode::Browse(selected) => {
+                    let selected = *selected;
+                    let rows = tree_rows(&REGIONS, &app.browse_expanded);
+                    let count = rows.len();
+                    let target = rows[selected.min(count - 1)].target.clone();
+                    match (key.code, target) {
+                        (KeyCode::Down, _) => {
+                            app.view = ViewMode::Browse((selected + 1) % count);
+                        }
+                        (KeyCode::Up, _) => {
+                            app.view = ViewMode::Browse((selected + count - 1) % count);
+                        }
+                        (
+                            code @ (KeyCode::PageUp
+                            | KeyCode::PageDown
+                            | KeyCode::Home
+                            | KeyCode::End),
+                            _,
+                        ) => {
+                            if let Some(index) = page(code, selected, count, visible_popup_rows) {
+                                app.view = ViewMode::Browse(index);
+                            }
+                        }
+                        (
+                            KeyCode::Enter | KeyCode::Right | KeyCode::Left,
+                            TreeTarget::Group(key_name),
+                        ) => {
+                            let open = app.browse_expanded.contains(&key_name);
+                            if open && key.code != KeyCode::Right {
+                                app.browse_expanded.retain(|group| group != &key_name);
+                            } else if !open && key.code != KeyCode::Left {
+                                app.browse_expanded.push(key_name);
+                            }
+                        }
+                        (KeyCode::Enter, TreeTarget::System(system)) => {
+                            app.switch_system(&system);
+                            app.view = ViewMode::Normal;
+                            save(&mut app);
+                        }
+                        _ => {}
+                    }
+                }
+                ViewMode::ImportConflicts(selected) => {
+                    let selected = *selected;
+                    let count = app.import_conflicts.len();
+                    let system = app
+                        .import_conflicts
+                        .get(selected)
+                        .map(|conflict| conflict.system.clone());
+                    match (key.code, system) {
+                        (KeyCode::Down, _) if count > 0 => {
+                            app.view = ViewMode::ImportConflicts((selected + 1) % count);
+                        }
+                        (KeyCode::Up, _) if count > 0 => {
+                            app.view = ViewMode::ImportConflicts((selected + count - 1) % count);
+                        }
+                        (
+                            code @ (KeyCode::PageUp
+                            | KeyCode::PageDown
+                            | KeyCode::Home
+                            | KeyCode::End),
+                            _,
+                        ) => {
+                            if let Some(index) = page(code, selected, count, visible_popup_rows) {
+                                app.view = ViewMode::ImportConflicts(index);
+                            }
+                        }
+                        (KeyCode::Char(c @ ('m' | 't' | 'M' | 'T')), Some(system)) => {
+                            let resolution = if c.eq_ignore_ascii_case(&'t') {
+                                Resolution::Theirs
+                            } else {
+                                Resolution::Mine
+                            };
+                            if c.is_ascii_uppercase() {
+                                app.resolve_system_conflicts(&system, resolution);
+                            } else {
+                                app.resolve_conflict(selected, resolution);
                             }
-                            KeyCode::Char('v') => {
-                                if let Ok(clipboard) = cli_clipboard::get_contents() {
-                                    debug!("Parsing content of clipboard");
-                                    let results = parse_paste(&clipboard);
-                                    debug!("Got {} results from clipboard", results.len());
-                                    app.merge_in(&results);
+                            save(&mut app);
+                            let remaining = app.import_conflicts.len();
+                            app.view = if remaining == 0 {
+                                app.status_message = Some("All conflicts resolved".to_owned());
+                                ViewMode::Normal
+                            } else {
+                                ViewMode::ImportConflicts(selected.min(remaining - 1))
+                            };
+                        }
+                        _ => {}
+                    }
+                }
+                ViewMode::WhatsNew(_) => {
+                    app.view = ViewMode::Normal;
+                }
+                ViewMode::Help(scroll) => {
+                    // the data version, any update and a blank line come first
+                    let count =
+                        actions::help_lines().len() + 2 + usize::from(new_release.is_some());
+                    let last = count.saturating_sub(visible_popup_rows);
+                    app.view = match key.code {
+                        KeyCode::Down => ViewMode::Help((scroll + 1).min(last)),
+                        KeyCode::Up => ViewMode::Help(scroll.saturating_sub(1)),
+                        code @ (KeyCode::PageUp
+                        | KeyCode::PageDown
+                        | KeyCode::Home
+                        | KeyCode::End) => ViewMode::Help(
+                            page(code, *scroll, last + 1, visible_popup_rows).unwrap_or(*scroll),
+                        ),
+                        _ => ViewMode::Normal,
+                    };
+                }
+                ViewMode::CsvImport(import) => {
+                    let mut import = import.clone();
+                    let count = import.columns.len().max(1);
+                    match key.code {
+                        KeyCode::Right | KeyCode::Tab => {
+                            import.selected = (import.selected + 1) % count;
+                        }
+                        KeyCode::Left | KeyCode::BackTab => {
+                            import.selected = (import.selected + count - 1) % count;
+                        }
+                        KeyCode::Char(' ') => {
+                            if let Some(column) = import.columns.get_mut(import.selected) {
+                                *column = column.next();
+                            }
+                        }
+                        KeyCode::Enter => {
+                            app.view = ViewMode::Normal;
+                            match app.import_table(&import) {
+                                Some(summary) => {
+                                    save(&mut app);
+                                    app.status_message =
+                                        Some(format!("Imported table: {}", summary.label()));
+                                    if !app.import_conflicts.is_empty() {
+                                        app.view = ViewMode::ImportConflicts(0);
+                                    }
+                                }
+                                None => {
+                                    app.status_message = Some("Select a system first".to_owned());
                                 }
                             }
-                            _ => {}
                         }
+                        _ => {}
+                    }
+                    if key.code != KeyCode::Enter {
+                        app.view = ViewMode::CsvImport(import);
+                    }
+                }
+                ViewMode::Integrity(problems) => {
+                    let repairable = problems.iter().any(|problem| problem.repairable());
+                    app.view = ViewMode::Normal;
+                    if key.code == KeyCode::Char('r') && repairable && !app.read_only {
+                        let done = integrity::repair(&mut app);
+                        save(&mut app);
+                        app.status_message = Some(format!("Made {} repair(s)", done.len()));
+                    }
+                }
+                ViewMode::StaticPlaceholder(wh_type) => {
+                    if key.code == KeyCode::Char('y') {
+                        if let Some(current_system) = app.current_system.clone() {
+                            let wh_type = wh_type.clone();
+                            app.add_static_placeholder(&current_system, &wh_type);
+                            save(&mut app);
+                        }
+                    }
+                    app.view = ViewMode::Normal;
+                }
+                ViewMode::ExitRoute(safe_only) => {
+                    if key.code == KeyCode::Char('s') {
+                        app.view = ViewMode::ExitRoute(!*safe_only);
+                    }
+                }
+                ViewMode::Favorites(selected) => {
+                    let selected = *selected;
+                    let count = app.favorites.len();
+                    match key.code {
+                        KeyCode::Down if count > 0 => {
+                            app.view = ViewMode::Favorites((selected + 1) % count);
+                        }
+                        KeyCode::Up if count > 0 => {
+                            app.view = ViewMode::Favorites((selected + count - 1) % count);
+                        }
+                        code @ (KeyCode::PageUp
+                        | KeyCode::PageDown
+                        | KeyCode::Home
+                        | KeyCode::End) => {
+                            if let Some(index) = page(code, selected, count, visible_popup_rows) {
+                                app.view = ViewMode::Favorites(index);
+                            }
+                        }
+                        KeyCode::Enter if count > 0 => {
+                            app.select_favorite(selected);
+                            app.view = ViewMode::Normal;
+                            save(&mut app);
+                        }
+                        _ => {}
                     }
-                    ViewMode::Adding(_new_sig) => {}
-                    ViewMode::Editing(_edit_sig) => {}
                 }
             }
         }
     }
 
-    // exit, restore terminal
+    // exit, writing any pending save before restoring the terminal
+    if let Some(saver) = app.saver.take() {
+        if let Err(e) = saver.finish().await {
+            error!("Could not save state: {e}");
+        }
+    }
     disable_raw_mode()?;
     execute!(
         terminal.backend_mut(),
@@ -220,6 +1837,204 @@ pub async fn run(_esi: Esi) -> Result<()> {
     Ok(())
 }
 
+/// When the system's signatures were last scanned, e.g. "Scanned 3h ago" or "Scanned never".
+fn format_last_scanned(app: &App, system: &str, format: TimestampFormat) -> String {
+    match app.last_scanned.get(system) {
+        Some(at) => format!("Scanned {}", format_timestamp(*at, Utc::now(), format)),
+        None => "Scanned never".to_owned(),
+    }
+}
+
+/// An inbound wormhole as its source system, type, and life/mass.
+fn format_inbound(system: &str, sig: &Signature) -> String {
+    match &sig.signature_type {
+        SignatureType::Wormhole(wh) => format!(
+            "{system} {} {}/{}",
+            wh.wh_type.as_deref().unwrap_or("?"),
+            wh.life.as_str(),
+            wh.mass.as_str()
+        ),
+        _ => system.to_owned(),
+    }
+}
+
+/// After deleting signatures, offer a placeholder if one of them was a static.
+fn offer_static_placeholder(app: &mut App, removed: &[Signature]) {
+    for sig in removed {
+        if let SignatureType::Wormhole(SignatureWormhole {
+            wh_type: Some(wh_type),
+            connection_kind: ConnectionKind::Static,
+            ..
+        }) = &sig.signature_type
+        {
+            app.view = ViewMode::StaticPlaceholder(wh_type.clone());
+            return;
+        }
+    }
+}
+
+/// Rolling plan for the hole with the ship, one configuration per paragraph.
+///
+/// `used` is the mass logged through the hole so far.
+fn format_rolling_plan(info: &WormholeInfo, used: u64, ship: &ShipMass) -> String {
+    let mut text = format!(
+        "Ship: {} (Left/Right to change)\nHole: {}M total, {}M per jump\nUsed: {}M (j/p to log a jump with prop off/on, r to reset)\n",
+        ship.name,
+        info.mass / 1_000_000,
+        info.jump / 1_000_000,
+        used / 1_000_000
+    );
+    let plans = rolling::plan(info, used, ship);
+    if plans.is_empty() {
+        text.push_str("\nShip is too heavy for this hole");
+    }
+    for plan in plans {
+        let prop = if plan.prop_on { "Prop on" } else { "Prop off" };
+        text.push_str(&format!(
+            "\n{prop}: {} pass(es) to crit, {} to close",
+            plan.passes_to_crit, plan.passes_to_close
+        ));
+        if let Some(pass) = plan.stranding_pass {
+            text.push_str(&format!(
+                "\n! Pass {pass} could close the hole with you on the far side"
+            ));
+        }
+    }
+    text
+}
+
+/// Jump counts to the trade hubs, or a placeholder while they're being fetched.
+fn format_hub_routes(routes: Option<&[(&str, HubRoute)]>) -> Vec<Spans<'static>> {
+    let Some(routes) = routes else {
+        return vec![Spans::from(vec![Span::raw("Trade hubs: fetching...")])];
+    };
+    let mut spans = vec![Spans::from(vec![Span::raw("Trade hubs (short/safe):")])];
+    for (hub, route) in routes {
+        spans.push(Spans::from(vec![Span::raw(format!(
+            "  {hub}: {}",
+            route.label()
+        ))]));
+    }
+    spans
+}
+
+/// Copy the current system's signatures that match the filter, in the configured format.
+fn copy_export(app: &mut App, config: &Config, filter: SignatureFilter) {
+    let Some(system) = app.current_system.clone() else {
+        return;
+    };
+    let signatures = app.system_data.get(&system).cloned().unwrap_or_default();
+    let text = match config.export_format {
+        ExportFormat::Text => export_system(&system, &signatures, filter),
+        ExportFormat::Markdown => export_markdown(&system, &signatures, filter),
+        ExportFormat::Scanner => export_scanner(&signatures, filter),
+    };
+    app.status_message = Some(match cli_clipboard::set_contents(text) {
+        Ok(_) => format!("Copied {system} signatures"),
+        Err(e) => {
+            error!("Could not copy to clipboard: {e}");
+            "Could not copy to clipboard".to_owned()
+        }
+    });
+}
+
+/// Set up the app with the settings that can change while running.
+fn apply_config(app: &mut App, config: &Config) {
+    app.new_wormhole = config.new_wormhole_state.wormhole();
+    app.auto_static_placeholders = config.auto_static_placeholders;
+    app.valuable_site_tier = config.valuable_site_tier;
+    app.min_scan_strength = config.min_scan_strength;
+    app.focus_on_paste = config.focus_follows_paste;
+    app.home_system = config
+        .home_system
+        .as_deref()
+        .and_then(find_system)
+        .map(str::to_owned);
+    app.sweep_on_save = config.sweep_empty_systems;
+    app.read_only = config.read_only;
+    app.compress_state = config.compress_state;
+    app.confirmations = config.confirm;
+    app.author = config.identity();
+    app.stale_after = config
+        .stale_branch_hours
+        .map(|hours| chrono::Duration::hours(hours as i64));
+}
+
+/// Whether an import path is a spreadsheet export rather than a teammate's map.
+fn is_table_file(path: &str) -> bool {
+    let path = path.trim().to_lowercase();
+    path.ends_with(".csv") || path.ends_with(".tsv")
+}
+
+/// Apply pasted text, alerting on anything notable.
+fn paste(app: &mut App, alerter: &mut Alerter, text: &str, mode: PasteMode) {
+    if let Some(alerts) = app.paste_as(text, mode) {
+        if !alerts.is_empty() {
+            debug!("Alerting for {alerts:?}");
+            alerter.trigger(Instant::now());
+        }
+        save(app);
+    }
+}
+
+/// Merge a scan file from the watched directory into the system it names, or the current one.
+fn merge_scan_file(app: &mut App, scan: ScanFile) {
+    app.status_message = Some(match scan {
+        ScanFile::Parsed(file, system, items) => {
+            let name = file
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            match system.or_else(|| app.current_system.clone()) {
+                Some(_) if app.read_only => format!("Read-only: ignored {name}"),
+                Some(system) => {
+                    let added = app.merge_in_system(&system, &items);
+                    save(app);
+                    format!(
+                        "Merged {name} into {system}: {} new signature(s)",
+                        added.len()
+                    )
+                }
+                None => {
+                    if let Err(e) = watcher::move_to(&file, REJECTED_DIR) {
+                        error!("Could not reject scan file {name}: {e}");
+                    }
+                    format!("Rejected {name}: unknown system and none selected")
+                }
+            }
+        }
+        ScanFile::Rejected(name, reason) => format!("Rejected {name}: {reason}"),
+    });
+}
+
+/// Handle the keys checked before the normal view's bindings, returning whether the key was.
+///
+/// Ctrl+P opens the palette, and actions that change the map are refused when read-only.
+fn intercept_key(app: &mut App, key: &KeyEvent) -> bool {
+    if key.code == KeyCode::Char('p') && key.modifiers.contains(KeyModifiers::CONTROL) {
+        app.view = ViewMode::Palette(String::new(), 0);
+        return true;
+    }
+    match actions::editing_action(key).filter(|_| app.read_only) {
+        Some(action) => {
+            app.status_message = Some(format!("Read-only: {} is disabled", action.name));
+            true
+        }
+        None => false,
+    }
+}
+
+/// Persist the app state, logging rather than failing on errors.
+fn save(app: &mut App) {
+    app.stamp_edits();
+    if app.sweep_on_save {
+        app.sweep_orphaned_systems();
+    }
+    if let Err(e) = app.save() {
+        error!("Could not save state: {e}");
+    }
+}
+
 /// https://github.com/fdehau/tui-rs/blob/master/examples/popup.rs#L103
 pub fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::default()
@@ -260,8 +2075,60 @@ fn style_for_system(leads_to: &str) -> Style {
     }
 }
 
+/// Colors destinations are spread over, leaving out the ones used for warnings and ignored rows.
+const DESTINATION_COLORS: [Color; 8] = [
+    Color::Cyan,
+    Color::Green,
+    Color::Magenta,
+    Color::Blue,
+    Color::LightCyan,
+    Color::LightGreen,
+    Color::LightMagenta,
+    Color::LightBlue,
+];
+
+/// Stable color for a wormhole's destination, so the same system looks the same everywhere.
+fn color_for_destination(dest: &str) -> Color {
+    // FNV-1a, so colors don't change between runs or builds
+    let hash = dest.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    DESTINATION_COLORS[(hash % DESTINATION_COLORS.len() as u64) as usize]
+}
+
+/// How long to wait for input, which is longer once idle in low-power mode.
+fn poll_interval(idle: Duration, low_power: bool) -> Duration {
+    if low_power && idle >= IDLE_AFTER {
+        Duration::from_secs(IDLE_POLL_RATE)
+    } else {
+        Duration::from_secs(EVENT_POLL_RATE)
+    }
+}
+
+/// Wait up to `timeout` for input, returning whether there is some.
+///
+/// Stops waiting early once a listener request arrives, so it's answered straight away.
+fn wait_for_input(
+    timeout: Duration,
+    commands: Option<&UnboundedReceiver<listener::Pending>>,
+) -> Result<bool> {
+    let Some(commands) = commands else {
+        return Ok(event::poll(timeout)?);
+    };
+    let deadline = Instant::now() + timeout;
+    loop {
+        let left = deadline.saturating_duration_since(Instant::now());
+        if event::poll(left.min(REQUEST_CHECK_RATE))? {
+            return Ok(true);
+        }
+        if left <= REQUEST_CHECK_RATE || !commands.is_empty() {
+            return Ok(false);
+        }
+    }
+}
+
 /// Format the static connections for display.
-pub fn format_system_statics(statics: &[String]) -> Vec<Spans> {
+pub fn format_system_statics(statics: &[String]) -> Vec<Spans<'_>> {
     statics
         .iter()
         .map(|s| {
@@ -275,3 +2142,62 @@ pub fn format_system_statics(statics: &[String]) -> Vec<Spans> {
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        color_for_destination, intercept_key, poll_interval, DESTINATION_COLORS, EVENT_POLL_RATE,
+        IDLE_AFTER, IDLE_POLL_RATE,
+    };
+    use crate::state::{App, ViewMode};
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use std::time::Duration;
+
+    #[test]
+    fn test_intercept_key_read_only() {
+        let mut app = App::new();
+        app.read_only = true;
+
+        // navigation keys get through to the normal bindings
+        assert!(!intercept_key(
+            &mut app,
+            &KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE)
+        ));
+        assert!(intercept_key(
+            &mut app,
+            &KeyEvent::new(KeyCode::Char('p'), KeyModifiers::NONE)
+        ));
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("Read-only: Pin signature to top is disabled")
+        );
+        assert!(app.view == ViewMode::Normal);
+
+        assert!(intercept_key(
+            &mut app,
+            &KeyEvent::new(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        ));
+        assert!(app.view == ViewMode::Palette(String::new(), 0));
+    }
+
+    #[test]
+    fn test_poll_interval() {
+        let active = Duration::from_secs(EVENT_POLL_RATE);
+        let idle = Duration::from_secs(IDLE_POLL_RATE);
+        assert_eq!(poll_interval(Duration::from_secs(10), true), active);
+        assert_eq!(poll_interval(IDLE_AFTER, true), idle);
+        assert_eq!(poll_interval(IDLE_AFTER * 10, true), idle);
+        assert_eq!(poll_interval(IDLE_AFTER * 10, false), active);
+    }
+
+    #[test]
+    fn test_color_for_destination() {
+        assert_eq!(color_for_destination("Jita"), color_for_destination("Jita"));
+        assert!(DESTINATION_COLORS.contains(&color_for_destination("J173213")));
+        let colors: Vec<_> = ["Jita", "Amarr", "J173213", "J105443", "Thera", "Dodixie"]
+            .iter()
+            .map(|dest| color_for_destination(dest))
+            .collect();
+        assert!(colors.iter().any(|color| *color != colors[0]));
+    }
+}
@@ -0,0 +1,190 @@
+use anyhow::Result;
+use log::{debug, warn};
+use rfesi::prelude::{Esi, RequestType};
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+/// How often to refresh kill counts; ESI only updates them hourly.
+const REFRESH_RATE: Duration = Duration::from_secs(15 * 60);
+
+#[derive(Deserialize)]
+struct SystemKills {
+    system_id: u64,
+    #[serde(default)]
+    ship_kills: u32,
+    #[serde(default)]
+    pod_kills: u32,
+}
+
+#[derive(Deserialize)]
+struct NamedSystem {
+    id: u64,
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct Ids {
+    systems: Option<Vec<NamedSystem>>,
+}
+
+/// Ship and pod kills in the last hour for each mapped system, refreshed in the background.
+pub struct KillCache {
+    last_fetched: Option<Instant>,
+    fetching: bool,
+    sender: UnboundedSender<HashMap<String, u32>>,
+    receiver: UnboundedReceiver<HashMap<String, u32>>,
+}
+
+impl Default for KillCache {
+    fn default() -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        Self {
+            last_fetched: None,
+            fetching: false,
+            sender,
+            receiver,
+        }
+    }
+}
+
+impl KillCache {
+    /// Whether it's time to fetch the kill counts again.
+    pub fn is_due(&self) -> bool {
+        !self.fetching
+            && self
+                .last_fetched
+                .is_none_or(|at| at.elapsed() >= REFRESH_RATE)
+    }
+
+    /// Fetch kill counts for the systems on a background task.
+    ///
+    /// They're handed back by `receive` once they arrive. Failures are retried at the next refresh.
+    pub fn fetch(&mut self, esi: &Esi, systems: Vec<String>) {
+        self.fetching = true;
+        self.last_fetched = Some(Instant::now());
+        let esi = esi.clone();
+        let sender = self.sender.clone();
+        tokio::spawn(async move {
+            debug!("Fetching kill counts for {} systems", systems.len());
+            let kills = match fetch_kills(&esi, &systems).await {
+                Ok(kills) => kills,
+                Err(e) => {
+                    warn!("Could not get kill counts: {e}");
+                    HashMap::new()
+                }
+            };
+            let _ = sender.send(kills);
+        });
+    }
+
+    /// Kill counts fetched since this was last called, by system name.
+    pub fn receive(&mut self) -> Option<HashMap<String, u32>> {
+        let mut latest = None;
+        while let Ok(kills) = self.receiver.try_recv() {
+            self.fetching = false;
+            latest = Some(kills);
+        }
+        latest
+    }
+}
+
+async fn fetch_kills(esi: &Esi, systems: &[String]) -> Result<HashMap<String, u32>> {
+    if systems.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let body = serde_json::to_string(systems)?;
+    let ids: Ids = esi
+        .query(
+            "POST",
+            RequestType::Public,
+            "universe/ids/",
+            None,
+            Some(&body),
+        )
+        .await?;
+    let names: HashMap<u64, String> = ids
+        .systems
+        .unwrap_or_default()
+        .into_iter()
+        .map(|system| (system.id, system.name))
+        .collect();
+    let kills: Vec<SystemKills> = esi
+        .query(
+            "GET",
+            RequestType::Public,
+            "universe/system_kills/",
+            None,
+            None,
+        )
+        .await?;
+    Ok(by_name(&names, &kills))
+}
+
+/// Player kills in each named system, leaving out systems with none.
+fn by_name(names: &HashMap<u64, String>, kills: &[SystemKills]) -> HashMap<String, u32> {
+    kills
+        .iter()
+        .filter_map(|entry| {
+            let name = names.get(&entry.system_id)?;
+            let total = entry.ship_kills + entry.pod_kills;
+            (total > 0).then(|| (name.clone(), total))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{by_name, KillCache, SystemKills};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_kills_by_name() {
+        let names = HashMap::from([
+            (30000142, "Jita".to_owned()),
+            (30002537, "Amamake".to_owned()),
+        ]);
+        let kills = vec![
+            SystemKills {
+                system_id: 30000142,
+                ship_kills: 3,
+                pod_kills: 1,
+            },
+            SystemKills {
+                system_id: 30002537,
+                ship_kills: 0,
+                pod_kills: 0,
+            },
+            SystemKills {
+                system_id: 30002187,
+                ship_kills: 9,
+                pod_kills: 0,
+            },
+        ];
+
+        assert_eq!(
+            by_name(&names, &kills),
+            HashMap::from([("Jita".to_owned(), 4)])
+        );
+    }
+
+    #[test]
+    fn test_receive_kills() {
+        let mut cache = KillCache::default();
+        assert!(cache.is_due());
+        assert!(cache.receive().is_none());
+
+        // as if a fetch were running
+        cache.fetching = true;
+        assert!(!cache.is_due());
+        cache
+            .sender
+            .send(HashMap::from([("Jita".to_owned(), 2)]))
+            .unwrap();
+        assert_eq!(cache.receive().unwrap()["Jita"], 2);
+        assert!(!cache.fetching);
+    }
+}
@@ -0,0 +1,258 @@
+use crate::{
+    eve_data::{find_system, parse_paste},
+    state::App,
+};
+use anyhow::Result;
+use log::{debug, error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+    sync::{
+        mpsc::{self, UnboundedReceiver, UnboundedSender},
+        oneshot,
+    },
+};
+
+/// A command sent to the listener, one JSON object per line, e.g.
+/// `{"cmd":"paste","system":"J173213","text":"...","token":"..."}` or
+/// `{"cmd":"set_system","system":"J173213"}`.
+///
+/// Each line gets a JSON response line: `{"ok":true,"message":"..."}`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+pub enum Command {
+    /// Scanner results for the system, or the current system if it's left out
+    Paste {
+        system: Option<String>,
+        text: String,
+        /// Who the changes are attributed to, instead of the local user
+        #[serde(default)]
+        author: Option<String>,
+    },
+    /// Make the system the current one
+    SetSystem { system: String },
+}
+
+#[derive(Deserialize)]
+struct Request {
+    token: Option<String>,
+    #[serde(flatten)]
+    command: Command,
+}
+
+/// Outcome of a command, sent back to the client.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Response {
+    pub ok: bool,
+    pub message: String,
+}
+
+impl Response {
+    fn ok(message: String) -> Self {
+        Self { ok: true, message }
+    }
+
+    fn error(message: String) -> Self {
+        Self { ok: false, message }
+    }
+}
+
+/// A command waiting to be applied to the app, and where to send the result.
+pub struct Pending {
+    pub command: Command,
+    pub reply: oneshot::Sender<Response>,
+}
+
+/// Apply a command through the same paths as the interface.
+pub fn apply(app: &mut App, command: Command) -> Response {
+    match command {
+        Command::Paste { .. } if app.read_only => {
+            Response::error("The map is read-only".to_owned())
+        }
+        Command::Paste {
+            system,
+            text,
+            author,
+        } => {
+            let items = parse_paste(&text);
+            if items.is_empty() {
+                return Response::error("No signatures in the text".to_owned());
+            }
+            let system = match system {
+                Some(name) => match find_system(&name) {
+                    Some(system) => system.to_owned(),
+                    None => return Response::error(format!("Unknown system '{name}'")),
+                },
+                None => match &app.current_system {
+                    Some(system) => system.clone(),
+                    None => return Response::error("No system selected".to_owned()),
+                },
+            };
+            let added = app.merge_in_system(&system, &items);
+            match author {
+                Some(author) => app.stamp_edits_by(&author),
+                None => app.stamp_edits(),
+            }
+            Response::ok(format!(
+                "Merged {} signature(s) into {system}, {} new",
+                items.len(),
+                added.len()
+            ))
+        }
+        Command::SetSystem { system } => match find_system(&system) {
+            Some(system) => {
+                app.switch_system(system);
+                Response::ok(format!("Switched to {system}"))
+            }
+            None => Response::error(format!("Unknown system '{system}'")),
+        },
+    }
+}
+
+/// Listen on the localhost port, passing valid commands on to be applied.
+///
+/// Port 0 picks a free port; the address actually bound is returned.
+pub async fn spawn(
+    port: u16,
+    token: Option<String>,
+) -> Result<(SocketAddr, UnboundedReceiver<Pending>)> {
+    let listener = TcpListener::bind(("127.0.0.1", port)).await?;
+    let addr = listener.local_addr()?;
+    info!("Listening for commands on {addr}");
+    let (sender, receiver) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, peer)) => {
+                    debug!("Command connection from {peer}");
+                    tokio::spawn(handle(stream, token.clone(), sender.clone()));
+                }
+                Err(e) => {
+                    error!("Could not accept command connection: {e}");
+                    return;
+                }
+            }
+        }
+    });
+    Ok((addr, receiver))
+}
+
+async fn handle(stream: TcpStream, token: Option<String>, sender: UnboundedSender<Pending>) {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = respond(&line, token.as_deref(), &sender).await;
+        let Ok(mut text) = serde_json::to_string(&response) else {
+            return;
+        };
+        text.push('\n');
+        if writer.write_all(text.as_bytes()).await.is_err() {
+            return;
+        }
+    }
+}
+
+async fn respond(line: &str, token: Option<&str>, sender: &UnboundedSender<Pending>) -> Response {
+    let request: Request = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(e) => return Response::error(format!("Invalid command: {e}")),
+    };
+    if token.is_some() && request.token.as_deref() != token {
+        warn!("Rejected command with a bad token");
+        return Response::error("Invalid token".to_owned());
+    }
+    let (reply, result) = oneshot::channel();
+    let pending = Pending {
+        command: request.command,
+        reply,
+    };
+    if sender.send(pending).is_err() {
+        return Response::error("App is shutting down".to_owned());
+    }
+    result
+        .await
+        .unwrap_or_else(|_| Response::error("Command was dropped".to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply, spawn, Response};
+    use crate::state::App;
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+        net::TcpStream,
+    };
+
+    async fn send(
+        lines: &mut tokio::io::Lines<BufReader<tokio::net::tcp::OwnedReadHalf>>,
+        writer: &mut tokio::net::tcp::OwnedWriteHalf,
+        command: &str,
+    ) -> Response {
+        writer
+            .write_all(format!("{command}\n").as_bytes())
+            .await
+            .unwrap();
+        let line = lines.next_line().await.unwrap().unwrap();
+        serde_json::from_str(&line).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_listener_end_to_end() {
+        let (addr, mut receiver) = spawn(0, Some("secret".to_owned())).await.unwrap();
+        let app = tokio::spawn(async move {
+            let mut app = App::new();
+            while let Some(pending) = receiver.recv().await {
+                let _ = pending.reply.send(apply(&mut app, pending.command));
+            }
+        });
+
+        let stream = TcpStream::connect(addr).await.unwrap();
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+
+        let response = send(
+            &mut lines,
+            &mut writer,
+            r#"{"cmd":"set_system","system":"J105443","token":"secret"}"#,
+        )
+        .await;
+        assert_eq!(response, Response::ok("Switched to J105443".to_owned()));
+
+        let response = send(
+            &mut lines,
+            &mut writer,
+            r#"{"cmd":"paste","text":"UWG-400\tCosmic Signature\tWormhole\tUnstable Wormhole\t100.0%\t33.21 AU","token":"secret"}"#,
+        )
+        .await;
+        assert_eq!(
+            response,
+            Response::ok("Merged 1 signature(s) into J105443, 1 new".to_owned())
+        );
+
+        let response = send(
+            &mut lines,
+            &mut writer,
+            r#"{"cmd":"set_system","system":"Nowhere","token":"secret"}"#,
+        )
+        .await;
+        assert!(!response.ok);
+
+        let response = send(
+            &mut lines,
+            &mut writer,
+            r#"{"cmd":"set_system","system":"Jita"}"#,
+        )
+        .await;
+        assert_eq!(response, Response::error("Invalid token".to_owned()));
+
+        let response = send(&mut lines, &mut writer, "not json").await;
+        assert!(response.message.starts_with("Invalid command"));
+
+        app.abort();
+    }
+}
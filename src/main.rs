@@ -1,15 +1,43 @@
 #![deny(clippy::all, unsafe_code)]
 
-use crate::config::Config;
-use anyhow::Result;
-use log::{debug, error};
+use crate::{config::Config, state::App};
+use anyhow::{anyhow, Result};
+use log::{debug, error, info};
 use rfesi::prelude::{Esi, EsiBuilder};
 use std::{env, process, time::SystemTime};
 
+mod actions;
+mod activity;
+mod alert;
+mod autopaste;
+mod chain;
+mod changelog;
 mod config;
+mod csv_import;
+mod data_update;
+mod effects;
 mod eve_data;
+mod export;
+mod fetch_cache;
+mod integrity;
 mod interface;
+mod kills;
+mod listener;
+mod merge;
+mod paging;
+mod planets;
+mod regions;
+mod rolling;
+mod routes;
+mod saver;
+mod schedule;
+mod sites;
+mod snapshot;
 mod state;
+mod timestamps;
+mod update_check;
+mod wanderer;
+mod watcher;
 
 fn setup_logging() -> Result<()> {
     if env::var("RUST_LOG").is_err() {
@@ -31,16 +59,59 @@ fn setup_logging() -> Result<()> {
     Ok(())
 }
 
-async fn setup_esi(config: &Config) -> Result<Esi> {
-    let esi = EsiBuilder::new()
+/// Character named with `--character`, whose SSO app ESI is set up with.
+fn character_arg() -> Option<String> {
+    let mut args = env::args().skip_while(|arg| arg != "--character");
+    args.next()?;
+    args.next()
+}
+
+async fn setup_esi(config: &Config, character: Option<&str>) -> Result<Esi> {
+    let app = match character {
+        Some(character) => config.app_for_character(character),
+        None => config.apps().into_iter().next(),
+    }
+    .ok_or_else(|| anyhow!("No SSO application configured"))?;
+    debug!(
+        "Using SSO app {}",
+        app.label
+            .as_deref()
+            .unwrap_or("from the top-level settings")
+    );
+    let mut builder = EsiBuilder::new()
         .user_agent("github.com/celeo/evemapping")
-        .client_id(&config.sso_client_id)
-        .client_secret(&config.sso_client_secret)
-        .callback_url(&config.sso_callback_url)
-        .build()?;
+        .client_id(&app.client_id)
+        .client_secret(&app.client_secret)
+        .callback_url(&app.callback_url);
+    if let Some(base_url) = &app.base_url {
+        builder = builder.base_api_url(base_url);
+    }
+    let esi = builder.build()?;
     Ok(esi)
 }
 
+/// Print the problems in the saved map, repairing the safe ones if asked.
+fn check_state(mut app: App, repairing: bool) -> Result<()> {
+    let problems = integrity::check(&app);
+    if problems.is_empty() {
+        println!("No problems found");
+        return Ok(());
+    }
+    for problem in &problems {
+        println!("{}", problem.label());
+    }
+    if !repairing {
+        if problems.iter().any(|problem| problem.repairable()) {
+            println!("Run with --repair to fix what can be fixed automatically");
+        }
+        return Ok(());
+    }
+    for repair in integrity::repair(&mut app) {
+        println!("Repaired: {repair}");
+    }
+    app.save()
+}
+
 #[tokio::main]
 async fn main() {
     if let Err(e) = setup_logging() {
@@ -48,25 +119,125 @@ async fn main() {
         process::exit(1);
     }
 
+    if env::args().any(|arg| arg == "--snapshot") {
+        let glyphs = Config::load()
+            .map(|config| config.badge_glyphs)
+            .unwrap_or_default();
+        match App::load().and_then(|app| snapshot::write(&app, glyphs)) {
+            Ok(file) => {
+                println!("Saved map snapshot to {file}");
+                return;
+            }
+            Err(e) => {
+                error!("Could not save map snapshot: {e}");
+                process::exit(1);
+            }
+        }
+    }
+
+    if env::args().any(|arg| arg == "--check-state") {
+        let repairing = env::args().any(|arg| arg == "--repair");
+        let compress = Config::load()
+            .map(|config| config.compress_state)
+            .unwrap_or_default();
+        let state = App::load().and_then(|mut app| {
+            app.compress_state = compress;
+            check_state(app, repairing)
+        });
+        match state {
+            Ok(()) => return,
+            Err(e) => {
+                error!("Could not check the state file: {e}");
+                process::exit(1);
+            }
+        }
+    }
+
+    if env::args().any(|arg| arg == "--check-config") {
+        println!("Static data: {}", data_update::data_version());
+        for file in data_update::outdated_user_data() {
+            println!("{file} isn't newer than the bundled data, so isn't used");
+        }
+        match Config::load() {
+            Ok(_) => {
+                println!("config.toml is valid");
+                return;
+            }
+            Err(e) => {
+                println!("config.toml has a problem: {e}");
+                process::exit(1);
+            }
+        }
+    }
+
+    if env::args().nth(1).as_deref() == Some("update-data") {
+        let url = Config::load()
+            .ok()
+            .and_then(|config| config.data_url)
+            .unwrap_or_else(|| data_update::DEFAULT_DATA_URL.to_owned());
+        match data_update::update(&url).await {
+            Ok(report) => {
+                for line in report {
+                    println!("{line}");
+                }
+                return;
+            }
+            Err(e) => {
+                error!("Could not update static data, keeping the current data: {e}");
+                process::exit(1);
+            }
+        }
+    }
+
+    if env::args().nth(1).as_deref() == Some("report") {
+        let options = Config::load()
+            .map(|config| config.report)
+            .unwrap_or_default();
+        match App::load() {
+            Ok(app) => {
+                print!("{}", export::chain_report(&app.system_data, options));
+                return;
+            }
+            Err(e) => {
+                error!("Could not load state for the chain report: {e}");
+                process::exit(1);
+            }
+        }
+    }
+
+    let offline_flag = env::args().any(|arg| arg == "--offline");
+
     debug!("Loading config");
     let config = match Config::load() {
         Ok(c) => c,
+        Err(e) if offline_flag => {
+            debug!("No usable config, continuing offline: {e}");
+            Config::default()
+        }
         Err(e) => {
             error!("Could not load config: {e}");
             process::exit(1);
         }
     };
-    debug!("Setting up ESI");
-    let esi = match setup_esi(&config).await {
-        Ok(e) => e,
-        Err(e) => {
-            error!("Could not set up connection to ESI: {e}");
-            process::exit(1);
+    let esi = if offline_flag {
+        info!("Running offline; ESI features are disabled");
+        None
+    } else if !config.has_credentials() {
+        info!("No SSO credentials configured; ESI features are disabled");
+        None
+    } else {
+        debug!("Setting up ESI");
+        match setup_esi(&config, character_arg().as_deref()).await {
+            Ok(e) => Some(e),
+            Err(e) => {
+                error!("Could not set up connection to ESI: {e}");
+                process::exit(1);
+            }
         }
     };
 
     debug!("Starting");
-    if let Err(e) = interface::run(esi).await {
+    if let Err(e) = interface::run(esi, &config).await {
         error!("An error occurred during running: {e}");
         process::exit(1);
     }
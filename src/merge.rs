@@ -0,0 +1,283 @@
+use crate::eve_data::{ConnectionKind, Signature, SignatureType};
+use std::collections::HashMap;
+
+/// Which side of a conflict to keep.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Resolution {
+    Mine,
+    Theirs,
+}
+
+/// A signature in both maps whose details disagree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pub system: String,
+    pub mine: Signature,
+    pub theirs: Signature,
+    /// Details that differ, e.g. "destination".
+    pub fields: Vec<&'static str>,
+}
+
+impl Conflict {
+    /// One-line description for the resolution list.
+    pub fn label(&self) -> String {
+        format!(
+            "{} {} ({}): mine {} | theirs {}",
+            self.system,
+            self.mine.identifier,
+            self.fields.join(", "),
+            self.mine.signature_type,
+            self.theirs.signature_type
+        )
+    }
+}
+
+/// Differences between my map and an imported one.
+#[derive(Debug, Default, PartialEq)]
+pub struct MergeSummary {
+    /// Signatures only in the import, by system.
+    pub added: Vec<(String, Signature)>,
+    /// Signatures with details only the import has, merged with mine.
+    pub filled: Vec<(String, Signature)>,
+    pub conflicts: Vec<Conflict>,
+}
+
+impl MergeSummary {
+    /// Counts of each kind of change, e.g. "2 added, 1 filled in, 1 conflict(s)".
+    pub fn label(&self) -> String {
+        format!(
+            "{} added, {} filled in, {} conflict(s)",
+            self.added.len(),
+            self.filled.len(),
+            self.conflicts.len()
+        )
+    }
+}
+
+/// How an imported signature type compares to mine.
+enum Comparison {
+    Same,
+    /// The import only adds details; the merged type.
+    Fills(SignatureType),
+    /// Details both sides have but disagree on.
+    Differs(Vec<&'static str>),
+}
+
+/// Merge an optional detail, noting a conflict if both sides have different values.
+fn merge_option<T: Clone + PartialEq>(
+    mine: &mut Option<T>,
+    theirs: &Option<T>,
+    field: &'static str,
+    conflicts: &mut Vec<&'static str>,
+) {
+    match (mine.as_ref(), theirs) {
+        (None, Some(_)) => *mine = theirs.clone(),
+        (Some(a), Some(b)) if a != b => conflicts.push(field),
+        _ => {}
+    }
+}
+
+fn compare(mine: &SignatureType, theirs: &SignatureType) -> Comparison {
+    if theirs == &SignatureType::Unknown || mine == theirs {
+        return Comparison::Same;
+    }
+    if mine == &SignatureType::Unknown {
+        return Comparison::Fills(theirs.clone());
+    }
+    if mine.kind() != theirs.kind() {
+        return Comparison::Differs(vec!["type"]);
+    }
+    let mut conflicts = Vec::new();
+    let merged = match (mine, theirs) {
+        (SignatureType::Wormhole(a), SignatureType::Wormhole(b)) => {
+            let mut merged = a.clone();
+            merge_option(&mut merged.wh_type, &b.wh_type, "type", &mut conflicts);
+            merge_option(
+                &mut merged.destination,
+                &b.destination,
+                "destination",
+                &mut conflicts,
+            );
+            merge_option(
+                &mut merged.origin_type,
+                &b.origin_type,
+                "origin",
+                &mut conflicts,
+            );
+            if a.life != b.life {
+                conflicts.push("life");
+            }
+            if a.mass != b.mass {
+                conflicts.push("mass");
+            }
+            if merged.connection_kind == ConnectionKind::Unknown {
+                merged.connection_kind = b.connection_kind;
+            }
+            SignatureType::Wormhole(merged)
+        }
+        _ => match (mine.name(), theirs.name()) {
+            (None, Some(_)) => theirs.clone(),
+            (Some(a), Some(b)) if a != b => {
+                conflicts.push("name");
+                mine.clone()
+            }
+            _ => mine.clone(),
+        },
+    };
+    if !conflicts.is_empty() {
+        Comparison::Differs(conflicts)
+    } else if &merged == mine {
+        Comparison::Same
+    } else {
+        Comparison::Fills(merged)
+    }
+}
+
+/// Compare an imported map to mine, system by system.
+///
+/// Signatures are matched on ID. Details only one side has aren't conflicts.
+pub fn diff(
+    mine: &HashMap<String, Vec<Signature>>,
+    theirs: &HashMap<String, Vec<Signature>>,
+) -> MergeSummary {
+    let mut systems: Vec<_> = theirs.keys().collect();
+    systems.sort();
+    let mut summary = MergeSummary::default();
+    for system in systems {
+        let existing = mine.get(system).map(Vec::as_slice).unwrap_or_default();
+        for signature in &theirs[system] {
+            if signature.is_placeholder() {
+                continue;
+            }
+            let Some(my_sig) = existing
+                .iter()
+                .find(|sig| sig.identifier == signature.identifier)
+            else {
+                summary.added.push((system.clone(), signature.clone()));
+                continue;
+            };
+            match compare(&my_sig.signature_type, &signature.signature_type) {
+                Comparison::Same => {}
+                Comparison::Fills(signature_type) => {
+                    let mut merged = my_sig.clone();
+                    merged.signature_type = signature_type;
+                    if signature.last_edit.is_some() {
+                        merged.last_edit = signature.last_edit.clone();
+                    }
+                    summary.filled.push((system.clone(), merged));
+                }
+                Comparison::Differs(fields) => summary.conflicts.push(Conflict {
+                    system: system.clone(),
+                    mine: my_sig.clone(),
+                    theirs: signature.clone(),
+                    fields,
+                }),
+            }
+        }
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::diff;
+    use crate::eve_data::{Signature, SignatureType, SignatureWormhole, WormholeLife};
+    use std::collections::HashMap;
+
+    fn wormhole(wh_type: Option<&str>, destination: Option<&str>) -> SignatureType {
+        SignatureType::Wormhole(SignatureWormhole {
+            wh_type: wh_type.map(str::to_owned),
+            destination: destination.map(str::to_owned),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_diff() {
+        let mut mine = HashMap::new();
+        mine.insert(
+            "J173213".to_owned(),
+            vec![
+                Signature::new("ABC", "123", wormhole(Some("B274"), None)),
+                Signature::new("DEF", "456", wormhole(None, Some("Jita"))),
+                Signature::new("GHI", "789", SignatureType::Unknown),
+                Signature::new("JKL", "012", SignatureType::Gas(None)),
+                Signature::new("MNO", "345", SignatureType::Relic(Some("A".to_owned()))),
+            ],
+        );
+        let eol = SignatureWormhole {
+            life: WormholeLife::EndOfLife,
+            ..Default::default()
+        };
+        let mut theirs = HashMap::new();
+        theirs.insert(
+            "J173213".to_owned(),
+            vec![
+                Signature::new("ABC", "123", wormhole(Some("B274"), Some("Jita"))),
+                Signature::new("DEF", "456", wormhole(None, Some("Amarr"))),
+                Signature::new("GHI", "789", SignatureType::Data(None)),
+                Signature::new("JKL", "012", SignatureType::Unknown),
+                Signature::new("MNO", "345", SignatureType::Relic(Some("B".to_owned()))),
+            ],
+        );
+        theirs.insert(
+            "Thera".to_owned(),
+            vec![Signature::new("PQR", "678", SignatureType::Wormhole(eol))],
+        );
+
+        let summary = diff(&mine, &theirs);
+
+        assert_eq!(summary.added.len(), 1);
+        assert_eq!(summary.added[0].0, "Thera");
+        let filled: Vec<_> = summary
+            .filled
+            .iter()
+            .map(|(_, sig)| sig.identifier.to_string())
+            .collect();
+        assert_eq!(filled, vec!["ABC-123", "GHI-789"]);
+        assert_eq!(
+            summary.filled[0].1.signature_type,
+            wormhole(Some("B274"), Some("Jita"))
+        );
+        let conflicts: Vec<_> = summary
+            .conflicts
+            .iter()
+            .map(|conflict| {
+                (
+                    conflict.mine.identifier.to_string(),
+                    conflict.fields.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            conflicts,
+            vec![
+                ("DEF-456".to_owned(), vec!["destination"]),
+                ("MNO-345".to_owned(), vec!["name"])
+            ]
+        );
+        assert_eq!(summary.label(), "1 added, 2 filled in, 2 conflict(s)");
+    }
+
+    #[test]
+    fn test_diff_wormhole_life_and_type() {
+        let mut mine = HashMap::new();
+        mine.insert(
+            "J173213".to_owned(),
+            vec![Signature::new("ABC", "123", SignatureType::Gas(None))],
+        );
+        let eol = SignatureWormhole {
+            life: WormholeLife::EndOfLife,
+            ..Default::default()
+        };
+        let mut theirs = HashMap::new();
+        theirs.insert(
+            "J173213".to_owned(),
+            vec![Signature::new("ABC", "123", SignatureType::Wormhole(eol))],
+        );
+        assert_eq!(diff(&mine, &theirs).conflicts[0].fields, vec!["type"]);
+
+        mine.get_mut("J173213").unwrap()[0].signature_type = wormhole(None, None);
+        assert_eq!(diff(&mine, &theirs).conflicts[0].fields, vec!["life"]);
+    }
+}
@@ -0,0 +1,50 @@
+use crossterm::event::KeyCode;
+
+/// Selection after a PageUp/PageDown/Home/End key in a list of `len` items
+/// with `height` rows on screen.
+///
+/// Pages move by a full screen, stopping at the ends, so the new selection is
+/// always on the screen the list scrolls to. Returns `None` for other keys
+/// and for empty lists.
+pub fn page(key: KeyCode, selected: usize, len: usize, height: usize) -> Option<usize> {
+    let last = len.checked_sub(1)?;
+    let step = height.max(1);
+    let selected = selected.min(last);
+    match key {
+        KeyCode::PageDown => Some((selected + step).min(last)),
+        KeyCode::PageUp => Some(selected.saturating_sub(step)),
+        KeyCode::Home => Some(0),
+        KeyCode::End => Some(last),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::page;
+    use crossterm::event::KeyCode;
+
+    #[test]
+    fn test_page() {
+        assert_eq!(page(KeyCode::PageDown, 0, 50, 10), Some(10));
+        assert_eq!(page(KeyCode::PageDown, 45, 50, 10), Some(49));
+        assert_eq!(page(KeyCode::PageUp, 15, 50, 10), Some(5));
+        assert_eq!(page(KeyCode::PageUp, 5, 50, 10), Some(0));
+        assert_eq!(page(KeyCode::Home, 30, 50, 10), Some(0));
+        assert_eq!(page(KeyCode::End, 3, 50, 10), Some(49));
+        assert_eq!(page(KeyCode::Down, 3, 50, 10), None);
+    }
+
+    #[test]
+    fn test_page_edge_cases() {
+        assert_eq!(page(KeyCode::PageDown, 0, 0, 10), None);
+        assert_eq!(page(KeyCode::End, 0, 0, 10), None);
+        // shorter than the screen
+        assert_eq!(page(KeyCode::PageDown, 1, 3, 10), Some(2));
+        assert_eq!(page(KeyCode::PageUp, 2, 3, 10), Some(0));
+        // nothing on screen yet still moves
+        assert_eq!(page(KeyCode::PageDown, 0, 3, 0), Some(1));
+        // stale selection past the end
+        assert_eq!(page(KeyCode::PageUp, 9, 3, 1), Some(1));
+    }
+}
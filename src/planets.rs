@@ -0,0 +1,155 @@
+use crate::{
+    fetch_cache::{FetchCache, Fetched},
+    routes::{system_id, UnknownToEsi},
+};
+use anyhow::Result;
+use log::{debug, warn};
+use rfesi::prelude::{Esi, RequestType};
+use serde::Deserialize;
+
+/// Planet type IDs and their short names, in display order.
+pub const PLANET_TYPES: [(u64, &str); 9] = [
+    (11, "Temperate"),
+    (12, "Ice"),
+    (13, "Gas"),
+    (2014, "Oceanic"),
+    (2015, "Lava"),
+    (2016, "Barren"),
+    (2017, "Storm"),
+    (2063, "Plasma"),
+    (30889, "Shattered"),
+];
+
+/// Planet types and moon count of a system.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlanetSummary {
+    /// Planets of each type present, in `PLANET_TYPES` order.
+    pub counts: Vec<(&'static str, usize)>,
+    pub moons: usize,
+}
+
+impl PlanetSummary {
+    /// Count the planets of each type.
+    pub fn new(type_ids: &[u64], moons: usize) -> Self {
+        let counts = PLANET_TYPES
+            .iter()
+            .map(|(id, name)| (*name, type_ids.iter().filter(|t| *t == id).count()))
+            .filter(|(_, count)| *count > 0)
+            .collect();
+        Self { counts, moons }
+    }
+
+    /// Compact description, e.g. "2×Lava 1×Gas, 5 moons".
+    pub fn label(&self) -> String {
+        if self.counts.is_empty() {
+            return "none".to_owned();
+        }
+        let planets: Vec<_> = self
+            .counts
+            .iter()
+            .map(|(name, count)| format!("{count}×{name}"))
+            .collect();
+        format!("{}, {} moons", planets.join(" "), self.moons)
+    }
+}
+
+/// Planet line for the info panel, with "—" when the data isn't available.
+pub fn planets_line(summary: Option<&PlanetSummary>) -> String {
+    match summary {
+        Some(summary) => format!("Planets: {}", summary.label()),
+        None => "Planets: —".to_owned(),
+    }
+}
+
+#[derive(Deserialize)]
+struct SystemPlanet {
+    planet_id: u64,
+    #[serde(default)]
+    moons: Vec<u64>,
+}
+
+#[derive(Deserialize)]
+struct SystemInfo {
+    #[serde(default)]
+    planets: Vec<SystemPlanet>,
+}
+
+#[derive(Deserialize)]
+struct PlanetInfo {
+    type_id: u64,
+}
+
+/// Planets of systems, fetched when a system is first viewed and kept for the session.
+pub type PlanetCache = FetchCache<PlanetSummary>;
+
+impl PlanetCache {
+    /// Fetch the system's planets on a background task.
+    pub fn fetch(&mut self, esi: &Esi, system: &str) {
+        let esi = esi.clone();
+        let name = system.to_owned();
+        self.spawn(system, async move {
+            let system = name;
+            debug!("Fetching planets for {system}");
+            match fetch_summary(&esi, &system).await {
+                Ok(summary) => Fetched {
+                    system,
+                    value: Some(summary),
+                    unknown_to_esi: false,
+                },
+                Err(e) => {
+                    warn!("Could not get planets for {system}: {e}");
+                    Fetched {
+                        unknown_to_esi: e.is::<UnknownToEsi>(),
+                        system,
+                        value: None,
+                    }
+                }
+            }
+        });
+    }
+}
+
+async fn fetch_summary(esi: &Esi, system: &str) -> Result<PlanetSummary> {
+    let id = system_id(esi, system).await?;
+    let path = format!("universe/systems/{id}/");
+    let info: SystemInfo = esi
+        .query("GET", RequestType::Public, &path, None, None)
+        .await?;
+    let mut type_ids = Vec::new();
+    for planet in &info.planets {
+        let path = format!("universe/planets/{}/", planet.planet_id);
+        let planet: PlanetInfo = esi
+            .query("GET", RequestType::Public, &path, None, None)
+            .await?;
+        type_ids.push(planet.type_id);
+    }
+    let moons = info.planets.iter().map(|p| p.moons.len()).sum();
+    Ok(PlanetSummary::new(&type_ids, moons))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{planets_line, PlanetCache, PlanetSummary};
+
+    #[test]
+    fn test_planet_summary() {
+        let summary = PlanetSummary::new(&[2016, 2015, 13, 2016, 2015, 99999, 2016], 7);
+
+        assert_eq!(summary.counts, vec![("Gas", 1), ("Lava", 2), ("Barren", 3)]);
+        assert_eq!(
+            planets_line(Some(&summary)),
+            "Planets: 1×Gas 2×Lava 3×Barren, 7 moons"
+        );
+        assert_eq!(
+            planets_line(Some(&PlanetSummary::new(&[], 0))),
+            "Planets: none"
+        );
+    }
+
+    #[test]
+    fn test_planets_missing() {
+        let cache = PlanetCache::default();
+        assert!(!cache.has_tried("J173213"));
+        assert_eq!(planets_line(cache.get("J173213")), "Planets: —");
+    }
+}
@@ -0,0 +1,229 @@
+use crate::eve_data::{SystemClassification, SystemData, ALL_SYSTEMS};
+use once_cell::sync::Lazy;
+use std::collections::BTreeMap;
+
+/// Systems of a constellation, by name.
+#[derive(Debug, PartialEq)]
+pub struct Constellation {
+    /// Empty when the systems' constellations aren't known.
+    pub name: String,
+    pub systems: Vec<String>,
+}
+
+/// Constellations of a region, by name.
+#[derive(Debug, PartialEq)]
+pub struct Region {
+    pub name: String,
+    pub constellations: Vec<Constellation>,
+}
+
+/// Every known system grouped by region, for browsing.
+pub static REGIONS: Lazy<Vec<Region>> =
+    Lazy::new(|| group_systems(ALL_SYSTEMS.iter().map(|(name, data)| (name.as_str(), data))));
+
+/// Region and constellation of the system.
+///
+/// Falls back to w-space class regions ("A-R" for class 1 through "F-R" for
+/// class 6) grouped by effect, or the security band, when the data doesn't have them.
+fn placement(data: &SystemData) -> (String, String) {
+    if let Some(region) = &data.region {
+        return (
+            region.clone(),
+            data.constellation.clone().unwrap_or_default(),
+        );
+    }
+    match data.classification() {
+        SystemClassification::WSpace(class @ 1..=6) => {
+            let letter = (b'A' + class - 1) as char;
+            (
+                format!("{letter}-R (Class {class})"),
+                data.effect
+                    .clone()
+                    .unwrap_or_else(|| "No effect".to_owned()),
+            )
+        }
+        other => (other.as_str(), String::new()),
+    }
+}
+
+/// Group systems by region and constellation, each sorted by name.
+pub fn group_systems<'a>(systems: impl Iterator<Item = (&'a str, &'a SystemData)>) -> Vec<Region> {
+    let mut tree: BTreeMap<String, BTreeMap<String, Vec<String>>> = BTreeMap::new();
+    for (name, data) in systems {
+        let (region, constellation) = placement(data);
+        tree.entry(region)
+            .or_default()
+            .entry(constellation)
+            .or_default()
+            .push(name.to_owned());
+    }
+    tree.into_iter()
+        .map(|(name, constellations)| Region {
+            name,
+            constellations: constellations
+                .into_iter()
+                .map(|(name, mut systems)| {
+                    systems.sort();
+                    Constellation { name, systems }
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+/// What a row of the browser tree opens.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TreeTarget {
+    /// A region or constellation, by its key in the expanded list.
+    Group(String),
+    System(String),
+}
+
+/// A row of the browser tree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeRow {
+    pub depth: usize,
+    pub label: String,
+    pub target: TreeTarget,
+}
+
+/// Key of a constellation in the expanded list, e.g. "The Forge/Kimotoro".
+fn constellation_key(region: &str, constellation: &str) -> String {
+    format!("{region}/{constellation}")
+}
+
+/// The regions as rows, showing the contents of the expanded ones.
+///
+/// Constellations without a name are left out, with their systems directly under the region.
+pub fn tree_rows(regions: &[Region], expanded: &[String]) -> Vec<TreeRow> {
+    let mut rows = Vec::new();
+    for region in regions {
+        let count: usize = region.constellations.iter().map(|c| c.systems.len()).sum();
+        let open = expanded.contains(&region.name);
+        rows.push(TreeRow {
+            depth: 0,
+            label: format!("{} {} ({count})", if open { "▾" } else { "▸" }, region.name),
+            target: TreeTarget::Group(region.name.clone()),
+        });
+        if !open {
+            continue;
+        }
+        for constellation in &region.constellations {
+            let mut depth = 1;
+            if !constellation.name.is_empty() {
+                let key = constellation_key(&region.name, &constellation.name);
+                let open = expanded.contains(&key);
+                rows.push(TreeRow {
+                    depth,
+                    label: format!(
+                        "{} {} ({})",
+                        if open { "▾" } else { "▸" },
+                        constellation.name,
+                        constellation.systems.len()
+                    ),
+                    target: TreeTarget::Group(key),
+                });
+                if !open {
+                    continue;
+                }
+                depth += 1;
+            }
+            rows.extend(constellation.systems.iter().map(|system| TreeRow {
+                depth,
+                label: system.clone(),
+                target: TreeTarget::System(system.clone()),
+            }));
+        }
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{group_systems, tree_rows, Constellation, TreeTarget, REGIONS};
+    use crate::eve_data::SystemData;
+
+    fn system(security: f32, class: Option<u8>, region: Option<&str>) -> SystemData {
+        SystemData {
+            security,
+            class,
+            effect: None,
+            statics: Vec::new(),
+            region: region.map(str::to_owned),
+            constellation: region.map(|_| "Kimotoro".to_owned()),
+        }
+    }
+
+    #[test]
+    fn test_group_systems() {
+        let systems = [
+            ("Jita", system(0.94, None, Some("The Forge"))),
+            ("Perimeter", system(0.95, None, Some("The Forge"))),
+            ("Amamake", system(0.4, None, None)),
+            ("J173213", system(-0.99, Some(2), None)),
+        ];
+        let regions = group_systems(systems.iter().map(|(name, data)| (*name, data)));
+
+        let names: Vec<_> = regions.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["B-R (Class 2)", "Low-Sec", "The Forge"]);
+        assert_eq!(
+            regions[2].constellations,
+            vec![Constellation {
+                name: "Kimotoro".to_owned(),
+                systems: vec!["Jita".to_owned(), "Perimeter".to_owned()],
+            }]
+        );
+        assert_eq!(regions[0].constellations[0].name, "No effect");
+        assert_eq!(regions[1].constellations[0].name, "");
+    }
+
+    #[test]
+    fn test_tree_rows() {
+        let systems = [
+            ("Jita", system(0.94, None, Some("The Forge"))),
+            ("Amamake", system(0.4, None, None)),
+        ];
+        let regions = group_systems(systems.iter().map(|(name, data)| (*name, data)));
+
+        let labels = |expanded: &[String]| -> Vec<String> {
+            tree_rows(&regions, expanded)
+                .into_iter()
+                .map(|row| format!("{}{}", "  ".repeat(row.depth), row.label))
+                .collect()
+        };
+        assert_eq!(labels(&[]), vec!["▸ Low-Sec (1)", "▸ The Forge (1)"]);
+        assert_eq!(
+            labels(&["Low-Sec".to_owned(), "The Forge".to_owned()]),
+            vec![
+                "▾ Low-Sec (1)",
+                "  Amamake",
+                "▾ The Forge (1)",
+                "  ▸ Kimotoro (1)"
+            ]
+        );
+        let rows = tree_rows(
+            &regions,
+            &["The Forge".to_owned(), "The Forge/Kimotoro".to_owned()],
+        );
+        assert_eq!(rows[3].target, TreeTarget::System("Jita".to_owned()));
+        assert_eq!(rows[3].depth, 2);
+    }
+
+    #[test]
+    fn test_known_systems_grouped() {
+        let region_of = |system: &str| {
+            REGIONS
+                .iter()
+                .find(|region| {
+                    region
+                        .constellations
+                        .iter()
+                        .any(|c| c.systems.iter().any(|s| s == system))
+                })
+                .map(|region| region.name.as_str())
+        };
+        assert_eq!(region_of("J173213"), Some("B-R (Class 2)"));
+        assert_eq!(region_of("Jita"), Some("High-Sec"));
+        assert_eq!(region_of("Thera"), Some("Class-12"));
+    }
+}
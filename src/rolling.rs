@@ -0,0 +1,121 @@
+use crate::eve_data::WormholeInfo;
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+
+/// Fraction of a hole's total mass below which it's critical.
+const CRITICAL_FRACTION: f64 = 0.1;
+/// Total mass of a hole can be up to this fraction more or less than listed.
+const MASS_VARIANCE: f64 = 0.1;
+
+/// Mass of a ship commonly used for rolling.
+#[derive(Debug, Deserialize)]
+pub struct ShipMass {
+    pub name: String,
+    /// Mass with the propulsion module off
+    pub mass: u64,
+    /// Mass with the propulsion module on
+    pub prop_mass: u64,
+}
+
+/// Rolling ships, in the order they're offered.
+pub static SHIP_MASSES: Lazy<Vec<ShipMass>> = Lazy::new(|| {
+    let raw = include_str!("../static/ship_masses.json");
+    serde_json::from_str(raw).unwrap()
+});
+
+/// How many round trips it takes to roll a hole with a ship in one configuration.
+#[derive(Debug, PartialEq)]
+pub struct RollingPlan {
+    /// Whether the propulsion module is on for every jump.
+    pub prop_on: bool,
+    pub passes_to_crit: u64,
+    pub passes_to_close: u64,
+    /// First pass whose outbound jump could close the hole, leaving the ship on the far side.
+    pub stranding_pass: Option<u64>,
+}
+
+/// Plans for rolling the hole with the ship, with prop off and on.
+///
+/// `used` is mass that's already gone through. Configurations too heavy for
+/// the hole's max jump mass are left out.
+pub fn plan(info: &WormholeInfo, used: u64, ship: &ShipMass) -> Vec<RollingPlan> {
+    [(false, ship.mass), (true, ship.prop_mass)]
+        .into_iter()
+        .filter(|(_, mass)| *mass > 0 && *mass <= info.jump)
+        .map(|(prop_on, mass)| {
+            let total = info.mass as f64;
+            let passes_until = |target: f64| {
+                let remaining = (target - used as f64).max(0.0);
+                (remaining / (2 * mass) as f64).ceil() as u64
+            };
+            let passes_to_close = passes_until(total);
+            // the hole could close as soon as the least mass it might have has gone through
+            let earliest_close = total * (1.0 - MASS_VARIANCE);
+            let stranding_pass = (1..=passes_to_close)
+                .find(|pass| (used + (2 * pass - 1) * mass) as f64 >= earliest_close);
+            RollingPlan {
+                prop_on,
+                passes_to_crit: passes_until(total * (1.0 - CRITICAL_FRACTION)),
+                passes_to_close,
+                stranding_pass,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{plan, ShipMass, SHIP_MASSES};
+    use crate::eve_data::WORMHOLE_TYPES;
+
+    #[test]
+    fn test_ship_masses_consistent() {
+        assert!(!SHIP_MASSES.is_empty());
+        for ship in SHIP_MASSES.iter() {
+            assert!(!ship.name.trim().is_empty());
+            assert!(ship.mass > 0, "{}", ship.name);
+            assert!(ship.prop_mass >= ship.mass, "{}", ship.name);
+        }
+    }
+
+    #[test]
+    fn test_plan() {
+        // 2,000M total, 375M jump
+        let info = WORMHOLE_TYPES.get("B274").unwrap();
+        let ship = ShipMass {
+            name: "Test".to_owned(),
+            mass: 100_000_000,
+            prop_mass: 150_000_000,
+        };
+
+        let plans = plan(info, 0, &ship);
+
+        assert_eq!(plans.len(), 2);
+        assert!(!plans[0].prop_on);
+        assert_eq!(plans[0].passes_to_crit, 9);
+        assert_eq!(plans[0].passes_to_close, 10);
+        // pass 9 goes out at 1,700M, under the 1,800M it could close at; pass 10 at 1,900M
+        assert_eq!(plans[0].stranding_pass, Some(10));
+        assert!(plans[1].prop_on);
+        assert_eq!(plans[1].passes_to_crit, 6);
+        assert_eq!(plans[1].passes_to_close, 7);
+        assert_eq!(plans[1].stranding_pass, Some(7));
+    }
+
+    #[test]
+    fn test_plan_used_and_too_heavy() {
+        let info = WORMHOLE_TYPES.get("B274").unwrap();
+        let ship = ShipMass {
+            name: "Test".to_owned(),
+            mass: 300_000_000,
+            prop_mass: 400_000_000,
+        };
+
+        let plans = plan(info, 1_500_000_000, &ship);
+
+        assert_eq!(plans.len(), 1);
+        assert_eq!(plans[0].passes_to_crit, 1);
+        assert_eq!(plans[0].passes_to_close, 1);
+        assert_eq!(plans[0].stranding_pass, Some(1));
+    }
+}
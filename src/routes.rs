@@ -0,0 +1,209 @@
+use crate::{
+    eve_data::{SystemClassification, ALL_SYSTEMS},
+    fetch_cache::{FetchCache, Fetched},
+};
+use anyhow::Result;
+use log::{debug, warn};
+use rfesi::prelude::{Esi, RequestType};
+use serde::Deserialize;
+use std::fmt;
+
+/// Major trade hubs, by name and system ID.
+pub const TRADE_HUBS: [(&str, u64); 5] = [
+    ("Jita", 30000142),
+    ("Amarr", 30002187),
+    ("Dodixie", 30002659),
+    ("Rens", 30002510),
+    ("Hek", 30002053),
+];
+
+/// Jump counts from a system to a trade hub.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HubRoute {
+    pub shortest: Option<usize>,
+    pub secure: Option<usize>,
+}
+
+impl HubRoute {
+    /// Jump counts as "shortest/secure", with "?" for routes that couldn't be found.
+    pub fn label(&self) -> String {
+        let fmt = |jumps: Option<usize>| jumps.map_or_else(|| "?".to_owned(), |j| j.to_string());
+        format!("{}/{}", fmt(self.shortest), fmt(self.secure))
+    }
+}
+
+#[derive(Deserialize)]
+struct NamedId {
+    id: u64,
+}
+
+#[derive(Deserialize)]
+struct Ids {
+    systems: Option<Vec<NamedId>>,
+}
+
+/// ESI has no system by a name from the static data, a sign the data is out of date.
+#[derive(Debug)]
+pub struct UnknownToEsi(pub String);
+
+impl fmt::Display for UnknownToEsi {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "No system named {}", self.0)
+    }
+}
+
+impl std::error::Error for UnknownToEsi {}
+
+/// Routes from k-space systems to the trade hubs, fetched on demand and kept for the session.
+pub type RouteCache = FetchCache<Vec<(&'static str, HubRoute)>>;
+
+impl RouteCache {
+    /// The first k-space system of those given that doesn't have routes yet or being fetched.
+    pub fn next_missing<'a>(
+        &self,
+        systems: impl IntoIterator<Item = &'a String>,
+    ) -> Option<&'a str> {
+        let mut missing: Vec<_> = systems
+            .into_iter()
+            .filter(|system| is_kspace(system) && !self.has_tried(system))
+            .collect();
+        missing.sort();
+        missing.first().map(|system| system.as_str())
+    }
+
+    /// Fetch routes from the system to each hub on a background task.
+    ///
+    /// Routes that couldn't be found are cached as unknown.
+    pub fn fetch(&mut self, esi: &Esi, system: &str) {
+        let esi = esi.clone();
+        self.spawn(system, fetch_routes(esi, system.to_owned()));
+    }
+}
+
+async fn fetch_routes(esi: Esi, system: String) -> Fetched<Vec<(&'static str, HubRoute)>> {
+    debug!("Fetching trade hub routes for {system}");
+    let mut unknown_to_esi = false;
+    let origin = match system_id(&esi, &system).await {
+        Ok(id) => Some(id),
+        Err(e) => {
+            warn!("Could not look up system ID for {system}: {e}");
+            unknown_to_esi = e.is::<UnknownToEsi>();
+            None
+        }
+    };
+    let mut routes = Vec::new();
+    for (hub, hub_id) in TRADE_HUBS {
+        let route = match origin {
+            Some(origin) => HubRoute {
+                shortest: route_jumps(&esi, origin, hub_id, "shortest").await,
+                secure: route_jumps(&esi, origin, hub_id, "secure").await,
+            },
+            None => HubRoute {
+                shortest: None,
+                secure: None,
+            },
+        };
+        routes.push((hub, route));
+    }
+    Fetched {
+        system,
+        value: Some(routes),
+        unknown_to_esi,
+    }
+}
+
+/// The hub with the fewest jumps by the shortest route, if any route is known.
+pub fn nearest_hub(routes: &[(&'static str, HubRoute)]) -> Option<(&'static str, usize)> {
+    routes
+        .iter()
+        .filter_map(|(hub, route)| route.shortest.map(|jumps| (*hub, jumps)))
+        .min_by_key(|(_, jumps)| *jumps)
+}
+
+/// Whether the system is known and in k-space.
+fn is_kspace(system: &str) -> bool {
+    ALL_SYSTEMS
+        .get(system)
+        .is_some_and(|data| !matches!(data.classification(), SystemClassification::WSpace(_)))
+}
+
+/// ESI ID of the named system.
+pub async fn system_id(esi: &Esi, system: &str) -> Result<u64> {
+    let body = serde_json::to_string(&[system])?;
+    let ids: Ids = esi
+        .query(
+            "POST",
+            RequestType::Public,
+            "universe/ids/",
+            None,
+            Some(&body),
+        )
+        .await?;
+    ids.systems
+        .and_then(|systems| systems.first().map(|s| s.id))
+        .ok_or_else(|| UnknownToEsi(system.to_owned()).into())
+}
+
+async fn route_jumps(esi: &Esi, origin: u64, destination: u64, flag: &str) -> Option<usize> {
+    let path = format!("route/{origin}/{destination}/");
+    match esi
+        .query::<Vec<u64>>(
+            "GET",
+            RequestType::Public,
+            &path,
+            Some(&[("flag", flag)]),
+            None,
+        )
+        .await
+    {
+        Ok(route) => Some(route.len().saturating_sub(1)),
+        Err(e) => {
+            warn!("Could not get {flag} route from {origin} to {destination}: {e}");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{nearest_hub, HubRoute, RouteCache};
+
+    #[test]
+    fn test_hub_route_label() {
+        let route = HubRoute {
+            shortest: Some(4),
+            secure: None,
+        };
+        assert_eq!(route.label(), "4/?");
+    }
+
+    #[test]
+    fn test_next_missing_only_kspace() {
+        let cache = RouteCache::default();
+        let systems = vec![
+            "J173213".to_owned(),
+            "Thera".to_owned(),
+            "Jita".to_owned(),
+            "Not A System".to_owned(),
+        ];
+        assert_eq!(cache.next_missing(&systems), Some("Jita"));
+    }
+
+    #[test]
+    fn test_next_missing_skips_fetched() {
+        let mut cache = RouteCache::default();
+        let systems = vec!["Jita".to_owned(), "Amamake".to_owned()];
+        let route = |shortest| HubRoute {
+            shortest,
+            secure: None,
+        };
+        cache.insert(
+            "Amamake",
+            vec![("Jita", route(Some(12))), ("Hek", route(Some(5)))],
+        );
+
+        assert_eq!(cache.next_missing(&systems), Some("Jita"));
+        assert_eq!(nearest_hub(cache.get("Amamake").unwrap()), Some(("Hek", 5)));
+        assert_eq!(nearest_hub(&[("Jita", route(None))]), None);
+    }
+}
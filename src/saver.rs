@@ -0,0 +1,212 @@
+use crate::state::{App, Snapshot};
+use anyhow::{anyhow, Result};
+use log::{debug, error};
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::{
+    sync::mpsc::{self, UnboundedReceiver, UnboundedSender},
+    task::JoinHandle,
+    time::Instant,
+};
+
+/// Where saved state ends up.
+pub trait StateWriter: Send + 'static {
+    fn write(&mut self, snapshot: &Snapshot) -> Result<()>;
+}
+
+/// Writes to the state file, keeping backups.
+pub struct FileWriter(pub PathBuf);
+
+impl StateWriter for FileWriter {
+    fn write(&mut self, snapshot: &Snapshot) -> Result<()> {
+        App::write_snapshot(&self.0, snapshot)
+    }
+}
+
+/// Saves the state on a background task, so the UI never waits on the disk.
+///
+/// Saves queued close together are written once, the latest winning.
+pub struct Saver {
+    snapshots: UnboundedSender<Snapshot>,
+    errors: UnboundedReceiver<String>,
+    task: JoinHandle<Result<()>>,
+}
+
+impl Saver {
+    /// Queue the snapshot to be written.
+    pub fn queue(&self, snapshot: Snapshot) -> Result<()> {
+        self.snapshots
+            .send(snapshot)
+            .map_err(|_| anyhow!("The background saver has stopped"))
+    }
+
+    /// Why the last write failed, if it did since this was last called.
+    ///
+    /// Failed writes are retried.
+    pub fn take_error(&mut self) -> Option<String> {
+        self.errors.try_recv().ok()
+    }
+
+    /// Write anything still queued and stop.
+    pub async fn finish(self) -> Result<()> {
+        drop(self.snapshots);
+        self.task.await?
+    }
+}
+
+/// Start saving on a background task, writing at most once per `debounce`.
+pub fn spawn(writer: impl StateWriter, debounce: Duration) -> Saver {
+    let (snapshots, receiver) = mpsc::unbounded_channel();
+    let (errors, error_receiver) = mpsc::unbounded_channel();
+    let task = tokio::spawn(run(writer, receiver, errors, debounce));
+    Saver {
+        snapshots,
+        errors: error_receiver,
+        task,
+    }
+}
+
+async fn run(
+    writer: impl StateWriter,
+    mut snapshots: UnboundedReceiver<Snapshot>,
+    errors: UnboundedSender<String>,
+    debounce: Duration,
+) -> Result<()> {
+    let writer = Arc::new(Mutex::new(writer));
+    let mut pending: Option<Snapshot> = None;
+    let mut due = Instant::now();
+    loop {
+        let received = if pending.is_some() {
+            match tokio::time::timeout_at(due, snapshots.recv()).await {
+                Ok(received) => received,
+                Err(_) => {
+                    if let Err(e) = write(&writer, &mut pending).await {
+                        error!("Could not save state: {e}");
+                        let _ = errors.send(e.to_string());
+                    }
+                    due = Instant::now() + debounce;
+                    continue;
+                }
+            }
+        } else {
+            snapshots.recv().await
+        };
+        match received {
+            Some(snapshot) => {
+                if pending.is_none() {
+                    due = due.max(Instant::now() + debounce);
+                }
+                pending = Some(snapshot);
+            }
+            // closed: write what's left straight away
+            None => return write(&writer, &mut pending).await,
+        }
+    }
+}
+
+/// Write the pending snapshot off the async threads, keeping it to retry if that fails.
+async fn write(
+    writer: &Arc<Mutex<impl StateWriter>>,
+    pending: &mut Option<Snapshot>,
+) -> Result<()> {
+    let Some(snapshot) = pending.take() else {
+        return Ok(());
+    };
+    debug!("Writing state");
+    let writer = writer.clone();
+    let (snapshot, result) = tokio::task::spawn_blocking(move || {
+        let result = match writer.lock() {
+            Ok(mut writer) => writer.write(&snapshot),
+            Err(_) => Err(anyhow!("The state writer panicked")),
+        };
+        (snapshot, result)
+    })
+    .await?;
+    if result.is_err() {
+        *pending = Some(snapshot);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{spawn, StateWriter};
+    use crate::state::Snapshot;
+    use anyhow::{anyhow, Result};
+    use std::{
+        sync::{Arc, Mutex},
+        time::{Duration, Instant},
+    };
+
+    /// Records what it writes, taking a while about it, and failing the first few times if asked.
+    struct FakeWriter {
+        delay: Duration,
+        failures: usize,
+        written: Arc<Mutex<Vec<serde_json::Value>>>,
+    }
+
+    impl StateWriter for FakeWriter {
+        fn write(&mut self, snapshot: &Snapshot) -> Result<()> {
+            std::thread::sleep(self.delay);
+            if self.failures > 0 {
+                self.failures -= 1;
+                return Err(anyhow!("disk full"));
+            }
+            self.written.lock().unwrap().push(snapshot.state.clone());
+            Ok(())
+        }
+    }
+
+    fn snapshot(n: u32) -> Snapshot {
+        Snapshot {
+            state: serde_json::json!(n),
+            compress: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_saves_dont_wait_on_the_writer() {
+        let written = Arc::new(Mutex::new(Vec::new()));
+        let saver = spawn(
+            FakeWriter {
+                delay: Duration::from_millis(300),
+                failures: 0,
+                written: written.clone(),
+            },
+            Duration::from_millis(50),
+        );
+
+        let start = Instant::now();
+        for n in 0..5 {
+            saver.queue(snapshot(n)).unwrap();
+        }
+        assert!(start.elapsed() < Duration::from_millis(100));
+
+        // only the latest of the saves made close together is written
+        saver.finish().await.unwrap();
+        assert_eq!(*written.lock().unwrap(), vec![serde_json::json!(4)]);
+    }
+
+    #[tokio::test]
+    async fn test_failed_saves_are_reported_and_retried() {
+        let written = Arc::new(Mutex::new(Vec::new()));
+        let mut saver = spawn(
+            FakeWriter {
+                delay: Duration::ZERO,
+                failures: 1,
+                written: written.clone(),
+            },
+            Duration::from_millis(20),
+        );
+
+        saver.queue(snapshot(1)).unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        assert_eq!(saver.take_error().as_deref(), Some("disk full"));
+        assert_eq!(*written.lock().unwrap(), vec![serde_json::json!(1)]);
+        saver.finish().await.unwrap();
+    }
+}
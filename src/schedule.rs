@@ -0,0 +1,182 @@
+use crate::{
+    config::{ReportOptions, ScheduledExport, ScheduledFormat},
+    export::chain_report,
+    state::App,
+};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use log::{error, info, warn};
+use std::{
+    fmt::Write,
+    fs,
+    time::{Duration, Instant},
+};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+/// The chain rendered in the format.
+pub fn render(app: &App, format: ScheduledFormat, report: ReportOptions) -> Result<String> {
+    Ok(match format {
+        ScheduledFormat::Json => serde_json::to_string_pretty(app)?,
+        ScheduledFormat::Markdown => chain_report(&app.system_data, report),
+        ScheduledFormat::Dot => app.export_dot(),
+    })
+}
+
+/// Fill the strftime-style fields of the path template from the time.
+pub fn expand_path(template: &str, at: DateTime<Utc>) -> Result<String> {
+    let mut path = String::new();
+    write!(path, "{}", at.format(template))
+        .map_err(|_| anyhow!("Invalid export path template '{template}'"))?;
+    Ok(path)
+}
+
+/// Writes the configured export every interval, skipping runs where nothing changed.
+///
+/// The file is written on a background thread so the UI never waits on the disk.
+pub struct Scheduler {
+    export: ScheduledExport,
+    /// Sections of the markdown report, kept in step with the config.
+    pub report: ReportOptions,
+    last_run: Instant,
+    last_content: Option<String>,
+    /// When an export was last handed off to be written.
+    pub last_export: Option<DateTime<Utc>>,
+    failed_sender: UnboundedSender<String>,
+    failed: UnboundedReceiver<String>,
+}
+
+impl Scheduler {
+    pub fn new(export: ScheduledExport, report: ReportOptions) -> Self {
+        let (failed_sender, failed) = mpsc::unbounded_channel();
+        Self {
+            export,
+            report,
+            last_run: Instant::now(),
+            last_content: None,
+            last_export: None,
+            failed_sender,
+            failed,
+        }
+    }
+
+    /// Whether the interval has passed since the last run.
+    pub fn is_due(&self, now: Instant) -> bool {
+        now.duration_since(self.last_run) >= Duration::from_secs(self.export.interval_minutes * 60)
+    }
+
+    /// Render the export if the chain changed since the last one, returning the path and content to write.
+    ///
+    /// The app is borrowed for the whole render, so the export can't see a half-applied change.
+    pub fn run(&mut self, app: &App, now: DateTime<Utc>) -> Result<Option<(String, String)>> {
+        self.last_run = Instant::now();
+        // a failed write is retried even if nothing changed since
+        if let Ok(path) = self.failed.try_recv() {
+            warn!("Retrying scheduled export to {path}");
+            self.last_content = None;
+        }
+        let content = render(app, self.export.format, self.report)?;
+        if self.last_content.as_ref() == Some(&content) {
+            return Ok(None);
+        }
+        let path = expand_path(&self.export.path, now)?;
+        self.last_content = Some(content.clone());
+        self.last_export = Some(now);
+        Ok(Some((path, content)))
+    }
+
+    /// Run the export if it's due, writing it on a background thread and logging the outcome.
+    pub fn tick(&mut self, app: &App) {
+        if !self.is_due(Instant::now()) {
+            return;
+        }
+        match self.run(app, Utc::now()) {
+            Ok(Some((path, content))) => {
+                let failed = self.failed_sender.clone();
+                tokio::task::spawn_blocking(move || match fs::write(&path, content) {
+                    Ok(_) => info!("Wrote scheduled export to {path}"),
+                    Err(e) => {
+                        error!("Could not write scheduled export to {path}: {e}");
+                        let _ = failed.send(path);
+                    }
+                });
+            }
+            Ok(None) => info!("Chain unchanged, skipping scheduled export"),
+            Err(e) => error!("Scheduled export failed: {e}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expand_path, render, Scheduler};
+    use crate::{
+        config::{ReportOptions, ScheduledExport, ScheduledFormat},
+        eve_data::{Signature, SignatureType},
+        state::App,
+    };
+    use chrono::{TimeZone, Utc};
+    use std::time::{Duration, Instant};
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.system_data.insert(
+            "J173213".to_owned(),
+            vec![Signature::new("ABC", "123", SignatureType::Unknown)],
+        );
+        app
+    }
+
+    #[test]
+    fn test_expand_path() {
+        let at = Utc.with_ymd_and_hms(2026, 10, 17, 15, 30, 0).unwrap();
+        assert_eq!(
+            expand_path("chain-%Y%m%d-%H%M.md", at).unwrap(),
+            "chain-20261017-1530.md"
+        );
+        assert_eq!(expand_path("chain.dot", at).unwrap(), "chain.dot");
+        assert!(expand_path("chain-%Q.md", at).is_err());
+    }
+
+    #[test]
+    fn test_render_formats() {
+        let app = app();
+        let report = ReportOptions::default();
+
+        let json = render(&app, ScheduledFormat::Json, report).unwrap();
+        assert!(json.contains("\"J173213\""));
+        let markdown = render(&app, ScheduledFormat::Markdown, report).unwrap();
+        assert!(markdown.starts_with("# Chain report"));
+        let dot = render(&app, ScheduledFormat::Dot, report).unwrap();
+        assert_eq!(dot, app.export_dot());
+    }
+
+    #[test]
+    fn test_scheduler_skips_unchanged() {
+        let mut scheduler = Scheduler::new(
+            ScheduledExport {
+                format: ScheduledFormat::Markdown,
+                path: "chain-%H%M.md".to_owned(),
+                interval_minutes: 15,
+            },
+            ReportOptions::default(),
+        );
+        let mut app = app();
+        let at = Utc.with_ymd_and_hms(2026, 10, 17, 15, 30, 0).unwrap();
+
+        assert!(!scheduler.is_due(Instant::now()));
+        assert!(scheduler.is_due(Instant::now() + Duration::from_secs(15 * 60)));
+
+        let (written, content) = scheduler.run(&app, at).unwrap().unwrap();
+        assert!(written.ends_with("chain-1530.md"));
+        assert!(content.contains("ABC-123"));
+        assert_eq!(scheduler.last_export, Some(at));
+
+        assert_eq!(scheduler.run(&app, at).unwrap(), None);
+        // a failed write is tried again
+        scheduler.failed_sender.send(written).unwrap();
+        assert!(scheduler.run(&app, at).unwrap().is_some());
+
+        app.system_data.remove("J173213");
+        assert!(scheduler.run(&app, at).unwrap().is_some());
+    }
+}
@@ -0,0 +1,313 @@
+use crate::eve_data::{ClipboardItem, SignatureType};
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Rough loot value of a relic or data site.
+#[derive(Deserialize)]
+pub struct SiteValue {
+    pub faction: String,
+    /// 1 (low) to 3 (high)
+    pub tier: u8,
+}
+
+impl SiteValue {
+    /// Tier as a short marker, e.g. "$$$".
+    pub fn marker(&self) -> String {
+        "$".repeat(self.tier as usize)
+    }
+}
+
+/// Known relic and data site names to their value.
+pub static SITE_VALUES: Lazy<HashMap<String, SiteValue>> = Lazy::new(|| {
+    let raw = include_str!("../static/site_values.json");
+    serde_json::from_str(raw).unwrap()
+});
+
+/// A combat site that's dangerous to warp into unprepared.
+#[derive(Deserialize)]
+pub struct SiteDanger {
+    /// W-space classes the warning applies in; empty for all systems.
+    pub classes: Vec<u8>,
+    pub warning: String,
+}
+
+/// Known dangerous combat site names.
+pub static SITE_DANGERS: Lazy<HashMap<String, SiteDanger>> = Lazy::new(|| {
+    let raw = include_str!("../static/site_dangers.json");
+    serde_json::from_str(raw).unwrap()
+});
+
+/// Warning for the named combat site in a system of the class, if it's dangerous.
+pub fn danger_warning(name: &str, class: Option<u8>) -> Option<&'static str> {
+    let danger = SITE_DANGERS.get(name)?;
+    let applies =
+        danger.classes.is_empty() || class.is_some_and(|class| danger.classes.contains(&class));
+    if applies {
+        Some(danger.warning.as_str())
+    } else {
+        None
+    }
+}
+
+/// Known w-space site names to the classes they spawn in.
+pub static SITE_CLASSES: Lazy<HashMap<String, Vec<u8>>> = Lazy::new(|| {
+    let raw = include_str!("../static/site_classes.json");
+    serde_json::from_str(raw).unwrap()
+});
+
+/// Whether the named site can spawn in a system of the class (`None` for k-space).
+///
+/// Names that aren't known are assumed to fit anywhere.
+pub fn can_appear_in(name: &str, class: Option<u8>) -> bool {
+    match SITE_CLASSES.get(name) {
+        Some(classes) => class.is_some_and(|class| classes.contains(&class)),
+        None => true,
+    }
+}
+
+/// Pasted sites that can't exist in a system of the class, hinting at a paste into the wrong system.
+pub fn wrong_class_sites(items: &[ClipboardItem], class: Option<u8>) -> Vec<&ClipboardItem> {
+    items
+        .iter()
+        .filter(|item| !item.sig_name.is_empty() && !can_appear_in(&item.sig_name, class))
+        .collect()
+}
+
+/// A single gas cloud in a gas site.
+#[derive(Deserialize)]
+pub struct GasCloud {
+    pub gas: String,
+    pub units: u32,
+}
+
+/// Contents and rough value of a gas site.
+#[derive(Deserialize)]
+pub struct GasSite {
+    /// 1 (low) to 3 (high)
+    pub tier: u8,
+    pub clouds: Vec<GasCloud>,
+}
+
+/// Known gas site names, w-space and k-space, to their contents.
+pub static GAS_SITES: Lazy<HashMap<String, GasSite>> = Lazy::new(|| {
+    let raw = include_str!("../static/gas_sites.json");
+    serde_json::from_str(raw).unwrap()
+});
+
+/// Value marker for the named gas site, or an empty string if it isn't known.
+pub fn gas_value_marker(name: &str) -> String {
+    match GAS_SITES.get(name) {
+        Some(site) => "$".repeat(site.tier as usize),
+        None => String::new(),
+    }
+}
+
+/// A single ore type in an ore site.
+#[derive(Deserialize)]
+pub struct OreDeposit {
+    pub ore: String,
+    /// Rough quantity: small, medium, or large
+    pub amount: String,
+}
+
+/// Asteroid composition of an ore site.
+#[derive(Deserialize)]
+pub struct OreSite {
+    pub ores: Vec<OreDeposit>,
+}
+
+/// Known ore site names to their composition.
+pub static ORE_SITES: Lazy<HashMap<String, OreSite>> = Lazy::new(|| {
+    let raw = include_str!("../static/ore_sites.json");
+    serde_json::from_str(raw).unwrap()
+});
+
+/// Lines of known information about a site, for the detail view.
+pub fn site_details(signature_type: &SignatureType) -> Vec<String> {
+    let mut lines = Vec::new();
+    match signature_type {
+        SignatureType::Relic(Some(name)) | SignatureType::Data(Some(name)) => {
+            if let Some(value) = SITE_VALUES.get(name) {
+                lines.push(format!("Value: {} ({})", value.marker(), value.faction));
+            }
+        }
+        SignatureType::Gas(Some(name)) => {
+            if let Some(site) = GAS_SITES.get(name) {
+                lines.push(format!("Value: {}", "$".repeat(site.tier as usize)));
+                lines.push("Clouds:".to_owned());
+                for cloud in &site.clouds {
+                    lines.push(format!("- {} x{}", cloud.gas, cloud.units));
+                }
+            }
+        }
+        SignatureType::Ore(Some(name)) => {
+            if let Some(site) = ORE_SITES.get(name) {
+                lines.push("Ores:".to_owned());
+                for deposit in &site.ores {
+                    lines.push(format!("- {} ({})", deposit.ore, deposit.amount));
+                }
+            }
+        }
+        _ => {}
+    }
+    lines
+}
+
+/// Value tier of a scanned relic, data, or gas site, if it's known.
+pub fn site_tier(signature_type: &SignatureType) -> Option<u8> {
+    match signature_type {
+        SignatureType::Relic(Some(name)) | SignatureType::Data(Some(name)) => {
+            SITE_VALUES.get(name).map(|value| value.tier)
+        }
+        SignatureType::Gas(Some(name)) => GAS_SITES.get(name).map(|site| site.tier),
+        _ => None,
+    }
+}
+
+/// Value marker and faction for the named site, or an empty string if it isn't known.
+pub fn value_marker(name: &str) -> String {
+    match SITE_VALUES.get(name) {
+        Some(value) => format!("{} {}", value.marker(), value.faction),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        can_appear_in, danger_warning, gas_value_marker, site_details, site_tier, value_marker,
+        wrong_class_sites, GAS_SITES, ORE_SITES, SITE_CLASSES, SITE_DANGERS, SITE_VALUES,
+    };
+    use crate::eve_data::{ClipboardItem, SignatureType};
+
+    #[test]
+    fn test_site_values_consistent() {
+        assert!(!SITE_VALUES.is_empty());
+        for (name, value) in SITE_VALUES.iter() {
+            assert!(!name.trim().is_empty());
+            assert!(!value.faction.trim().is_empty(), "{name}");
+            assert!((1..=3).contains(&value.tier), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_value_marker() {
+        assert_eq!(value_marker("Ruined Angel Temple Site"), "$$$ Angel Cartel");
+        assert_eq!(
+            value_marker("Forgotten Perimeter Power Array"),
+            "$ Sleepers"
+        );
+        assert_eq!(value_marker("Some Unknown Site"), "");
+    }
+
+    #[test]
+    fn test_site_dangers_consistent() {
+        assert!(!SITE_DANGERS.is_empty());
+        for (name, danger) in SITE_DANGERS.iter() {
+            assert!(!name.trim().is_empty());
+            assert!(!danger.warning.trim().is_empty(), "{name}");
+            assert!(danger.classes.iter().all(|c| (1..=6).contains(c)), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_danger_warning() {
+        assert!(danger_warning("Core Garrison", Some(5)).is_some());
+        assert!(danger_warning("Core Garrison", Some(2)).is_none());
+        assert!(danger_warning("Sentinel Hive", None).is_some());
+        assert!(danger_warning("Perimeter Camp", Some(5)).is_none());
+    }
+
+    #[test]
+    fn test_site_classes_consistent() {
+        assert!(!SITE_CLASSES.is_empty());
+        for (name, classes) in SITE_CLASSES.iter() {
+            assert!(!name.trim().is_empty());
+            assert!(!classes.is_empty(), "{name}");
+            assert!(classes.iter().all(|c| (1..=6).contains(c)), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_can_appear_in() {
+        assert!(can_appear_in("Core Garrison", Some(5)));
+        assert!(!can_appear_in("Core Garrison", Some(2)));
+        assert!(!can_appear_in("Core Garrison", None));
+        assert!(can_appear_in("Ruined Angel Temple Site", None));
+    }
+
+    #[test]
+    fn test_wrong_class_sites() {
+        let items = vec![
+            ClipboardItem::new("ABC-123", "Combat", "Perimeter Camp"),
+            ClipboardItem::new("DEF-456", "Combat", "Core Garrison"),
+            ClipboardItem::new("GHI-789", "Wormhole", ""),
+        ];
+
+        let wrong = wrong_class_sites(&items, Some(1));
+
+        assert_eq!(wrong.len(), 1);
+        assert_eq!(wrong[0].id, "DEF-456");
+        assert!(wrong_class_sites(&items[..1], Some(1)).is_empty());
+    }
+
+    #[test]
+    fn test_gas_sites_consistent() {
+        assert!(!GAS_SITES.is_empty());
+        for (name, site) in GAS_SITES.iter() {
+            assert!(!name.trim().is_empty());
+            assert!((1..=3).contains(&site.tier), "{name}");
+            assert!(!site.clouds.is_empty(), "{name}");
+            assert!(site.clouds.iter().all(|c| c.units > 0), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_gas_site_lookup() {
+        assert_eq!(gas_value_marker("Vital Core Reservoir"), "$$$");
+        assert_eq!(gas_value_marker("Barren Perimeter Reservoir"), "$");
+        assert_eq!(gas_value_marker("Some Unknown Reservoir"), "");
+
+        let details = site_details(&SignatureType::Gas(Some("Vital Core Reservoir".to_owned())));
+        assert_eq!(details[0], "Value: $$$");
+        assert!(details.contains(&"- Fullerite-C320 x6000".to_owned()));
+        assert!(site_details(&SignatureType::Gas(None)).is_empty());
+    }
+
+    #[test]
+    fn test_ore_sites_consistent() {
+        assert!(!ORE_SITES.is_empty());
+        for (name, site) in ORE_SITES.iter() {
+            assert!(!name.trim().is_empty());
+            assert!(!site.ores.is_empty(), "{name}");
+            for deposit in &site.ores {
+                assert!(!deposit.ore.trim().is_empty(), "{name}");
+                assert!(
+                    ["small", "medium", "large"].contains(&deposit.amount.as_str()),
+                    "{name}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_ore_site_details() {
+        let details = site_details(&SignatureType::Ore(Some(
+            "Ordinary Perimeter Deposit".to_owned(),
+        )));
+        assert_eq!(details[0], "Ores:");
+        assert!(details.contains(&"- Veldspar (large)".to_owned()));
+        assert!(site_details(&SignatureType::Ore(Some("Nope".to_owned()))).is_empty());
+    }
+
+    #[test]
+    fn test_site_tier() {
+        let relic = SignatureType::Relic(Some("Ruined Angel Temple Site".to_owned()));
+        assert_eq!(site_tier(&relic), Some(3));
+        let gas = SignatureType::Gas(Some("Barren Perimeter Reservoir".to_owned()));
+        assert_eq!(site_tier(&gas), Some(1));
+        assert_eq!(site_tier(&SignatureType::Relic(None)), None);
+        assert_eq!(site_tier(&SignatureType::Unknown), None);
+    }
+}
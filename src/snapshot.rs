@@ -0,0 +1,149 @@
+use crate::{
+    chain::{self, map_line, node_badges},
+    config::BadgeGlyphs,
+    export::HEADER,
+    state::App,
+};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::fs;
+
+/// Name of the snapshot file taken at the time, e.g. "snapshot-20261017-153000.txt".
+pub fn file_name(at: DateTime<Utc>) -> String {
+    format!("snapshot-{}.txt", at.format("%Y%m%d-%H%M%S"))
+}
+
+/// Rows as plain text, with each column padded to its widest cell.
+pub fn format_table(rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = Vec::new();
+    for row in rows {
+        for (i, cell) in row.iter().enumerate() {
+            let width = cell.chars().count();
+            match widths.get_mut(i) {
+                Some(w) => *w = (*w).max(width),
+                None => widths.push(width),
+            }
+        }
+    }
+    let mut out = String::new();
+    for row in rows {
+        let cells: Vec<_> = row
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| format!("{cell:<width$}", width = widths[i]))
+            .collect();
+        out.push_str(cells.join("  ").trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+/// The map as the Map pane shows it, then the signatures of each system in the chain.
+pub fn render(app: &App, glyphs: BadgeGlyphs, at: DateTime<Utc>) -> String {
+    let mut out = format!("Chain snapshot {}\n\n", at.format("%Y-%m-%d %H:%M UTC"));
+    let systems: Vec<String> = match &app.current_system {
+        Some(current_system) => {
+            for node in app.map_nodes() {
+                let mut line = map_line(&app.system_data, &node);
+                let badges = node_badges(&app.system_data, &app.recent_kills, None, &node.system)
+                    .render(glyphs);
+                if node.collapsed.is_empty() && !badges.is_empty() {
+                    line.push_str(&format!("  {badges}"));
+                }
+                out.push_str(&line);
+                out.push('\n');
+            }
+            chain::map_tree(&app.system_data, current_system)
+                .into_iter()
+                .map(|node| node.system)
+                .collect()
+        }
+        None => {
+            out.push_str("No system selected\n");
+            let mut systems: Vec<_> = app.system_data.keys().cloned().collect();
+            systems.sort();
+            systems
+        }
+    };
+
+    for system in systems {
+        let signatures: Vec<_> = app
+            .system_data
+            .get(&system)
+            .into_iter()
+            .flatten()
+            .filter(|sig| !sig.ignored)
+            .collect();
+        if signatures.is_empty() {
+            continue;
+        }
+        out.push_str(&format!("\n{system}\n"));
+        let mut rows = vec![HEADER.iter().map(|h| h.to_string()).collect()];
+        rows.extend(signatures.iter().map(|sig| sig.to_row()));
+        out.push_str(&format_table(&rows));
+    }
+    out
+}
+
+/// Write a snapshot of the app to a timestamped file, returning its name.
+pub fn write(app: &App, glyphs: BadgeGlyphs) -> Result<String> {
+    let now = Utc::now();
+    let name = file_name(now);
+    fs::write(&name, render(app, glyphs, now))?;
+    Ok(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{file_name, format_table, render};
+    use crate::{
+        config::BadgeGlyphs,
+        eve_data::{Signature, SignatureType, SignatureWormhole, WormholeLife},
+        state::App,
+    };
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn test_format_table() {
+        let rows = vec![
+            vec!["ID".to_owned(), "Type".to_owned(), "".to_owned()],
+            vec!["ABC-123".to_owned(), "Relic".to_owned(), "".to_owned()],
+        ];
+        assert_eq!(format_table(&rows), "ID       Type\nABC-123  Relic\n");
+    }
+
+    #[test]
+    fn test_render() {
+        let mut app = App::new();
+        app.system_data.insert(
+            "J173213".to_owned(),
+            vec![
+                Signature::new(
+                    "ABC",
+                    "123",
+                    SignatureType::Wormhole(SignatureWormhole {
+                        destination: Some("Jita".to_owned()),
+                        life: WormholeLife::EndOfLife,
+                        ..Default::default()
+                    }),
+                ),
+                Signature::new("DEF", "456", SignatureType::Unknown),
+            ],
+        );
+        app.current_system = Some("J173213".to_owned());
+        let at = Utc.with_ymd_and_hms(2026, 10, 17, 15, 30, 0).unwrap();
+
+        let text = render(&app, BadgeGlyphs::Ascii, at);
+
+        assert_eq!(file_name(at), "snapshot-20261017-153000.txt");
+        let lines: Vec<_> = text.lines().collect();
+        assert_eq!(lines[0], "Chain snapshot 2026-10-17 15:30 UTC");
+        assert_eq!(lines[2], "J173213  2/1  E");
+        assert_eq!(lines[3], "  Jita  E");
+        assert_eq!(lines[5], "J173213");
+        assert!(lines[6].starts_with("ID       Type"));
+        assert!(lines[7].starts_with("ABC-123  Wormhole"));
+        assert!(lines[8].starts_with("DEF-456  Unknown"));
+        assert_eq!(lines.len(), 9);
+    }
+}
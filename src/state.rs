@@ -2,27 +2,494 @@
 
 use cli_clipboard::x11_clipboard::Clipboard;
 
-use crate::eve_data::{
-    ClipboardItem, Signature, SignatureId, SignatureType, SignatureWormhole, WormholeLife,
-    WormholeMass,
+use crate::{
+    activity::{self, ActivityEvent, ActivityKind},
+    alert::{detect_alerts, detect_valuable_sites, Alert},
+    chain::{self, Connection},
+    changelog,
+    config::Confirmations,
+    csv_import::{to_signatures, CsvImport},
+    eve_data::{
+        destination_class, is_grouped_paste, parse_grouped_paste, parse_paste, static_for_class,
+        Attribution, ClipboardItem, ConnectionKind, Provenance, Signature, SignatureId,
+        SignatureType, SignatureWormhole, StaticMatch, WormholeDirection, WormholeLife,
+        WormholeMass, ALL_SYSTEMS, PLACEHOLDER_ID, WORMHOLE_TYPES,
+    },
+    integrity::Problem,
+    merge::{self, Conflict, MergeSummary, Resolution},
+    saver::Saver,
+    sites::wrong_class_sites,
+    wanderer::{is_wanderer_export, parse_wanderer_export},
 };
-use std::collections::HashMap;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Utc};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use log::{debug, error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{Read, Write},
+    path::{Path, PathBuf},
+};
+
+/// File that the app state is persisted to.
+pub const STATE_FILE: &str = "state.json";
+
+/// Previous saves kept alongside the state file, as "state.json.1" (newest) and so on.
+pub const BACKUP_COUNT: usize = 3;
+
+/// First bytes of a gzip stream, to tell compressed state files from plain JSON.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Version of the state file format written by this build.
+///
+/// Version 1 files are the bare app state, without a version wrapper, and
+/// keep the signatures under `system_data` rather than `systems`.
+pub const STATE_VERSION: u32 = 2;
+
+/// The app state as written to the state file.
+#[derive(Serialize)]
+struct VersionedState<'a> {
+    version: u32,
+    app: &'a App,
+}
+
+/// A copy of the app state taken to be written later.
+pub struct Snapshot {
+    pub state: serde_json::Value,
+    /// Whether to gzip it.
+    pub compress: bool,
+}
+
+/// Upgrades the saved app state from one version to the next.
+type Migration = fn(serde_json::Value) -> Result<serde_json::Value>;
+
+/// Each version's upgrade to the next, starting from version 1.
+const MIGRATIONS: [Migration; STATE_VERSION as usize - 1] = [v1_to_v2];
+
+/// Version 2 renamed `system_data` to `systems`.
+fn v1_to_v2(mut app: serde_json::Value) -> Result<serde_json::Value> {
+    let fields = app
+        .as_object_mut()
+        .ok_or_else(|| anyhow!("Saved state isn't an object"))?;
+    if let Some(systems) = fields.remove("system_data") {
+        fields.insert("systems".to_owned(), systems);
+    }
+    Ok(app)
+}
+
+/// Version of a saved state; version 1 files don't have one.
+fn saved_version(saved: &serde_json::Value) -> u64 {
+    saved.get("version").and_then(|v| v.as_u64()).unwrap_or(1)
+}
+
+/// Bring a saved state of any known version up to the current one, a version at a time.
+///
+/// Fields added since the state was saved are filled with their defaults.
+pub fn migrate(saved: serde_json::Value) -> Result<App> {
+    let version = saved_version(&saved);
+    if version > STATE_VERSION as u64 {
+        return Err(anyhow!(
+            "State file version {version} is newer than this build supports ({STATE_VERSION}); \
+             update the app to open it"
+        ));
+    }
+    if version == 0 {
+        return Err(anyhow!("State file has an unknown version 0"));
+    }
+    let mut app = if saved.get("version").is_some() {
+        saved["app"].clone()
+    } else {
+        saved
+    };
+    for (from, step) in (version..).zip(&MIGRATIONS[version as usize - 1..]) {
+        info!("Migrating state from version {from} to {}", from + 1);
+        app = step(app).map_err(|e| anyhow!("Migrating state from version {from}: {e}"))?;
+    }
+    Ok(serde_json::from_value(app)?)
+}
+
+/// A destructive change, which may need confirming first.
+#[derive(Clone, PartialEq, Debug)]
+pub enum ConfirmAction {
+    /// Delete signatures from the current system.
+    DeleteSignatures(Vec<SignatureId>),
+    /// Remove systems and their signatures from the map.
+    RemoveSystems(Vec<String>),
+    /// Mark every wormhole in a system EOL, or restore their lives from before.
+    AllEol { system: String, restore: bool },
+    /// Replace placeholders with the scanned wormholes they look like.
+    MatchPlaceholders(Vec<PlaceholderMatch>),
+}
+
+/// A typed placeholder wormhole and the scanned one it looks like.
+#[derive(Clone, PartialEq, Debug)]
+pub struct PlaceholderMatch {
+    pub system: String,
+    pub placeholder: SignatureId,
+    pub wh_type: String,
+    pub scanned: SignatureId,
+}
+
+impl ConfirmAction {
+    /// Title of the confirmation.
+    pub fn title(&self) -> &'static str {
+        match self {
+            Self::DeleteSignatures(_) => "Delete",
+            Self::RemoveSystems(_) => "Remove systems",
+            Self::AllEol { .. } => "EOL",
+            Self::MatchPlaceholders(_) => "Placeholders",
+        }
+    }
+
+    /// Question asked before doing it.
+    pub fn message(&self) -> String {
+        match self {
+            Self::DeleteSignatures(ids) => format!("Delete {} signature(s)? (y/n)", ids.len()),
+            Self::RemoveSystems(systems) => format!(
+                "Remove {} and their signatures from the map? (y/n)",
+                systems.join(", ")
+            ),
+            Self::AllEol { restore: true, .. } => {
+                "Restore wormhole lives from before they were all marked EOL? (y/n)".to_owned()
+            }
+            Self::AllEol { restore: false, .. } => {
+                "Mark every wormhole in this system EOL? (y/n)".to_owned()
+            }
+            Self::MatchPlaceholders(matches) => {
+                let mut message = "Scanned wormholes that look like placeholders:\n".to_owned();
+                for found in matches {
+                    message.push_str(&format!(
+                        "{} {} ({}) is {}\n",
+                        found.system, found.placeholder, found.wh_type, found.scanned
+                    ));
+                }
+                message.push_str("Replace the placeholders? (y/n)");
+                message
+            }
+        }
+    }
+}
+
+/// How much of the probe scanner a paste covers.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PasteMode {
+    /// Every signature in the system: ones missing from it have despawned
+    Full,
+    /// Only the selected rows: merged in without removing anything
+    Partial,
+}
+
+/// Write the file through a temporary file next to it, renamed into place,
+/// so a crash part-way through leaves the old contents intact.
+///
+/// The data is flushed to disk before the rename, and on Unix the rename
+/// itself is flushed by syncing the directory.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<()> {
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    let temp = PathBuf::from(temp);
+    let mut file = File::create(&temp)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    drop(file);
+    fs::rename(&temp, path)?;
+    #[cfg(unix)]
+    {
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        File::open(dir)?.sync_all()?;
+    }
+    Ok(())
+}
+
+/// The path with a suffix added, e.g. "state.json.1".
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Path of the nth newest backup of the state file, counting from 1.
+pub fn backup_path(path: &Path, n: usize) -> PathBuf {
+    with_suffix(path, &format!(".{n}"))
+}
+
+/// Shift the backups along and copy the current state file in as the newest.
+fn rotate_backups(path: &Path) -> Result<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+    for n in (1..BACKUP_COUNT).rev() {
+        let older = backup_path(path, n);
+        if older.exists() {
+            fs::rename(&older, backup_path(path, n + 1))?;
+        }
+    }
+    fs::copy(path, backup_path(path, 1))?;
+    Ok(())
+}
+
+/// State file contents, gzipped if asked.
+fn encode(text: &str, compress: bool) -> Result<Vec<u8>> {
+    if !compress {
+        return Ok(text.as_bytes().to_vec());
+    }
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(text.as_bytes())?;
+    Ok(encoder.finish()?)
+}
+
+/// Text of a state file, decompressing it if it starts like gzip.
+fn decode(bytes: &[u8]) -> Result<String> {
+    if !bytes.starts_with(&GZIP_MAGIC) {
+        return Ok(String::from_utf8(bytes.to_vec())?);
+    }
+    let mut text = String::new();
+    GzDecoder::new(bytes).read_to_string(&mut text)?;
+    Ok(text)
+}
+
+/// The saved state in the file, compressed or not, before migrating.
+fn read_saved(path: &Path) -> Result<serde_json::Value> {
+    Ok(serde_json::from_str(&decode(&fs::read(path)?)?)?)
+}
+
+/// What the text typed into a prompt is for.
+#[derive(Clone, PartialEq, Debug)]
+pub enum PromptKind {
+    /// Origin type of the selected K162
+    WormholeOrigin,
+    /// Note on the selected wormhole's connection
+    ConnectionNote,
+    /// System to switch to
+    SwitchSystem,
+    /// System to move the selected signature to
+    MoveSignature,
+    /// System to copy the selected signature to
+    CopySignature,
+    /// Scratchpad note on the current system
+    SystemNote,
+    /// Type of the highlighted wormhole
+    WormholeType,
+    /// Path of a teammate's map to import
+    ImportFile,
+    /// ID or name of a signature in any system
+    FindSignature,
+}
+
+impl PromptKind {
+    pub fn title(&self) -> &'static str {
+        match self {
+            PromptKind::WormholeOrigin => "K162 origin type",
+            PromptKind::ConnectionNote => "Connection note",
+            PromptKind::SwitchSystem => "Go to system",
+            PromptKind::MoveSignature => "Move signature to system",
+            PromptKind::CopySignature => "Copy signature to system",
+            PromptKind::SystemNote => "System note",
+            PromptKind::WormholeType => "Wormhole type",
+            PromptKind::ImportFile => "Import teammate map or CSV from file",
+            PromptKind::FindSignature => "Find signature in any system",
+        }
+    }
+}
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, PartialEq, Default)]
 pub enum ViewMode {
+    #[default]
     Normal,
     Adding(Signature),
     Editing(Signature),
+    /// Fields: selected index in the favorites list
+    Favorites(usize),
+    /// Fields: what the input is for, text entered so far
+    Prompt(PromptKind, String),
+    /// Planned hops to the nearest k-space exit.
+    /// Fields: whether EOL and critical holes are avoided
+    ExitRoute(bool),
+    /// Mass-rolling plan for the selected wormhole.
+    /// Fields: index of the rolling ship
+    Rolling(usize),
+    /// Wormholes from other systems into the current one.
+    /// Fields: selected index in the list
+    Inbound(usize),
+    /// Navigating the map.
+    /// Fields: selected index in the map
+    Map(usize),
+    /// Ask for a yes before a destructive action.
+    Confirm {
+        message: String,
+        action: ConfirmAction,
+    },
+    /// Offer to replace a deleted static with an unscanned placeholder.
+    /// Fields: the static's wormhole type
+    StaticPlaceholder(String),
+    /// Ask whether a paste is the full scanner list or only some rows.
+    /// Fields: the pasted text
+    PasteMode(String),
+    /// Searching for an action to run.
+    /// Fields: search text, selected index in the matches
+    Palette(String, usize),
+    /// Browsing systems grouped by region, with the groups in `App::browse_expanded` open.
+    /// Fields: selected index in the tree
+    Browse(usize),
+    /// Resolving conflicts from a teammate import, listed in `App::import_conflicts`.
+    /// Fields: selected index in the list
+    ImportConflicts(usize),
+    /// Changes since the last version run, shown once after upgrading.
+    /// Fields: the release notes
+    WhatsNew(String),
+    /// Problems found in the map, offering to repair them.
+    /// Fields: the problems
+    Integrity(Vec<Problem>),
+    /// Mapping the columns of a spreadsheet paste or file before importing it.
+    CsvImport(CsvImport),
+    /// Every key and what it does.
+    /// Fields: lines scrolled past
+    Help(usize),
+}
+
+/// Everything a signature move or teammate import can change, saved so it can be undone.
+#[derive(Clone)]
+struct MapSnapshot {
+    system_data: HashMap<String, Vec<Signature>>,
+    activity: HashMap<String, Vec<ActivityEvent>>,
+    connections: Vec<Connection>,
+    favorites: Vec<(String, SignatureId)>,
+    pinned: HashMap<String, Vec<SignatureId>>,
 }
 
 // App state.
+#[derive(Serialize, Deserialize)]
 pub struct App {
     pub current_system: Option<String>,
+    #[serde(rename = "systems")]
     pub system_data: HashMap<String, Vec<Signature>>,
+    /// Starred signatures, as (system, signature) pairs.
+    #[serde(default)]
+    pub favorites: Vec<(String, SignatureId)>,
+    /// Wormholes linked across systems.
+    #[serde(default)]
+    pub connections: Vec<Connection>,
+    /// Recent signature appearances and disappearances, per system.
+    #[serde(default)]
+    pub activity: HashMap<String, Vec<ActivityEvent>>,
+    /// When each system was last pasted into, edited, or visited.
+    #[serde(default)]
+    pub last_activity: HashMap<String, DateTime<Utc>>,
+    /// When each system's signatures were last merged from a scan.
+    #[serde(default)]
+    pub last_scanned: HashMap<String, DateTime<Utc>>,
+    /// Free-text scratchpad note on each system.
+    #[serde(default)]
+    pub system_notes: HashMap<String, String>,
+    /// Signatures listed first in each system, in the order they were pinned.
+    #[serde(default)]
+    pub pinned: HashMap<String, Vec<SignatureId>>,
+    /// Version of the app that last ran with this state, to tell when it's been upgraded.
+    #[serde(default)]
+    pub last_seen_version: Option<String>,
 
+    #[serde(skip)]
     pub data_index: usize,
 
+    /// Life and mass given to wormholes as they're added.
+    #[serde(skip)]
+    pub new_wormhole: SignatureWormhole,
+
+    #[serde(skip)]
     pub view: ViewMode,
+
+    /// Whether going to a new w-space system adds placeholders for its statics.
+    #[serde(skip)]
+    pub auto_static_placeholders: bool,
+    /// Whether pasting highlights the first new or unresolved signature.
+    #[serde(skip)]
+    pub focus_on_paste: bool,
+    /// Scan strength a pasted signature must reach before its type is used.
+    #[serde(skip)]
+    pub min_scan_strength: Option<f32>,
+    /// Minimum value tier of newly-scanned sites to alert on.
+    #[serde(skip)]
+    pub valuable_site_tier: Option<u8>,
+    /// Map branches idle for longer than this are collapsed.
+    #[serde(skip)]
+    pub stale_after: Option<Duration>,
+    /// Stale branches the user has expanded, by their first system.
+    #[serde(skip)]
+    pub expanded_branches: Vec<String>,
+    /// System the chain is mapped out from.
+    #[serde(skip)]
+    pub home_system: Option<String>,
+    /// Whether orphaned systems are removed whenever the state is saved.
+    #[serde(skip)]
+    pub sweep_on_save: bool,
+    /// Whether the map can only be looked at, not changed.
+    #[serde(skip)]
+    pub read_only: bool,
+    /// Whether the state file and its backups are gzipped.
+    #[serde(skip)]
+    pub compress_state: bool,
+    /// Name local edits are attributed to.
+    #[serde(skip)]
+    pub author: String,
+    /// Which destructive actions are confirmed first.
+    #[serde(skip)]
+    pub confirmations: Confirmations,
+    /// Writes saves in the background, when running interactively.
+    #[serde(skip)]
+    pub saver: Option<Saver>,
+    /// Signatures as they were last attributed, to find what's changed since.
+    #[serde(skip)]
+    edit_baseline: HashMap<String, Vec<Signature>>,
+
+    /// Regions and constellations opened in the system browser.
+    #[serde(skip)]
+    pub browse_expanded: Vec<String>,
+
+    /// Whether ignored signatures are shown in the list.
+    #[serde(skip)]
+    pub show_ignored: bool,
+
+    /// Multi-selected signatures in the current system.
+    #[serde(skip)]
+    pub selection: Vec<SignatureId>,
+
+    /// Message shown in the status bar until the next key press.
+    #[serde(skip)]
+    pub status_message: Option<String>,
+    /// Ship and pod kills in the last hour by system, once they've been fetched from ESI.
+    #[serde(skip)]
+    pub recent_kills: HashMap<String, u32>,
+    /// Lives of wormholes before they were all marked EOL, by system.
+    #[serde(skip)]
+    pub lives_before_eol: HashMap<String, Vec<(SignatureId, WormholeLife)>>,
+    /// Conflicts from the last teammate import still to be resolved.
+    #[serde(skip)]
+    pub import_conflicts: Vec<Conflict>,
+    /// The map from before the last teammate import, to undo it.
+    /// Dropped as soon as anything else changes, except resolving its conflicts.
+    #[serde(skip)]
+    before_import: Option<MapSnapshot>,
+    /// The map from before the last signature move, to undo it.
+    /// Dropped as soon as anything else changes.
+    #[serde(skip)]
+    before_move: Option<MapSnapshot>,
+}
+
+/// The lowest placeholder number not yet used in the system, e.g. "001".
+fn next_placeholder_number(signatures: &[Signature]) -> String {
+    (1..)
+        .map(|n| format!("{n:03}"))
+        .find(|number| {
+            !signatures
+                .iter()
+                .any(|sig| sig.is_placeholder() && &sig.identifier.number == number)
+        })
+        .unwrap_or_default()
 }
 
 impl App {
@@ -44,6 +511,9 @@ impl App {
                         destination: None,
                         life: WormholeLife::Stable,
                         mass: WormholeMass::Stable,
+                        origin_type: None,
+                        connection_kind: ConnectionKind::IncomingK162,
+                        mass_used: 0,
                     }),
                 ),
             ],
@@ -52,235 +522,2243 @@ impl App {
         Self {
             current_system: Some("J173213".to_owned()),
             system_data,
+            favorites: Vec::new(),
+            connections: Vec::new(),
+            activity: HashMap::new(),
+            last_activity: HashMap::new(),
+            last_scanned: HashMap::new(),
+            system_notes: HashMap::new(),
+            pinned: HashMap::new(),
+            last_seen_version: Some(changelog::VERSION.to_owned()),
 
             data_index: 0,
 
+            new_wormhole: SignatureWormhole::default(),
+
             view: ViewMode::Normal,
-        }
-    }
 
-    pub fn system_signatures(&self) -> Vec<&Signature> {
-        if let Some(current_system) = self.current_system.as_ref() {
-            if let Some(data) = self.system_data.get(current_system) {
-                return data.iter().collect();
-            }
+            auto_static_placeholders: false,
+            focus_on_paste: false,
+            min_scan_strength: None,
+            valuable_site_tier: None,
+            stale_after: None,
+            expanded_branches: Vec::new(),
+            home_system: None,
+            sweep_on_save: false,
+            read_only: false,
+            compress_state: false,
+            author: "local".to_owned(),
+            confirmations: Confirmations::default(),
+            saver: None,
+            edit_baseline: HashMap::new(),
+
+            browse_expanded: Vec::new(),
+            show_ignored: false,
+
+            selection: Vec::new(),
+
+            status_message: None,
+            recent_kills: HashMap::new(),
+            lives_before_eol: HashMap::new(),
+            import_conflicts: Vec::new(),
+            before_import: None,
+            before_move: None,
         }
-        Vec::new()
     }
 
-    /// Merge data from a paste into the existing system data.
-    pub fn merge_in(&mut self, new_data: &[ClipboardItem]) {
-        if let Some(current_system) = self.current_system.as_ref() {
-            if !self.system_data.contains_key(current_system) {
-                self.system_data
-                    .insert(current_system.to_owned(), Vec::new());
-            }
-            let existing_ids: Vec<_> = self
-                .system_data
-                .get(current_system)
-                .unwrap()
-                .iter()
-                .map(|sig| sig.identifier.clone())
-                .collect();
+    /// Load the app state from the state file, starting fresh if there isn't one.
+    pub fn load() -> Result<Self> {
+        Self::load_from(Path::new(STATE_FILE))
+    }
 
-            // update existing data
-            let existing = self.system_data.get_mut(current_system).unwrap();
-            for signature in existing {
-                let id: String = format!("{}", signature.identifier);
-                if let Some(check) = new_data.iter().find(|d| d.id == id) {
-                    let (_new_id, new_type) = check.into();
-                    match new_type {
-                        SignatureType::Unknown => {
-                            // no new information; leave it
-                        }
-                        SignatureType::Wormhole(_) => {
-                            match &signature.signature_type {
-                                SignatureType::Wormhole(signature_wh) => {
-                                    // existing signature is a wormhole, so nothing to do
-                                    // since the scanner doesn't give any additional info
-                                }
-                                _ => {
-                                    // existing signature is something else (likely unknown),
-                                    // so overwrite with a default wormhole
-                                    signature.signature_type =
-                                        SignatureType::Wormhole(SignatureWormhole::default());
-                                }
-                            }
-                        }
-                        _ => {
-                            if new_type.has_name() {
-                                // overwrite with the new data since the new data has the same
-                                signature.signature_type = new_type;
-                            } else if signature.signature_type.has_name() {
-                                // existing has a name; do nothing
-                            } else {
-                                // neither has the name, so overwrite in case the classifier updated
-                                signature.signature_type = new_type;
-                            }
+    /// Load the app state from the file, which may be plain or gzipped JSON.
+    ///
+    /// A file that can't be read or migrated, such as one cut short by a
+    /// crash, is moved aside to a `.bak` file. The newest backup that can be
+    /// loaded is used instead, or failing that the app starts with an empty map.
+    /// A file from a newer version is an error and left alone.
+    ///
+    /// A file from an older version is kept as e.g. `state.json.v1` and
+    /// rewritten in the current format once it's loaded.
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::new());
+        }
+        let e = match read_saved(path) {
+            // not ours to move aside; a newer build can still open it
+            Ok(saved) if saved_version(&saved) > STATE_VERSION as u64 => return migrate(saved),
+            Ok(saved) => {
+                let version = saved_version(&saved);
+                match migrate(saved) {
+                    Ok(app) => {
+                        if version < STATE_VERSION as u64 {
+                            fs::copy(path, with_suffix(path, &format!(".v{version}")))?;
+                            app.save_to(path)?;
                         }
+                        return Ok(app);
                     }
+                    Err(e) => e,
                 }
             }
+            Err(e) => e,
+        };
+        let corrupt = with_suffix(path, ".bak");
+        error!(
+            "State file is corrupt, moving it to {}: {e}",
+            corrupt.display()
+        );
+        fs::rename(path, &corrupt)?;
+        for n in 1..=BACKUP_COUNT {
+            let backup = backup_path(path, n);
+            if let Ok(mut app) = read_saved(&backup).and_then(migrate) {
+                warn!("Restoring state from {}", backup.display());
+                app.status_message = Some(format!(
+                    "State file was corrupt; it was saved as {} and {} was restored",
+                    corrupt.display(),
+                    backup.display()
+                ));
+                return Ok(app);
+            }
+        }
+        let mut app = Self {
+            current_system: None,
+            system_data: HashMap::new(),
+            ..Self::new()
+        };
+        app.status_message = Some(format!(
+            "State file was corrupt; it was saved as {}",
+            corrupt.display()
+        ));
+        Ok(app)
+    }
+
+    /// Save the app state to the state file.
+    ///
+    /// With a background saver, this only queues a snapshot for it.
+    pub fn save(&self) -> Result<()> {
+        match &self.saver {
+            Some(saver) => saver.queue(self.snapshot()?),
+            None => self.save_to(Path::new(STATE_FILE)),
+        }
+    }
+
+    /// Save the app state to the file, keeping the previous saves as backups.
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        Self::write_snapshot(path, &self.snapshot()?)
+    }
+
+    /// A copy of the app state as it's saved.
+    pub fn snapshot(&self) -> Result<Snapshot> {
+        Ok(Snapshot {
+            state: serde_json::to_value(VersionedState {
+                version: STATE_VERSION,
+                app: self,
+            })?,
+            compress: self.compress_state,
+        })
+    }
+
+    /// Write the snapshot to the file, keeping the previous saves as backups.
+    pub fn write_snapshot(path: &Path, snapshot: &Snapshot) -> Result<()> {
+        let text = serde_json::to_string_pretty(&snapshot.state)?;
+        rotate_backups(path)?;
+        write_atomic(path, &encode(&text, snapshot.compress)?)
+    }
 
-            // insert any new items
-            let existing = self.system_data.get_mut(current_system).unwrap();
-            for clipboard_item in new_data {
-                let (new_sig_id, new_sig_type) = clipboard_item.into();
-                if !existing_ids.contains(&new_sig_id) {
-                    existing.push(Signature {
-                        identifier: new_sig_id,
-                        signature_type: new_sig_type,
+    /// Signatures listed for the current system, leaving out ignored ones unless they're shown.
+    ///
+    /// Pinned signatures come first.
+    pub fn system_signatures(&self) -> Vec<&Signature> {
+        if let Some(current_system) = self.current_system.as_ref() {
+            if let Some(data) = self.system_data.get(current_system) {
+                let mut signatures: Vec<_> = data
+                    .iter()
+                    .filter(|sig| self.show_ignored || !sig.ignored)
+                    .collect();
+                if let Some(pinned) = self.pinned.get(current_system) {
+                    signatures.sort_by_key(|sig| {
+                        pinned
+                            .iter()
+                            .position(|id| id == &sig.identifier)
+                            .unwrap_or(pinned.len())
                     });
                 }
+                return signatures;
             }
         }
+        Vec::new()
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::App;
-    use crate::eve_data::{
-        ClipboardItem, Signature, SignatureType, SignatureWormhole, WormholeLife,
-    };
 
-    #[test]
-    fn test_app_merge_in_empty_empty() {
-        let mut app = App::new();
-        app.current_system = Some("Thera".to_owned());
-        app.merge_in(&[]);
+    /// Position of the highlighted signature among all of the current system's, hidden ones included.
+    pub fn selected_data_index(&self) -> Option<usize> {
+        let id = &self.selected_signature()?.identifier;
+        self.system_data
+            .get(self.current_system.as_ref()?)?
+            .iter()
+            .position(|sig| &sig.identifier == id)
+    }
 
-        assert!(app.system_data.contains_key("Thera"));
-        assert!(app.system_data.get("Thera").unwrap().is_empty());
+    /// Keep the highlighted row within the listed signatures.
+    fn clamp_data_index(&mut self) {
+        let count = self.system_signatures().len();
+        if self.data_index >= count {
+            self.data_index = count.saturating_sub(1);
+        }
     }
 
-    #[test]
-    fn test_app_merge_in_some_empty() {
-        let mut app = App::new();
-        app.current_system = Some("Thera".to_owned());
-        app.system_data.insert(
-            "Thera".to_owned(),
-            vec![Signature::new(
-                "ABC",
-                "123",
-                crate::eve_data::SignatureType::Data(Some("Foobar".to_owned())),
-            )],
-        );
-        app.merge_in(&[]);
+    /// Index of the first signature row on screen, when `visible_rows` fit.
+    ///
+    /// The list scrolls just far enough to keep the highlighted row in view.
+    pub fn visible_row_offset(&self, visible_rows: usize) -> usize {
+        self.data_index
+            .saturating_sub(visible_rows.saturating_sub(1))
+    }
 
-        assert_eq!(app.system_data.get("Thera").unwrap().len(), 1);
+    /// Quick-select digit (1-9) shown next to the signature row at the index, if it's on screen.
+    pub fn row_digit(&self, index: usize, visible_rows: usize) -> Option<usize> {
+        let offset = self.visible_row_offset(visible_rows);
+        let digit = index.checked_sub(offset)? + 1;
+        (digit <= visible_rows.min(9)).then_some(digit)
     }
 
-    #[test]
-    fn test_app_merge_in_update_type() {
-        let mut app = App::new();
-        app.current_system = Some("Thera".to_owned());
-        app.system_data.insert(
-            "Thera".to_owned(),
-            vec![Signature::new(
-                "ABC",
-                "123",
-                crate::eve_data::SignatureType::Unknown,
-            )],
-        );
+    /// Highlight the on-screen signature row with the quick-select digit.
+    ///
+    /// Returns false if no visible row has that digit.
+    pub fn select_row_digit(&mut self, digit: usize, visible_rows: usize) -> bool {
+        if !(1..=visible_rows.min(9)).contains(&digit) {
+            return false;
+        }
+        let index = self.visible_row_offset(visible_rows) + digit - 1;
+        if index >= self.system_signatures().len() {
+            return false;
+        }
+        self.data_index = index;
+        true
+    }
 
-        app.merge_in(&[ClipboardItem::new("ABC-123", "Relic", "Foobar")]);
+    /// Ignore or un-ignore the signatures in the current system.
+    pub fn toggle_ignored(&mut self, ids: &[SignatureId]) {
+        let Some(sigs) = self
+            .current_system
+            .as_ref()
+            .and_then(|system| self.system_data.get_mut(system))
+        else {
+            return;
+        };
+        for sig in sigs.iter_mut().filter(|sig| ids.contains(&sig.identifier)) {
+            sig.ignored = !sig.ignored;
+        }
+        self.touch_current();
+        self.clamp_data_index();
+        self.reconcile_selection();
+    }
 
-        assert_eq!(app.system_data.get("Thera").unwrap().len(), 1);
-        let sig = app.system_data.get("Thera").unwrap().get(0).unwrap();
-        assert_eq!(sig.identifier.id, "ABC".to_owned());
-        assert_eq!(sig.identifier.number, "123");
-        match sig.signature_type {
-            crate::eve_data::SignatureType::Relic(ref name) => {
-                assert_eq!(name, &Some("Foobar".to_owned()));
-            }
-            _ => {
-                panic!("Should be a relic sig");
-            }
+    /// Suggest which probe scanner filter to use next in the current system.
+    pub fn probe_filter_hint(&self) -> &'static str {
+        let signatures: Vec<_> = self
+            .system_signatures()
+            .into_iter()
+            .filter(|sig| !sig.is_placeholder() && !sig.anomaly)
+            .collect();
+        if signatures.is_empty() {
+            "No signatures yet: paste from the Cosmic Signature filter"
+        } else if signatures.iter().any(|sig| sig.needs_scan()) {
+            "Unresolved signatures: keep probing with the Cosmic Signature filter"
+        } else {
+            "All signatures resolved: switch to the Cosmic Anomaly filter"
         }
     }
 
-    #[test]
-    fn test_app_merge_in_add_name() {
-        let mut app = App::new();
-        app.current_system = Some("Thera".to_owned());
-        app.system_data.insert(
-            "Thera".to_owned(),
-            vec![Signature::new(
-                "ABC",
-                "123",
-                crate::eve_data::SignatureType::Relic(None),
-            )],
-        );
+    /// The signature currently highlighted in the list, if any.
+    pub fn selected_signature(&self) -> Option<&Signature> {
+        self.system_signatures().get(self.data_index).copied()
+    }
 
-        app.merge_in(&[ClipboardItem::new("ABC-123", "Relic", "Foobar")]);
+    /// Index of the next wormhole signature in the current system after `from`, wrapping around.
+    pub fn next_wormhole(&self, from: usize) -> Option<usize> {
+        let signatures = self.system_signatures();
+        let count = signatures.len();
+        (1..=count)
+            .map(|offset| (from + offset) % count)
+            .find(|&index| matches!(signatures[index].signature_type, SignatureType::Wormhole(_)))
+    }
 
-        assert_eq!(app.system_data.get("Thera").unwrap().len(), 1);
-        let sig = app.system_data.get("Thera").unwrap().get(0).unwrap();
-        assert_eq!(sig.identifier.id, "ABC".to_owned());
-        assert_eq!(sig.identifier.number, "123");
-        match sig.signature_type {
-            crate::eve_data::SignatureType::Relic(ref name) => {
-                assert_eq!(name, &Some("Foobar".to_owned()));
+    /// Record the origin type of the selected signature, if it's a K162.
+    pub fn resolve_selected_origin(&mut self, origin_type: &str) -> bool {
+        let (Some(current_system), Some(index)) =
+            (self.current_system.as_ref(), self.selected_data_index())
+        else {
+            return false;
+        };
+        let sig = &mut self.system_data.get_mut(current_system).unwrap()[index];
+        let resolved = match &mut sig.signature_type {
+            SignatureType::Wormhole(wh) => wh.resolve_origin(origin_type),
+            _ => false,
+        };
+        if resolved {
+            self.touch_current();
+        }
+        resolved
+    }
+
+    /// Log a jump of the mass through the highlighted wormhole, or clear its log with `None`.
+    ///
+    /// Returns the mass logged through the hole so far, or `None` if it isn't a wormhole.
+    pub fn log_selected_mass(&mut self, mass: Option<u64>) -> Option<u64> {
+        let current_system = self.current_system.clone()?;
+        let index = self.selected_data_index()?;
+        let sig = &mut self.system_data.get_mut(&current_system)?[index];
+        let SignatureType::Wormhole(wh) = &mut sig.signature_type else {
+            return None;
+        };
+        wh.mass_used = match mass {
+            Some(mass) => wh.mass_used + mass,
+            None => 0,
+        };
+        let used = wh.mass_used;
+        self.touch(&current_system);
+        Some(used)
+    }
+
+    /// Move the highlighted signature on to the next type, for ones identified before they're scanned.
+    ///
+    /// Goes Unknown, Combat, Ore, Gas, Data, Relic, Wormhole and back to Unknown.
+    /// Signatures with a site name or wormhole details are left alone. Returns the new kind.
+    pub fn cycle_selected_type(&mut self) -> Option<&'static str> {
+        let current_system = self.current_system.clone()?;
+        let index = self.selected_data_index()?;
+        let new_wormhole = self.new_wormhole.clone();
+        let sig = &mut self.system_data.get_mut(&current_system)?[index];
+        let next = match &sig.signature_type {
+            SignatureType::Unknown => SignatureType::Combat(None),
+            SignatureType::Combat(None) => SignatureType::Ore(None),
+            SignatureType::Ore(None) => SignatureType::Gas(None),
+            SignatureType::Gas(None) => SignatureType::Data(None),
+            SignatureType::Data(None) => SignatureType::Relic(None),
+            SignatureType::Relic(None) => SignatureType::Wormhole(new_wormhole),
+            SignatureType::Wormhole(wh) if wh.wh_type.is_none() && wh.destination.is_none() => {
+                SignatureType::Unknown
             }
-            _ => {
-                panic!("Should be a relic sig");
+            _ => return None,
+        };
+        let kind = next.kind();
+        sig.signature_type = next;
+        self.touch(&current_system);
+        Some(kind)
+    }
+
+    pub fn is_favorite(&self, system: &str, id: &SignatureId) -> bool {
+        self.favorites.iter().any(|(s, i)| s == system && i == id)
+    }
+
+    /// Star or un-star the signature in the system.
+    pub fn toggle_favorite(&mut self, system: &str, id: &SignatureId) {
+        if self.is_favorite(system, id) {
+            self.favorites.retain(|(s, i)| !(s == system && i == id));
+        } else {
+            self.favorites.push((system.to_owned(), id.clone()));
+        }
+        self.forget_undo();
+    }
+
+    pub fn is_pinned(&self, system: &str, id: &SignatureId) -> bool {
+        self.pinned
+            .get(system)
+            .is_some_and(|pinned| pinned.contains(id))
+    }
+
+    /// Pin the highlighted signature to the top of the list, or unpin it.
+    ///
+    /// The signature stays highlighted as it moves.
+    pub fn toggle_pin_selected(&mut self) {
+        let (Some(system), Some(id)) = (
+            self.current_system.clone(),
+            self.selected_signature().map(|sig| sig.identifier.clone()),
+        ) else {
+            return;
+        };
+        let pinned = self.pinned.entry(system.clone()).or_default();
+        if let Some(index) = pinned.iter().position(|i| i == &id) {
+            pinned.remove(index);
+            if pinned.is_empty() {
+                self.pinned.remove(&system);
             }
+        } else {
+            pinned.push(id.clone());
+        }
+        self.forget_undo();
+        if let Some(index) = self
+            .system_signatures()
+            .iter()
+            .position(|sig| sig.identifier == id)
+        {
+            self.data_index = index;
         }
     }
 
-    #[test]
-    fn test_app_merge_in_no_overwrite_name() {
-        let mut app = App::new();
-        app.current_system = Some("Thera".to_owned());
-        app.system_data.insert(
-            "Thera".to_owned(),
-            vec![Signature::new(
-                "ABC",
-                "123",
-                crate::eve_data::SignatureType::Relic(Some("Foobar".to_owned())),
-            )],
-        );
+    /// Switch to the system of the favorite and highlight its signature.
+    pub fn select_favorite(&mut self, index: usize) {
+        let Some((system, id)) = self.favorites.get(index).cloned() else {
+            return;
+        };
+        self.jump_to_signature(&system, &id);
+    }
 
-        app.merge_in(&[ClipboardItem::new("ABC-123", "Relic", "")]);
+    /// Switch to the system and highlight the signature in it.
+    pub fn jump_to_signature(&mut self, system: &str, id: &SignatureId) {
+        self.current_system = Some(system.to_owned());
+        self.data_index = self
+            .system_signatures()
+            .iter()
+            .position(|sig| &sig.identifier == id)
+            .unwrap_or(0);
+        self.selection.clear();
+    }
 
-        assert_eq!(app.system_data.get("Thera").unwrap().len(), 1);
-        let sig = app.system_data.get("Thera").unwrap().get(0).unwrap();
-        assert_eq!(sig.identifier.id, "ABC".to_owned());
-        assert_eq!(sig.identifier.number, "123");
-        match sig.signature_type {
-            crate::eve_data::SignatureType::Relic(ref name) => {
-                assert_eq!(name, &Some("Foobar".to_owned()));
-            }
-            _ => {
-                panic!("Should be a relic sig");
-            }
+    /// Systems in the chain around the current system, for the map.
+    ///
+    /// Stale branches are collapsed unless they've been expanded.
+    pub fn map_nodes(&self) -> Vec<chain::MapNode> {
+        let Some(current_system) = self.current_system.as_ref() else {
+            return Vec::new();
+        };
+        let nodes = chain::map_tree(&self.system_data, current_system);
+        if self.stale_after.is_none() {
+            return nodes;
         }
+        chain::collapse_stale(
+            nodes,
+            |system| self.is_stale(system),
+            &self.expanded_branches,
+        )
     }
 
-    #[test]
-    fn test_app_merge_in_no_wormhole_data_overwrite() {
-        let mut app = App::new();
-        app.current_system = Some("Thera".to_owned());
-        let wh = {
-            let mut wh = SignatureWormhole::default();
-            wh.life = WormholeLife::EndOfLife;
-            wh.wh_type = Some("A239".to_owned());
-            wh
+    /// Systems in the current chain's stale branches, collapsed or not.
+    pub fn stale_systems(&self) -> Vec<String> {
+        let Some(current_system) = self.current_system.as_ref() else {
+            return Vec::new();
         };
-        app.system_data.insert(
-            "Thera".to_owned(),
-            vec![Signature::new(
-                "ABC",
-                "123",
-                crate::eve_data::SignatureType::Wormhole(wh),
-            )],
-        );
+        if self.stale_after.is_none() {
+            return Vec::new();
+        }
+        let nodes = chain::map_tree(&self.system_data, current_system);
+        chain::collapse_stale(nodes, |system| self.is_stale(system), &[])
+            .into_iter()
+            .flat_map(|node| node.collapsed)
+            .collect()
+    }
 
-        app.merge_in(&[ClipboardItem::new("ABC-123", "Wormhole", "")]);
+    /// Expand a collapsed stale branch on the map.
+    pub fn expand_branch(&mut self, system: &str) {
+        if !self.expanded_branches.iter().any(|s| s == system) {
+            self.expanded_branches.push(system.to_owned());
+        }
+    }
+
+    /// When the system was last pasted into, edited, or visited.
+    ///
+    /// Falls back to its newest signature, for state saved before this was tracked.
+    pub fn last_active(&self, system: &str) -> Option<DateTime<Utc>> {
+        let newest = self
+            .system_data
+            .get(system)
+            .and_then(|sigs| sigs.iter().map(|sig| sig.created).max());
+        self.last_activity.get(system).copied().max(newest)
+    }
+
+    fn is_stale(&self, system: &str) -> bool {
+        let Some(stale_after) = self.stale_after else {
+            return false;
+        };
+        self.last_active(system)
+            .is_none_or(|at| Utc::now() - at > stale_after)
+    }
+
+    fn touch(&mut self, system: &str) {
+        self.last_activity.insert(system.to_owned(), Utc::now());
+        self.forget_undo();
+    }
+
+    /// Drop the saved moves and imports, since undoing them now would lose later changes.
+    fn forget_undo(&mut self) {
+        self.before_move = None;
+        self.before_import = None;
+    }
+
+    fn map_snapshot(&self) -> MapSnapshot {
+        MapSnapshot {
+            system_data: self.system_data.clone(),
+            activity: self.activity.clone(),
+            connections: self.connections.clone(),
+            favorites: self.favorites.clone(),
+            pinned: self.pinned.clone(),
+        }
+    }
+
+    fn restore_snapshot(&mut self, snapshot: MapSnapshot) {
+        self.system_data = snapshot.system_data;
+        self.activity = snapshot.activity;
+        self.connections = snapshot.connections;
+        self.favorites = snapshot.favorites;
+        self.pinned = snapshot.pinned;
+        self.clamp_data_index();
+        self.reconcile_selection();
+    }
+
+    fn touch_current(&mut self) {
+        if let Some(system) = self.current_system.clone() {
+            self.touch(&system);
+        }
+    }
+
+    /// Switch to the system and highlight its first unknown signature, if it has one.
+    pub fn jump_to_first_unknown(&mut self, system: &str) {
+        self.switch_system(system);
+        if let Some(index) = self
+            .system_signatures()
+            .iter()
+            .position(|sig| sig.signature_type == SignatureType::Unknown)
+        {
+            self.data_index = index;
+        }
+    }
+
+    /// System and index of a signature matching the query in any system.
+    ///
+    /// IDs match from the start, e.g. "ABC" or "abc-12", and are preferred to
+    /// names containing the query. The current system is checked first, then
+    /// the others by name.
+    pub fn find_signature_global(&self, query: &str) -> Option<(String, usize)> {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return None;
+        }
+        let mut systems: Vec<_> = self.system_data.keys().collect();
+        systems.sort_by_key(|system| (Some(*system) != self.current_system.as_ref(), *system));
+        let find = |matches: &dyn Fn(&Signature) -> bool| {
+            systems.iter().find_map(|system| {
+                self.system_data[*system]
+                    .iter()
+                    .position(|sig| !sig.is_placeholder() && matches(sig))
+                    .map(|index| ((*system).clone(), index))
+            })
+        };
+        let id_query = query.replace('-', "");
+        find(&|sig| {
+            format!("{}{}", sig.identifier.id, sig.identifier.number)
+                .to_lowercase()
+                .starts_with(&id_query)
+        })
+        .or_else(|| {
+            find(&|sig| {
+                sig.signature_type
+                    .name()
+                    .is_some_and(|name| name.to_lowercase().contains(&query))
+            })
+        })
+    }
+
+    /// Wormholes in other systems leading into the current one, as (system, signature) pairs.
+    pub fn inbound_connections(&self) -> Vec<(&str, &Signature)> {
+        let Some(current_system) = self.current_system.as_ref() else {
+            return Vec::new();
+        };
+        chain::inbound_edges(&self.system_data, current_system)
+            .into_iter()
+            .map(|edge| (edge.from, edge.signature))
+            .collect()
+    }
+
+    /// Add or remove the highlighted signature from the multi-selection.
+    pub fn toggle_selection(&mut self) {
+        let Some(id) = self.selected_signature().map(|sig| sig.identifier.clone()) else {
+            return;
+        };
+        if self.selection.contains(&id) {
+            self.selection.retain(|i| i != &id);
+        } else {
+            self.selection.push(id);
+        }
+    }
+
+    /// Drop selected signatures that are no longer in the current system.
+    pub fn reconcile_selection(&mut self) {
+        let ids: Vec<_> = self
+            .system_signatures()
+            .iter()
+            .map(|sig| sig.identifier.clone())
+            .collect();
+        self.selection.retain(|id| ids.contains(id));
+    }
+
+    /// Signatures a bulk action applies to: the multi-selection, or else the highlighted one.
+    pub fn action_targets(&self) -> Vec<SignatureId> {
+        if !self.selection.is_empty() {
+            return self.selection.clone();
+        }
+        self.selected_signature()
+            .map(|sig| vec![sig.identifier.clone()])
+            .unwrap_or_default()
+    }
+
+    /// Do a destructive action, or ask first if confirmations for it are on.
+    ///
+    /// Returns the signatures deleted if it was done straight away.
+    pub fn confirm_or_perform(&mut self, action: ConfirmAction) -> Option<Vec<Signature>> {
+        let confirm = match action {
+            ConfirmAction::DeleteSignatures(_) => self.confirmations.delete,
+            ConfirmAction::RemoveSystems(_) => self.confirmations.remove_systems,
+            ConfirmAction::AllEol { .. } => self.confirmations.mark_all_eol,
+            ConfirmAction::MatchPlaceholders(_) => true,
+        };
+        if confirm {
+            self.view = ViewMode::Confirm {
+                message: action.message(),
+                action,
+            };
+            return None;
+        }
+        Some(self.perform(action))
+    }
+
+    /// Do a destructive action, returning the signatures deleted.
+    pub fn perform(&mut self, action: ConfirmAction) -> Vec<Signature> {
+        match action {
+            ConfirmAction::DeleteSignatures(ids) => return self.remove_signatures(&ids),
+            ConfirmAction::RemoveSystems(systems) => {
+                for system in &systems {
+                    self.remove_system(system);
+                }
+            }
+            ConfirmAction::AllEol {
+                system,
+                restore: true,
+            } => self.restore_wormhole_lives(&system),
+            ConfirmAction::AllEol {
+                system,
+                restore: false,
+            } => {
+                let count = self.set_all_wormholes_eol(&system);
+                self.status_message = Some(format!("Marked {count} wormhole(s) EOL"));
+            }
+            ConfirmAction::MatchPlaceholders(matches) => {
+                for found in &matches {
+                    self.apply_placeholder_match(found);
+                }
+                self.reconcile_selection();
+            }
+        }
+        Vec::new()
+    }
+
+    /// Remove the signatures from the current system, returning those removed.
+    pub fn remove_signatures(&mut self, ids: &[SignatureId]) -> Vec<Signature> {
+        let Some(system) = self.current_system.clone() else {
+            return Vec::new();
+        };
+        let mut removed = Vec::new();
+        for id in ids {
+            let index = self
+                .system_data
+                .get(&system)
+                .and_then(|sigs| sigs.iter().position(|sig| &sig.identifier == id));
+            if let Some(sig) = index.and_then(|index| self.remove_signature(&system, index)) {
+                removed.push(sig);
+            }
+        }
+        self.reconcile_selection();
+        removed
+    }
+
+    /// Apply a change to each of the wormholes among the signatures in the current system.
+    pub fn update_wormholes(
+        &mut self,
+        ids: &[SignatureId],
+        update: impl Fn(&mut SignatureWormhole),
+    ) {
+        let Some(sigs) = self
+            .current_system
+            .as_ref()
+            .and_then(|system| self.system_data.get_mut(system))
+        else {
+            return;
+        };
+        for sig in sigs.iter_mut().filter(|sig| ids.contains(&sig.identifier)) {
+            if let SignatureType::Wormhole(wh) = &mut sig.signature_type {
+                update(wh);
+            }
+        }
+        self.touch_current();
+    }
+
+    /// Remove a signature from a system, along with any favorite pointing at it.
+    pub fn remove_signature(&mut self, system: &str, index: usize) -> Option<Signature> {
+        let sigs = self.system_data.get_mut(system)?;
+        if index >= sigs.len() {
+            return None;
+        }
+        let removed = sigs.remove(index);
+        self.record_activity(system, ActivityKind::Disappeared);
+        self.touch(system);
+        self.favorites
+            .retain(|(s, i)| !(s == system && i == &removed.identifier));
+        if let Some(pinned) = self.pinned.get_mut(system) {
+            pinned.retain(|i| i != &removed.identifier);
+        }
+        for connection in self.connections.iter_mut() {
+            connection
+                .ends
+                .retain(|(s, i)| !(s == system && i == &removed.identifier));
+        }
+        self.connections.retain(|c| !c.ends.is_empty());
+        self.clamp_data_index();
+        Some(removed)
+    }
+
+    /// Start tracking a system with no signatures yet.
+    pub fn add_system(&mut self, system: &str) {
+        self.system_data.entry(system.to_owned()).or_default();
+        self.touch(system);
+    }
+
+    /// Mark every wormhole in the system EOL, remembering their lives so it can be undone.
+    ///
+    /// Other signatures are left alone. Returns how many wormholes there are.
+    pub fn set_all_wormholes_eol(&mut self, system: &str) -> usize {
+        let Some(signatures) = self.system_data.get_mut(system) else {
+            return 0;
+        };
+        let mut lives = Vec::new();
+        for sig in signatures.iter_mut() {
+            if let SignatureType::Wormhole(wh) = &mut sig.signature_type {
+                lives.push((sig.identifier.clone(), wh.life.clone()));
+                wh.life = WormholeLife::EndOfLife;
+            }
+        }
+        let count = lives.len();
+        self.lives_before_eol.insert(system.to_owned(), lives);
+        self.touch(system);
+        count
+    }
+
+    /// Whether the system's wormholes were all marked EOL and can be restored.
+    pub fn can_restore_lives(&self, system: &str) -> bool {
+        self.lives_before_eol.contains_key(system)
+    }
+
+    /// Put back the wormhole lives from before `set_all_wormholes_eol`.
+    pub fn restore_wormhole_lives(&mut self, system: &str) {
+        let Some(lives) = self.lives_before_eol.remove(system) else {
+            return;
+        };
+        let Some(signatures) = self.system_data.get_mut(system) else {
+            return;
+        };
+        for (id, life) in lives {
+            if let Some(SignatureType::Wormhole(wh)) = signatures
+                .iter_mut()
+                .find(|sig| sig.identifier == id)
+                .map(|sig| &mut sig.signature_type)
+            {
+                wh.life = life;
+            }
+        }
+        self.touch(system);
+    }
+
+    /// Remove a system and its signatures, along with their favorites and connections.
+    ///
+    /// Wormholes elsewhere leading into the system lose their destination.
+    pub fn remove_system(&mut self, system: &str) {
+        while self.remove_signature(system, 0).is_some() {}
+        self.system_data.remove(system);
+        self.activity.remove(system);
+        self.last_activity.remove(system);
+        self.last_scanned.remove(system);
+        self.system_notes.remove(system);
+        self.pinned.remove(system);
+        self.expanded_branches.retain(|s| s != system);
+        for sig in self.system_data.values_mut().flatten() {
+            if let SignatureType::Wormhole(wh) = &mut sig.signature_type {
+                if wh.destination.as_deref() == Some(system) {
+                    wh.destination = None;
+                }
+            }
+        }
+        if self.current_system.as_deref() == Some(system) {
+            self.current_system = None;
+            self.data_index = 0;
+        }
+    }
+
+    /// Systems with no signatures that nothing points at, so are safe to remove.
+    ///
+    /// The current and home systems are kept, as are systems that are part of
+    /// a connection or that a wormhole elsewhere leads to.
+    pub fn orphaned_systems(&self) -> Vec<String> {
+        let edges = chain::chain_edges(&self.system_data);
+        let mut orphans: Vec<_> = self
+            .system_data
+            .iter()
+            .filter(|(_, sigs)| sigs.is_empty())
+            .map(|(system, _)| system)
+            .filter(|system| {
+                self.current_system.as_ref() != Some(system)
+                    && self.home_system.as_ref() != Some(system)
+            })
+            .filter(|system| {
+                !self
+                    .connections
+                    .iter()
+                    .any(|c| c.ends.iter().any(|(s, _)| s == *system))
+            })
+            .filter(|system| !edges.iter().any(|edge| edge.to == system.as_str()))
+            .cloned()
+            .collect();
+        orphans.sort();
+        orphans
+    }
+
+    /// Remove all orphaned systems, returning how many were removed.
+    pub fn sweep_orphaned_systems(&mut self) -> usize {
+        let orphans = self.orphaned_systems();
+        for system in &orphans {
+            self.remove_system(system);
+        }
+        orphans.len()
+    }
+
+    fn record_activity(&mut self, system: &str, kind: ActivityKind) {
+        let events = self.activity.entry(system.to_owned()).or_default();
+        activity::record(events, kind, Utc::now());
+    }
+
+    /// Move a signature to another system, along with its favorite, pin and connection.
+    ///
+    /// If the target already has a signature with the same ID, the two are
+    /// merged the same way a paste would be. Returns false if there's no such
+    /// signature or it's already in the target.
+    pub fn move_signature(&mut self, from: &str, index: usize, to: &str) -> bool {
+        if from == to
+            || self
+                .system_data
+                .get(from)
+                .is_none_or(|sigs| index >= sigs.len())
+        {
+            return false;
+        }
+        let sig = self.system_data.get_mut(from).unwrap().remove(index);
+        let id = sig.identifier.clone();
+        let target = self.system_data.entry(to.to_owned()).or_default();
+        match target.iter_mut().find(|existing| existing.identifier == id) {
+            Some(existing) => {
+                merge_signature_type(&mut existing.signature_type, sig.signature_type)
+            }
+            None => target.push(sig),
+        }
+        let ends = self
+            .favorites
+            .iter_mut()
+            .chain(self.connections.iter_mut().flat_map(|c| c.ends.iter_mut()));
+        for (system, end_id) in ends {
+            if system == from && end_id == &id {
+                *system = to.to_owned();
+            }
+        }
+        let mut seen = Vec::new();
+        self.favorites.retain(|favorite| {
+            let first = !seen.contains(favorite);
+            seen.push(favorite.clone());
+            first
+        });
+        if let Some(pinned) = self.pinned.get_mut(from) {
+            if let Some(position) = pinned.iter().position(|i| i == &id) {
+                pinned.remove(position);
+                if pinned.is_empty() {
+                    self.pinned.remove(from);
+                }
+                let target = self.pinned.entry(to.to_owned()).or_default();
+                if !target.contains(&id) {
+                    target.push(id);
+                }
+            }
+        }
+        self.record_activity(from, ActivityKind::Disappeared);
+        self.record_activity(to, ActivityKind::Appeared);
+        self.touch(from);
+        self.touch(to);
+        self.clamp_data_index();
+        self.reconcile_selection();
+        true
+    }
+
+    /// Move signatures in the current system to another one, returning how many were moved.
+    ///
+    /// The move can be reverted with `undo_move` until the map next changes.
+    pub fn move_signatures(&mut self, ids: &[SignatureId], to: &str) -> usize {
+        let Some(from) = self.current_system.clone() else {
+            return 0;
+        };
+        let before = self.map_snapshot();
+        let mut moved = 0;
+        for id in ids {
+            let index = self
+                .system_data
+                .get(&from)
+                .and_then(|sigs| sigs.iter().position(|sig| &sig.identifier == id));
+            if index.is_some_and(|index| self.move_signature(&from, index, to)) {
+                moved += 1;
+            }
+        }
+        if moved > 0 {
+            self.before_move = Some(before);
+        }
+        moved
+    }
+
+    pub fn can_undo_move(&self) -> bool {
+        self.before_move.is_some()
+    }
+
+    /// Put the map back as it was before the last signature move.
+    ///
+    /// Returns whether there was a move to undo.
+    pub fn undo_move(&mut self) -> bool {
+        let Some(before) = self.before_move.take() else {
+            return false;
+        };
+        self.restore_snapshot(before);
+        true
+    }
+
+    /// Copy a signature to another system.
+    ///
+    /// Returns false if there's no such signature or the target already has one with its ID.
+    pub fn copy_signature(&mut self, from: &str, index: usize, to: &str) -> bool {
+        let Some(sig) = self.system_data.get(from).and_then(|sigs| sigs.get(index)) else {
+            return false;
+        };
+        if self.has_signature(to, &sig.identifier) {
+            return false;
+        }
+        let sig = sig.clone();
+        self.system_data.entry(to.to_owned()).or_default().push(sig);
+        self.touch(to);
+        true
+    }
+
+    /// Whether the system has a signature with the ID.
+    pub fn has_signature(&self, system: &str, id: &SignatureId) -> bool {
+        self.system_data
+            .get(system)
+            .is_some_and(|sigs| sigs.iter().any(|sig| &sig.identifier == id))
+    }
+
+    /// Switch the view to another system.
+    ///
+    /// If it's an unvisited w-space system and auto static placeholders are
+    /// on, placeholders are added for its statics.
+    pub fn switch_system(&mut self, system: &str) {
+        if self.auto_static_placeholders && !self.system_data.contains_key(system) {
+            if let Some(data) = ALL_SYSTEMS.get(system).filter(|data| data.class.is_some()) {
+                for wh_type in &data.statics {
+                    self.add_static_placeholder(system, wh_type);
+                }
+            }
+        }
+        self.current_system = Some(system.to_owned());
+        self.touch(system);
+        self.data_index = 0;
+        self.selection.clear();
+    }
+
+    /// Remove signatures that were first recorded longer ago than the max age.
+    ///
+    /// Returns the number of signatures removed.
+    pub fn prune_older_than(&mut self, max_age: Duration) -> usize {
+        let cutoff = Utc::now() - max_age;
+        let mut total = 0;
+        for (system, signatures) in self.system_data.iter_mut() {
+            let before = signatures.len();
+            signatures.retain(|sig| sig.created >= cutoff);
+            let pruned = before - signatures.len();
+            if pruned > 0 {
+                info!("Pruned {pruned} old signatures from {system}");
+                total += pruned;
+            }
+        }
+        let system_data = &self.system_data;
+        let exists = |system: &String, id: &SignatureId| {
+            system_data
+                .get(system)
+                .is_some_and(|sigs| sigs.iter().any(|sig| &sig.identifier == id))
+        };
+        self.favorites.retain(|(system, id)| exists(system, id));
+        for (system, pinned) in self.pinned.iter_mut() {
+            pinned.retain(|id| exists(system, id));
+        }
+        self.pinned.retain(|_, pinned| !pinned.is_empty());
+        for connection in self.connections.iter_mut() {
+            connection.ends.retain(|(system, id)| exists(system, id));
+        }
+        self.connections.retain(|c| !c.ends.is_empty());
+        self.data_index = 0;
+        if total > 0 {
+            self.forget_undo();
+        }
+        total
+    }
+
+    /// The connection the signature in the system is part of, if it's been linked.
+    pub fn connection(&self, system: &str, id: &SignatureId) -> Option<&Connection> {
+        self.connections.iter().find(|c| c.has_end(system, id))
+    }
+
+    /// Direction of the wormhole signature in the system, if it's a wormhole.
+    ///
+    /// It's bidirectional once linked with, or pairable to, its other side.
+    pub fn wormhole_direction(&self, system: &str, sig: &Signature) -> Option<WormholeDirection> {
+        let SignatureType::Wormhole(wh) = &sig.signature_type else {
+            return None;
+        };
+        let linked = self
+            .connection(system, &sig.identifier)
+            .is_some_and(|c| c.ends.len() > 1)
+            || chain::find_pair(&self.system_data, system, &sig.identifier).is_some();
+        Some(wh.direction(linked))
+    }
+
+    /// Link two wormhole signatures as the same physical hole.
+    ///
+    /// If either is already part of a connection, the other joins it.
+    pub fn link_wormholes(&mut self, a: (String, SignatureId), b: (String, SignatureId)) {
+        let existing = self
+            .connections
+            .iter()
+            .position(|c| c.has_end(&a.0, &a.1) || c.has_end(&b.0, &b.1));
+        let connection = match existing {
+            Some(index) => &mut self.connections[index],
+            None => {
+                self.connections.push(Connection::default());
+                self.connections.last_mut().unwrap()
+            }
+        };
+        let systems = [a.0.clone(), b.0.clone()];
+        for end in [a, b] {
+            if !connection.has_end(&end.0, &end.1) {
+                connection.ends.push(end);
+            }
+        }
+        for system in systems {
+            self.touch(&system);
+        }
+    }
+
+    /// Set the note on the connection for the signature, visible from both sides.
+    ///
+    /// The signature is first linked with its pair in the destination system,
+    /// if there is one. An empty note clears it.
+    pub fn set_connection_note(&mut self, system: &str, id: &SignatureId, note: &str) {
+        let end = (system.to_owned(), id.clone());
+        match chain::find_pair(&self.system_data, system, id) {
+            Some(pair) => self.link_wormholes(end, pair),
+            None => {
+                if self.connection(system, id).is_none() {
+                    self.connections.push(Connection {
+                        ends: vec![end],
+                        note: None,
+                    });
+                }
+            }
+        }
+        let note = note.trim();
+        if let Some(connection) = self.connections.iter_mut().find(|c| c.has_end(system, id)) {
+            connection.note = if note.is_empty() {
+                None
+            } else {
+                Some(note.to_owned())
+            };
+        }
+        self.touch(system);
+    }
+
+    /// The system's scratchpad note, if it has one.
+    pub fn system_note(&self, system: &str) -> Option<&str> {
+        self.system_notes.get(system).map(String::as_str)
+    }
+
+    /// Set the system's scratchpad note. An empty note clears it.
+    pub fn set_system_note(&mut self, system: &str, note: &str) {
+        let note = note.trim();
+        if note.is_empty() {
+            self.system_notes.remove(system);
+        } else {
+            self.system_notes.insert(system.to_owned(), note.to_owned());
+        }
+        self.touch(system);
+    }
+
+    /// Fill in the connection kind of typed wormholes in the system that don't have one.
+    pub fn suggest_connection_kinds(&mut self, system: &str) {
+        let statics = ALL_SYSTEMS
+            .get(system)
+            .map(|data| data.statics.clone())
+            .unwrap_or_default();
+        let Some(signatures) = self.system_data.get_mut(system) else {
+            return;
+        };
+        for sig in signatures {
+            if let SignatureType::Wormhole(wh) = &mut sig.signature_type {
+                if wh.connection_kind == ConnectionKind::Unknown {
+                    if let Some(wh_type) = &wh.wh_type {
+                        wh.connection_kind = ConnectionKind::suggest(wh_type, &statics);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Add a wormhole found before it's been scanned, with the next unused placeholder ID.
+    ///
+    /// The new wormhole is highlighted so its type can be filled in; a later
+    /// paste gives its details to the real signature.
+    pub fn quick_add_wormhole(&mut self) -> Option<SignatureId> {
+        let system = self.current_system.clone()?;
+        let signatures = self.system_data.entry(system.clone()).or_default();
+        let number = next_placeholder_number(signatures);
+        let id = SignatureId::new(PLACEHOLDER_ID, &number);
+        signatures.push(Signature {
+            provenance: Some(Provenance::Manual),
+            ..Signature::new(
+                PLACEHOLDER_ID,
+                &number,
+                SignatureType::Wormhole(self.new_wormhole.clone()),
+            )
+        });
+        self.touch(&system);
+        if let Some(index) = self
+            .system_signatures()
+            .iter()
+            .position(|sig| sig.identifier == id)
+        {
+            self.data_index = index;
+        }
+        Some(id)
+    }
+
+    /// Set the type of the highlighted wormhole, guessing whether it's a static.
+    ///
+    /// Returns false if the type isn't a known wormhole type.
+    pub fn set_selected_wormhole_type(&mut self, wh_type: &str) -> bool {
+        let wh_type = wh_type.trim().to_uppercase();
+        if wh_type != "K162" && !WORMHOLE_TYPES.contains_key(&wh_type) {
+            return false;
+        }
+        let (Some(current_system), Some(index)) =
+            (self.current_system.clone(), self.selected_data_index())
+        else {
+            return false;
+        };
+        let sig = &mut self.system_data.get_mut(&current_system).unwrap()[index];
+        let SignatureType::Wormhole(wh) = &mut sig.signature_type else {
+            return false;
+        };
+        wh.wh_type = Some(wh_type);
+        wh.connection_kind = ConnectionKind::Unknown;
+        self.suggest_connection_kinds(&current_system);
+        self.touch(&current_system);
+        true
+    }
+
+    /// Fill in the selected wormhole's type with the static leading to a class, e.g. "C3".
+    ///
+    /// Only w-space statics are checked, and only a unique match is filled in.
+    /// `None` when the input isn't a destination class.
+    pub fn fill_selected_static(&mut self, class: &str) -> Option<StaticMatch> {
+        let class = destination_class(class)?;
+        let statics = self
+            .current_system
+            .as_ref()
+            .and_then(|system| ALL_SYSTEMS.get(system))
+            .filter(|data| data.class.is_some())
+            .map(|data| data.statics.as_slice())
+            .unwrap_or_default();
+        let found = static_for_class(statics, &class);
+        if let StaticMatch::Unique(wh_type) = &found {
+            if !self.set_selected_wormhole_type(wh_type) {
+                return None;
+            }
+        }
+        Some(found)
+    }
+
+    /// Add an unscanned stand-in for a static that's respawned after being closed.
+    pub fn add_static_placeholder(&mut self, system: &str, wh_type: &str) {
+        let wormhole = SignatureWormhole {
+            wh_type: Some(wh_type.to_owned()),
+            connection_kind: ConnectionKind::Static,
+            ..self.new_wormhole.clone()
+        };
+        let signatures = self.system_data.entry(system.to_owned()).or_default();
+        let number = next_placeholder_number(signatures);
+        signatures.push(Signature {
+            provenance: Some(Provenance::Manual),
+            ..Signature::new(PLACEHOLDER_ID, &number, SignatureType::Wormhole(wormhole))
+        });
+        self.touch(system);
+    }
+
+    /// Drop typed placeholders in the system once a scanned wormhole of their type exists.
+    fn drop_scanned_placeholders(&mut self, system: &str) {
+        let Some(sigs) = self.system_data.get_mut(system) else {
+            return;
+        };
+        let scanned: Vec<_> = sigs
+            .iter()
+            .filter(|sig| !sig.is_placeholder())
+            .filter_map(|sig| match &sig.signature_type {
+                SignatureType::Wormhole(wh) => wh.wh_type.clone(),
+                _ => None,
+            })
+            .collect();
+        sigs.retain(|sig| {
+            !sig.is_placeholder()
+                || !matches!(
+                    &sig.signature_type,
+                    SignatureType::Wormhole(wh) if wh.wh_type.as_ref().is_some_and(|t| scanned.contains(t))
+                )
+        });
+    }
+
+    /// Typed placeholders in the system that pasted signatures look like.
+    ///
+    /// Each is paired with the next untyped wormhole among the pasted signatures.
+    pub fn placeholder_matches(
+        &self,
+        system: &str,
+        pasted: &[SignatureId],
+    ) -> Vec<PlaceholderMatch> {
+        let Some(sigs) = self.system_data.get(system) else {
+            return Vec::new();
+        };
+        let mut untyped = sigs.iter().filter(|sig| {
+            !sig.is_placeholder()
+                && pasted.contains(&sig.identifier)
+                && matches!(&sig.signature_type, SignatureType::Wormhole(wh) if wh.wh_type.is_none())
+        });
+        sigs.iter()
+            .filter(|sig| sig.is_placeholder())
+            .filter_map(|sig| match &sig.signature_type {
+                SignatureType::Wormhole(wh) => Some((sig, wh.wh_type.clone()?)),
+                _ => None,
+            })
+            .map_while(|(placeholder, wh_type)| {
+                Some(PlaceholderMatch {
+                    system: system.to_owned(),
+                    placeholder: placeholder.identifier.clone(),
+                    wh_type,
+                    scanned: untyped.next()?.identifier.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Ask whether to apply the placeholder matches, along with any already being asked about.
+    ///
+    /// Nothing is asked while another popup is open; the matches are offered
+    /// again the next time the signatures are pasted.
+    fn offer_placeholder_matches(&mut self, matches: Vec<PlaceholderMatch>) {
+        if matches.is_empty() {
+            return;
+        }
+        let matches = match &self.view {
+            ViewMode::Normal => matches,
+            ViewMode::Confirm {
+                action: ConfirmAction::MatchPlaceholders(earlier),
+                ..
+            } => earlier.iter().cloned().chain(matches).collect(),
+            _ => return,
+        };
+        let action = ConfirmAction::MatchPlaceholders(matches);
+        self.view = ViewMode::Confirm {
+            message: action.message(),
+            action,
+        };
+    }
+
+    /// Give the placeholder's type, kind and destination to the scanned wormhole and drop the placeholder.
+    fn apply_placeholder_match(&mut self, found: &PlaceholderMatch) {
+        let Some(sigs) = self.system_data.get_mut(&found.system) else {
+            return;
+        };
+        let Some(index) = sigs
+            .iter()
+            .position(|sig| sig.identifier == found.placeholder)
+        else {
+            return;
+        };
+        let SignatureType::Wormhole(placeholder) = sigs[index].signature_type.clone() else {
+            return;
+        };
+        let scanned = sigs.iter_mut().find(|sig| sig.identifier == found.scanned);
+        let Some(SignatureType::Wormhole(wh)) = scanned.map(|sig| &mut sig.signature_type) else {
+            return;
+        };
+        if wh.wh_type.is_some() {
+            return;
+        }
+        wh.wh_type = placeholder.wh_type;
+        wh.connection_kind = placeholder.connection_kind;
+        if wh.destination.is_none() {
+            wh.destination = placeholder.destination;
+        }
+        sigs.remove(index);
+        self.touch(&found.system);
+        self.clamp_data_index();
+    }
+
+    /// Systems to pass through from the current system to the best k-space exit.
+    pub fn exit_route(&self, safe_only: bool) -> Option<Vec<String>> {
+        let current_system = self.current_system.as_ref()?;
+        chain::best_exit(&self.system_data, current_system, safe_only)
+    }
+
+    /// Render the whole chain as a Graphviz DOT graph.
+    pub fn export_dot(&self) -> String {
+        chain::to_dot(&self.system_data)
+    }
+
+    /// Apply pasted clipboard text: a Wanderer export, or scanner results for the current system.
+    ///
+    /// Pastes are only processed in the normal view; while a modal is open
+    /// they're ignored and `None` is returned. Otherwise returns any alerts
+    /// raised by the change.
+    pub fn paste(&mut self, text: &str) -> Option<Vec<Alert>> {
+        self.paste_as(text, PasteMode::Partial)
+    }
+
+    /// Signatures in the current system that a scanner paste doesn't include.
+    ///
+    /// Placeholders aren't counted since they were never on the scanner.
+    /// Always empty for Wanderer exports and pastes grouped by system.
+    pub fn missing_from_paste(&self, text: &str) -> Vec<SignatureId> {
+        if is_wanderer_export(text) || is_grouped_paste(text) {
+            return Vec::new();
+        }
+        let pasted: Vec<_> = parse_paste(text).into_iter().map(|item| item.id).collect();
+        if pasted.is_empty() {
+            return Vec::new();
+        }
+        let Some(signatures) = self
+            .current_system
+            .as_ref()
+            .and_then(|system| self.system_data.get(system))
+        else {
+            return Vec::new();
+        };
+        signatures
+            .iter()
+            .filter(|sig| !sig.is_placeholder() && !pasted.contains(&sig.identifier.to_string()))
+            .map(|sig| sig.identifier.clone())
+            .collect()
+    }
+
+    /// Systems a paste grouped under system name lines would go into, with how many results each.
+    ///
+    /// Results before the first system name go into the current system, or
+    /// `None` if there isn't one. Empty for pastes that aren't grouped.
+    pub fn paste_breakdown(&self, text: &str) -> Vec<(Option<String>, usize)> {
+        if !is_grouped_paste(text) {
+            return Vec::new();
+        }
+        parse_grouped_paste(text)
+            .into_iter()
+            .map(|(system, items)| (system.or_else(|| self.current_system.clone()), items.len()))
+            .collect()
+    }
+
+    /// Apply pasted clipboard text, removing signatures missing from it if it's a full scan.
+    pub fn paste_as(&mut self, text: &str, mode: PasteMode) -> Option<Vec<Alert>> {
+        if self.view != ViewMode::Normal {
+            debug!("Ignoring paste while a modal is open");
+            return None;
+        }
+        if self.read_only {
+            self.status_message = Some("Read-only: pasting is disabled".to_owned());
+            return None;
+        }
+
+        if is_wanderer_export(text) {
+            debug!("Importing Wanderer export from clipboard");
+            match parse_wanderer_export(text) {
+                Ok(imported) => self.import_systems(imported),
+                Err(e) => {
+                    error!("Could not parse Wanderer export: {e}");
+                    self.status_message = Some("Could not parse Wanderer export".to_owned());
+                }
+            }
+            return Some(Vec::new());
+        }
+
+        if is_grouped_paste(text) {
+            debug!("Importing signatures grouped by system from clipboard");
+            let before: Vec<_> = self.system_signatures().into_iter().cloned().collect();
+            let groups = parse_grouped_paste(text);
+            let mut added = Vec::new();
+            for (system, items) in &groups {
+                match system.as_ref().or(self.current_system.as_ref()).cloned() {
+                    Some(system) => {
+                        let new_ids = self.merge_in_system(&system, items);
+                        if self.current_system.as_ref() == Some(&system) {
+                            added.extend(new_ids);
+                        }
+                    }
+                    None => {
+                        debug!("Dropping signatures with no system to go in");
+                        self.status_message = Some("Select a system first".to_owned());
+                    }
+                }
+            }
+            if self.status_message.is_none() {
+                self.status_message = Some(format!(
+                    "Imported signatures for {} system(s)",
+                    groups.len()
+                ));
+            }
+            self.reconcile_selection();
+            if self.focus_on_paste {
+                self.focus_new_signatures(&added);
+            }
+            return Some(self.paste_alerts(&before));
+        }
+
+        debug!("Parsing content of clipboard");
+        let missing = match mode {
+            PasteMode::Full => self.missing_from_paste(text),
+            PasteMode::Partial => Vec::new(),
+        };
+        let results = parse_paste(text);
+        debug!("Got {} results from clipboard", results.len());
+        let before: Vec<_> = self.system_signatures().into_iter().cloned().collect();
+        let added = match self.merge_in(&results) {
+            Ok(added) => added,
+            Err(e) => {
+                debug!("Could not merge paste: {e}");
+                self.status_message = Some("Select a system first".to_owned());
+                return Some(Vec::new());
+            }
+        };
+
+        if !missing.is_empty() {
+            let removed = self.remove_signatures(&missing);
+            self.status_message = Some(format!(
+                "Removed {} signature(s) missing from the scan",
+                removed.len()
+            ));
+        }
+
+        let class = self
+            .current_system
+            .as_ref()
+            .and_then(|s| ALL_SYSTEMS.get(s))
+            .and_then(|d| d.class);
+        let wrong = wrong_class_sites(&results, class);
+        if !wrong.is_empty() {
+            let ids: Vec<_> = wrong.iter().map(|item| item.id.as_str()).collect();
+            self.status_message = Some(format!(
+                "Possible wrong system? {} can't spawn here",
+                ids.join(", ")
+            ));
+        }
+
+        self.reconcile_selection();
+        if self.focus_on_paste {
+            self.focus_new_signatures(&added);
+        }
+        Some(self.paste_alerts(&before))
+    }
+
+    /// Alerts for the current system's signatures having changed from `before`.
+    ///
+    /// Newly-scanned valuable sites are also called out in the status bar.
+    fn paste_alerts(&mut self, before: &[Signature]) -> Vec<Alert> {
+        let after: Vec<_> = self.system_signatures().into_iter().cloned().collect();
+        let mut alerts = detect_alerts(before, &after);
+        if let Some(tier) = self.valuable_site_tier {
+            let valuable = detect_valuable_sites(before, &after, tier);
+            let ids: Vec<_> = valuable
+                .iter()
+                .filter_map(|alert| match alert {
+                    Alert::ValuableSite(id) => Some(id.to_string()),
+                    _ => None,
+                })
+                .collect();
+            if !ids.is_empty() {
+                self.status_message = Some(format!("Valuable site: {}", ids.join(", ")));
+            }
+            alerts.extend(valuable);
+        }
+        alerts
+    }
+
+    /// Merge signatures imported from another mapper into the system data.
+    ///
+    /// Signatures that are already tracked are only replaced if they're still unknown.
+    pub fn import_systems(&mut self, imported: HashMap<String, Vec<Signature>>) {
+        for (system, signatures) in imported {
+            let existing = self.system_data.entry(system.clone()).or_default();
+            for signature in signatures {
+                match existing
+                    .iter_mut()
+                    .find(|sig| sig.identifier == signature.identifier)
+                {
+                    Some(sig) => {
+                        if sig.signature_type == SignatureType::Unknown {
+                            sig.signature_type = signature.signature_type;
+                        }
+                    }
+                    None => {
+                        existing.push(Signature {
+                            provenance: Some(Provenance::Imported),
+                            ..signature
+                        });
+                        let events = self.activity.entry(system.clone()).or_default();
+                        activity::record(events, ActivityKind::Appeared, Utc::now());
+                    }
+                }
+            }
+            self.suggest_connection_kinds(&system);
+            self.touch(&system);
+        }
+    }
+
+    /// Take the current signatures as already attributed, e.g. after loading.
+    pub fn reset_edit_baseline(&mut self) {
+        self.edit_baseline = self.system_data.clone();
+    }
+
+    /// Attribute signatures changed since the last call to the local author.
+    pub fn stamp_edits(&mut self) {
+        let author = self.author.clone();
+        self.stamp_edits_by(&author);
+    }
+
+    /// Attribute signatures added or changed since the last call to the author.
+    ///
+    /// Ones whose attribution has already been updated, such as from an
+    /// import, keep it.
+    pub fn stamp_edits_by(&mut self, author: &str) {
+        let now = Utc::now();
+        for (system, signatures) in self.system_data.iter_mut() {
+            let before = self.edit_baseline.get(system);
+            for sig in signatures.iter_mut() {
+                let old =
+                    before.and_then(|sigs| sigs.iter().find(|s| s.identifier == sig.identifier));
+                let changed = match old {
+                    Some(old) => {
+                        (old.signature_type != sig.signature_type || old.ignored != sig.ignored)
+                            && old.last_edit == sig.last_edit
+                    }
+                    None => sig.last_edit.is_none(),
+                };
+                if changed {
+                    sig.last_edit = Some(Attribution {
+                        by: author.to_owned(),
+                        at: now,
+                    });
+                }
+            }
+        }
+        self.reset_edit_baseline();
+    }
+
+    /// Signatures from a teammate's state file or JSON export.
+    pub fn read_export(path: &Path) -> Result<HashMap<String, Vec<Signature>>> {
+        Ok(migrate(read_saved(path)?)?.system_data)
+    }
+
+    /// Merge the rows of an imported table into the current system, like a teammate import.
+    ///
+    /// `None` if no system is selected.
+    pub fn import_table(&mut self, import: &CsvImport) -> Option<MergeSummary> {
+        let system = self.current_system.clone()?;
+        let (signatures, _) = to_signatures(&import.table, &import.columns);
+        Some(self.import_teammate(&HashMap::from([(system, signatures)])))
+    }
+
+    /// Merge a teammate's map into mine, applying everything that doesn't conflict.
+    ///
+    /// Conflicts are kept in `import_conflicts` to be resolved one at a time.
+    /// The whole import can be reverted with `undo_import` until the map next changes.
+    pub fn import_teammate(&mut self, theirs: &HashMap<String, Vec<Signature>>) -> MergeSummary {
+        let summary = merge::diff(&self.system_data, theirs);
+        let before = self.map_snapshot();
+        for (system, signature) in &summary.added {
+            self.system_data
+                .entry(system.clone())
+                .or_default()
+                .push(Signature {
+                    provenance: Some(Provenance::Imported),
+                    ..signature.clone()
+                });
+            let events = self.activity.entry(system.clone()).or_default();
+            activity::record(events, ActivityKind::Appeared, Utc::now());
+        }
+        for (system, merged) in &summary.filled {
+            self.replace_signature_type(system, merged);
+        }
+        let mut systems: Vec<_> = summary
+            .added
+            .iter()
+            .chain(&summary.filled)
+            .map(|(system, _)| system.clone())
+            .collect();
+        systems.dedup();
+        for system in systems {
+            self.suggest_connection_kinds(&system);
+            self.touch(&system);
+        }
+        self.import_conflicts = summary.conflicts.clone();
+        self.before_import = Some(before);
+        summary
+    }
+
+    fn replace_signature_type(&mut self, system: &str, signature: &Signature) {
+        if let Some(sig) = self.system_data.get_mut(system).and_then(|sigs| {
+            sigs.iter_mut()
+                .find(|s| s.identifier == signature.identifier)
+        }) {
+            sig.signature_type = signature.signature_type.clone();
+            if signature.last_edit.is_some() {
+                sig.last_edit = signature.last_edit.clone();
+            }
+            self.touch(system);
+        }
+    }
+
+    /// Settle a conflict from the last import, keeping my details or taking theirs.
+    ///
+    /// The import can still be undone afterwards, conflicts and all.
+    pub fn resolve_conflict(&mut self, index: usize, resolution: Resolution) {
+        if index >= self.import_conflicts.len() {
+            return;
+        }
+        let conflict = self.import_conflicts.remove(index);
+        if resolution == Resolution::Theirs {
+            let before_import = self.before_import.take();
+            self.replace_signature_type(&conflict.system, &conflict.theirs);
+            self.before_import = before_import;
+        }
+    }
+
+    /// Settle every remaining conflict from the last import in the system the same way.
+    pub fn resolve_system_conflicts(&mut self, system: &str, resolution: Resolution) {
+        while let Some(index) = self
+            .import_conflicts
+            .iter()
+            .position(|conflict| conflict.system == system)
+        {
+            self.resolve_conflict(index, resolution);
+        }
+    }
+
+    /// Put the map back as it was before the last teammate import.
+    ///
+    /// Returns whether there was an import to undo.
+    pub fn undo_import(&mut self) -> bool {
+        let Some(before) = self.before_import.take() else {
+            return false;
+        };
+        self.restore_snapshot(before);
+        self.import_conflicts.clear();
+        true
+    }
+
+    /// Merge data from a paste into the current system's data, returning the IDs of new signatures.
+    ///
+    /// Fails without changing anything if no system is selected.
+    pub fn merge_in(&mut self, new_data: &[ClipboardItem]) -> Result<Vec<SignatureId>> {
+        let current_system = self
+            .current_system
+            .clone()
+            .ok_or_else(|| anyhow!("No system selected"))?;
+        Ok(self.merge_in_system(&current_system, new_data))
+    }
+
+    /// Merge data from a paste into the system's existing data, returning the IDs of new signatures.
+    pub fn merge_in_system(
+        &mut self,
+        system: &str,
+        new_data: &[ClipboardItem],
+    ) -> Vec<SignatureId> {
+        let existing = self.system_data.entry(system.to_owned()).or_default();
+        let existing_ids: Vec<_> = existing.iter().map(|sig| sig.identifier.clone()).collect();
+        let mut added = Vec::new();
+
+        // update existing data
+        for signature in existing.iter_mut() {
+            let id: String = format!("{}", signature.identifier);
+            if let Some(check) = new_data.iter().find(|d| d.id == id) {
+                let (_new_id, mut new_type) = check.into();
+                if let SignatureType::Wormhole(_) = new_type {
+                    new_type = SignatureType::Wormhole(self.new_wormhole.clone());
+                }
+                if check.below_strength(self.min_scan_strength) {
+                    new_type = SignatureType::Unknown;
+                }
+                merge_signature_type(&mut signature.signature_type, new_type);
+                signature.anomaly = check.anomaly;
+                // seen in my own scan now, whoever added it
+                signature.provenance = Some(Provenance::Scanned);
+                if let Some(strength) = check.strength {
+                    signature.record_strength(strength);
+                }
+            }
+        }
+
+        // insert any new items
+        for clipboard_item in new_data {
+            let (new_sig_id, mut new_sig_type) = clipboard_item.into();
+            if let SignatureType::Wormhole(_) = new_sig_type {
+                new_sig_type = SignatureType::Wormhole(self.new_wormhole.clone());
+            }
+            if clipboard_item.below_strength(self.min_scan_strength) {
+                new_sig_type = SignatureType::Unknown;
+            }
+            if !existing_ids.contains(&new_sig_id) && !added.contains(&new_sig_id) {
+                added.push(new_sig_id.clone());
+                existing.push(Signature {
+                    identifier: new_sig_id,
+                    signature_type: new_sig_type,
+                    created: Utc::now(),
+                    ignored: false,
+                    scan_strengths: clipboard_item.strength.into_iter().collect(),
+                    anomaly: clipboard_item.anomaly,
+                    last_edit: None,
+                    provenance: Some(Provenance::Scanned),
+                });
+                let events = self.activity.entry(system.to_owned()).or_default();
+                activity::record(events, ActivityKind::Appeared, Utc::now());
+            }
+        }
+
+        let pasted: Vec<_> = new_data
+            .iter()
+            .map(|item| <(SignatureId, SignatureType)>::from(item).0)
+            .collect();
+        self.drop_scanned_placeholders(system);
+        let matches = self.placeholder_matches(system, &pasted);
+        self.offer_placeholder_matches(matches);
+        self.touch(system);
+        self.last_scanned.insert(system.to_owned(), Utc::now());
+        added
+    }
+
+    /// Highlight the first of the signatures in the current system's list, or
+    /// failing that the first unresolved one.
+    fn focus_new_signatures(&mut self, added: &[SignatureId]) {
+        let signatures = self.system_signatures();
+        let index = signatures
+            .iter()
+            .position(|sig| added.contains(&sig.identifier))
+            .or_else(|| signatures.iter().position(|sig| sig.needs_scan()));
+        if let Some(index) = index {
+            self.data_index = index;
+        }
+    }
+}
+
+/// Combine what's known about a signature with newly-seen data for it.
+fn merge_signature_type(existing: &mut SignatureType, new_type: SignatureType) {
+    match new_type {
+        SignatureType::Unknown => {
+            // no new information; leave it
+        }
+        SignatureType::Wormhole(_) => {
+            if !matches!(existing, SignatureType::Wormhole(_)) {
+                // existing signature is something else (likely unknown), so overwrite;
+                // if it's already a wormhole, the new data doesn't add anything
+                *existing = new_type;
+            }
+        }
+        _ => {
+            if new_type.has_name() {
+                *existing = resolve_merge(existing.clone(), new_type);
+            } else if existing.has_name() {
+                // existing has a name; do nothing
+            } else {
+                // neither has the name, so overwrite in case the classifier updated
+                *existing = new_type;
+            }
+        }
+    }
+}
+
+/// Pick between two named scans of a signature.
+///
+/// Names for the same kind of site that differ are likely one cut short, so
+/// the longer is kept; otherwise the new scan wins.
+fn resolve_merge(existing: SignatureType, new_type: SignatureType) -> SignatureType {
+    // cut-off names can end in an ellipsis, which doesn't count
+    let length = |name: &str| name.trim_end_matches(['…', '.']).trim_end().chars().count();
+    match (existing.name(), new_type.name()) {
+        (Some(old), Some(new))
+            if existing.kind() == new_type.kind() && length(old) > length(new) =>
+        {
+            existing
+        }
+        _ => new_type,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        migrate, resolve_merge, App, ConfirmAction, PasteMode, PlaceholderMatch, Resolution,
+        ViewMode, STATE_VERSION,
+    };
+    use crate::{
+        activity::ActivityKind,
+        alert::Alert,
+        chain::Connection,
+        config::NewWormholeState,
+        csv_import::{self, CsvImport},
+        eve_data::{
+            ClipboardItem, ConnectionKind, Provenance, Signature, SignatureId, SignatureType,
+            SignatureWormhole, StaticMatch, WormholeDirection, WormholeLife, WormholeMass,
+        },
+    };
+    use chrono::{Duration, Utc};
+
+    #[test]
+    fn test_app_stamp_edits() {
+        let mut app = App::new();
+        app.author = "Alice".to_owned();
+        app.current_system = Some("Thera".to_owned());
+        app.reset_edit_baseline();
+        let unchanged = app.system_data["J173213"][0].clone();
+
+        app.merge_in(&[ClipboardItem::new("ABC-123", "", "")])
+            .unwrap();
+        app.stamp_edits();
+        let sig = &app.system_data["Thera"][0];
+        assert_eq!(sig.last_edit.as_ref().unwrap().by, "Alice");
+        assert_eq!(app.system_data["J173213"][0], unchanged);
+
+        // a listener paste from someone else
+        app.merge_in(&[ClipboardItem::new("ABC-123", "Gas", "")])
+            .unwrap();
+        app.stamp_edits_by("Bob");
+        app.stamp_edits();
+        let sig = &app.system_data["Thera"][0];
+        assert_eq!(sig.last_edit.as_ref().unwrap().by, "Bob");
+
+        app.system_data.get_mut("Thera").unwrap()[0].ignored = true;
+        app.stamp_edits();
+        assert_eq!(
+            app.system_data["Thera"][0].last_edit.as_ref().unwrap().by,
+            "Alice"
+        );
+    }
+
+    #[test]
+    fn test_app_merge_in_min_scan_strength() {
+        let mut app = App::new();
+        app.current_system = Some("Thera".to_owned());
+        app.min_scan_strength = Some(75.0);
+
+        app.merge_in(&[
+            ClipboardItem::new("ABC-123", "Gas", "").with_strength(25.0),
+            ClipboardItem::new("DEF-456", "Relic", "").with_strength(100.0),
+        ])
+        .unwrap();
+        let types: Vec<_> = app.system_data["Thera"]
+            .iter()
+            .map(|sig| sig.signature_type.clone())
+            .collect();
+        assert_eq!(
+            types,
+            vec![SignatureType::Unknown, SignatureType::Relic(None)]
+        );
+
+        app.merge_in(&[ClipboardItem::new("ABC-123", "Data", "").with_strength(80.0)])
+            .unwrap();
+        assert_eq!(
+            app.system_data["Thera"][0].signature_type,
+            SignatureType::Data(None)
+        );
+        // a weaker rescan doesn't undo what was already resolved
+        app.merge_in(&[ClipboardItem::new("ABC-123", "Gas", "").with_strength(10.0)])
+            .unwrap();
+        assert_eq!(
+            app.system_data["Thera"][0].signature_type,
+            SignatureType::Data(None)
+        );
+    }
+
+    #[test]
+    fn test_app_paste_read_only() {
+        let mut app = App::new();
+        app.read_only = true;
+        let before = app.system_data.clone();
+
+        let alerts = app.paste("XYZ-999\tCosmic Signature\t\t\t0.0%\t4.99 AU");
+
+        assert_eq!(alerts, None);
+        assert_eq!(app.system_data, before);
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("Read-only: pasting is disabled")
+        );
+    }
+
+    #[test]
+    fn test_app_paste_anomalies() {
+        let mut app = App::new();
+        app.current_system = Some("Thera".to_owned());
+
+        app.paste(include_str!("../tests/fixtures/anomaly_paste.txt"));
+
+        let sigs = &app.system_data["Thera"];
+        let needs_scan: Vec<_> = sigs
+            .iter()
+            .filter(|sig| sig.needs_scan())
+            .map(|sig| sig.identifier.to_string())
+            .collect();
+        assert_eq!(needs_scan, vec!["JKL-012"]);
+        assert_eq!(
+            sigs[1].signature_type,
+            SignatureType::Combat(Some("Sansha Hideaway".to_owned()))
+        );
+        assert!(sigs[1].anomaly);
+        assert_eq!(sigs[1].scan_estimate(), "done");
+        assert_eq!(sigs[2].to_row()[1], "Ore (anomaly)");
+        assert!(!sigs[0].anomaly);
+    }
+
+    #[test]
+    fn test_app_provenance() {
+        let mut app = App::new();
+        app.current_system = Some("Thera".to_owned());
+        let provenance = |app: &App, id: &str| {
+            app.system_data["Thera"]
+                .iter()
+                .find(|sig| sig.identifier.to_string() == id)
+                .and_then(|sig| sig.provenance)
+        };
+
+        let theirs = std::collections::HashMap::from([(
+            "Thera".to_owned(),
+            vec![Signature::new("ABC", "123", SignatureType::Unknown)],
+        )]);
+        app.import_teammate(&theirs);
+        assert_eq!(provenance(&app, "ABC-123"), Some(Provenance::Imported));
+
+        let placeholder = app.quick_add_wormhole().unwrap().to_string();
+        assert_eq!(provenance(&app, &placeholder), Some(Provenance::Manual));
+
+        app.merge_in(&[ClipboardItem::new("DEF-456", "Wormhole", "")])
+            .unwrap();
+        assert_eq!(provenance(&app, "DEF-456"), Some(Provenance::Scanned));
+        // scanning an imported signature myself verifies it
+        app.merge_in(&[ClipboardItem::new("ABC-123", "", "")])
+            .unwrap();
+        assert_eq!(provenance(&app, "ABC-123"), Some(Provenance::Scanned));
+    }
+
+    #[test]
+    fn test_app_import_table() {
+        let table =
+            csv_import::parse("ID,Type,Name\nZZZ-111,Gas,\nYYY-222,Relic,Crumbling").unwrap();
+        let import = CsvImport::new(table);
+        let mut app = App::new();
+        app.current_system = None;
+        assert!(app.import_table(&import).is_none());
+
+        app.current_system = Some("Thera".to_owned());
+        let summary = app.import_table(&import).unwrap();
+        assert_eq!(summary.added.len(), 2);
+        assert_eq!(app.system_data["Thera"].len(), 2);
+        assert!(app.undo_import());
+    }
+
+    #[test]
+    fn test_app_import_teammate() {
+        let mut app = App::new();
+        let before = app.system_data.clone();
+        let mut theirs = app.system_data.clone();
+        let sigs = theirs.get_mut("J173213").unwrap();
+        for sig in sigs.iter_mut() {
+            if let SignatureType::Wormhole(wh) = &mut sig.signature_type {
+                wh.destination = Some("Amarr".to_owned());
+                wh.life = WormholeLife::EndOfLife;
+            }
+        }
+        sigs.push(Signature::new("XYZ", "999", SignatureType::Gas(None)));
+        theirs.insert(
+            "Thera".to_owned(),
+            vec![Signature::new("QRS", "111", SignatureType::Unknown)],
+        );
+
+        let summary = app.import_teammate(&theirs);
+
+        assert_eq!(summary.added.len(), 2);
+        assert_eq!(app.system_data["Thera"].len(), 1);
+        assert_eq!(
+            app.system_data["J173213"].len(),
+            before["J173213"].len() + 1
+        );
+        assert_eq!(app.import_conflicts.len(), summary.conflicts.len());
+        assert!(!app.import_conflicts.is_empty());
+
+        let conflict = app.import_conflicts[0].clone();
+        app.resolve_conflict(0, Resolution::Theirs);
+        let sig = app.system_data[&conflict.system]
+            .iter()
+            .find(|sig| sig.identifier == conflict.mine.identifier)
+            .unwrap();
+        assert_eq!(sig.signature_type, conflict.theirs.signature_type);
+
+        app.resolve_system_conflicts("J173213", Resolution::Mine);
+        assert!(app.import_conflicts.is_empty());
+
+        assert!(app.undo_import());
+        assert_eq!(app.system_data, before);
+        assert!(!app.undo_import());
+    }
+
+    #[test]
+    fn test_app_undo_import_after_edit() {
+        let mut app = App::new();
+        let theirs = std::collections::HashMap::from([(
+            "Thera".to_owned(),
+            vec![Signature::new("QRS", "111", SignatureType::Unknown)],
+        )]);
+        let id = SignatureId::new("QRS", "111");
+
+        // starring an imported signature counts as an edit
+        app.import_teammate(&theirs);
+        app.toggle_favorite("Thera", &id);
+        assert!(!app.undo_import());
+        assert!(app.system_data.contains_key("Thera"));
+
+        // edits after an import keep it from being undone
+        app.import_teammate(&theirs);
+        app.set_system_note("Thera", "after the import");
+        assert!(!app.undo_import());
+        assert_eq!(app.system_note("Thera"), Some("after the import"));
+        assert!(app.is_favorite("Thera", &id));
+    }
+
+    #[test]
+    fn test_app_merge_in_empty_empty() {
+        let mut app = App::new();
+        app.current_system = Some("Thera".to_owned());
+        app.merge_in(&[]).unwrap();
+
+        assert!(app.system_data.contains_key("Thera"));
+        assert!(app.system_data.get("Thera").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_app_merge_in_some_empty() {
+        let mut app = App::new();
+        app.current_system = Some("Thera".to_owned());
+        app.system_data.insert(
+            "Thera".to_owned(),
+            vec![Signature::new(
+                "ABC",
+                "123",
+                crate::eve_data::SignatureType::Data(Some("Foobar".to_owned())),
+            )],
+        );
+        app.merge_in(&[]).unwrap();
+
+        assert_eq!(app.system_data.get("Thera").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_app_merge_in_update_type() {
+        let mut app = App::new();
+        app.current_system = Some("Thera".to_owned());
+        app.system_data.insert(
+            "Thera".to_owned(),
+            vec![Signature::new(
+                "ABC",
+                "123",
+                crate::eve_data::SignatureType::Unknown,
+            )],
+        );
+
+        app.merge_in(&[ClipboardItem::new("ABC-123", "Relic", "Foobar")])
+            .unwrap();
+
+        assert_eq!(app.system_data.get("Thera").unwrap().len(), 1);
+        let sig = app.system_data.get("Thera").unwrap().first().unwrap();
+        assert_eq!(sig.identifier.id, "ABC".to_owned());
+        assert_eq!(sig.identifier.number, "123");
+        match sig.signature_type {
+            crate::eve_data::SignatureType::Relic(ref name) => {
+                assert_eq!(name, &Some("Foobar".to_owned()));
+            }
+            _ => {
+                panic!("Should be a relic sig");
+            }
+        }
+    }
+
+    #[test]
+    fn test_app_merge_in_add_name() {
+        let mut app = App::new();
+        app.current_system = Some("Thera".to_owned());
+        app.system_data.insert(
+            "Thera".to_owned(),
+            vec![Signature::new(
+                "ABC",
+                "123",
+                crate::eve_data::SignatureType::Relic(None),
+            )],
+        );
+
+        app.merge_in(&[ClipboardItem::new("ABC-123", "Relic", "Foobar")])
+            .unwrap();
+
+        assert_eq!(app.system_data.get("Thera").unwrap().len(), 1);
+        let sig = app.system_data.get("Thera").unwrap().first().unwrap();
+        assert_eq!(sig.identifier.id, "ABC".to_owned());
+        assert_eq!(sig.identifier.number, "123");
+        match sig.signature_type {
+            crate::eve_data::SignatureType::Relic(ref name) => {
+                assert_eq!(name, &Some("Foobar".to_owned()));
+            }
+            _ => {
+                panic!("Should be a relic sig");
+            }
+        }
+    }
+
+    #[test]
+    fn test_app_merge_in_no_overwrite_name() {
+        let mut app = App::new();
+        app.current_system = Some("Thera".to_owned());
+        app.system_data.insert(
+            "Thera".to_owned(),
+            vec![Signature::new(
+                "ABC",
+                "123",
+                crate::eve_data::SignatureType::Relic(Some("Foobar".to_owned())),
+            )],
+        );
+
+        app.merge_in(&[ClipboardItem::new("ABC-123", "Relic", "")])
+            .unwrap();
+
+        assert_eq!(app.system_data.get("Thera").unwrap().len(), 1);
+        let sig = app.system_data.get("Thera").unwrap().first().unwrap();
+        assert_eq!(sig.identifier.id, "ABC".to_owned());
+        assert_eq!(sig.identifier.number, "123");
+        match sig.signature_type {
+            crate::eve_data::SignatureType::Relic(ref name) => {
+                assert_eq!(name, &Some("Foobar".to_owned()));
+            }
+            _ => {
+                panic!("Should be a relic sig");
+            }
+        }
+    }
+
+    #[test]
+    fn test_app_merge_in_prefers_fuller_name() {
+        let mut app = App::new();
+        app.current_system = Some("Thera".to_owned());
+        app.system_data.insert(
+            "Thera".to_owned(),
+            vec![Signature::new(
+                "ABC",
+                "123",
+                SignatureType::Combat(Some("Forgotten Perimeter Cor…".to_owned())),
+            )],
+        );
+        let full = "Forgotten Perimeter Coronation Platform";
+
+        app.merge_in(&[ClipboardItem::new("ABC-123", "Combat", full)])
+            .unwrap();
+        let name = |app: &App| {
+            app.system_data["Thera"][0]
+                .signature_type
+                .name()
+                .map(str::to_owned)
+        };
+        assert_eq!(name(&app).as_deref(), Some(full));
+
+        app.merge_in(&[ClipboardItem::new(
+            "ABC-123",
+            "Combat",
+            "Forgotten Perimeter Coronation...",
+        )])
+        .unwrap();
+        assert_eq!(name(&app).as_deref(), Some(full));
+    }
+
+    #[test]
+    fn test_resolve_merge() {
+        let short = SignatureType::Relic(Some("Ruined".to_owned()));
+        let long = SignatureType::Relic(Some("Ruined Sansha Monument Site".to_owned()));
+        assert_eq!(resolve_merge(short.clone(), long.clone()), long);
+        assert_eq!(resolve_merge(long.clone(), short.clone()), long);
+
+        // a different kind of site is a rescan, not a truncation
+        let data = SignatureType::Data(Some("Local".to_owned()));
+        assert_eq!(resolve_merge(long, data.clone()), data);
+    }
+
+    #[test]
+    fn test_app_merge_in_uses_new_wormhole_state() {
+        let mut app = App::new();
+        app.new_wormhole = NewWormholeState::Unknown.wormhole();
+        app.merge_in(&[
+            ClipboardItem::new("ABC-123", "Wormhole", ""),
+            ClipboardItem::new("GHI-789", "Wormhole", ""),
+        ])
+        .unwrap();
+
+        for sig in app.system_signatures() {
+            if sig.identifier.id == "DEF" {
+                continue;
+            }
+            match &sig.signature_type {
+                SignatureType::Wormhole(wh) => {
+                    assert_eq!(wh.life, WormholeLife::Unknown);
+                    assert_eq!(wh.mass, WormholeMass::Unknown);
+                }
+                _ => panic!("Should be a wormhole sig"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_app_merge_in_no_wormhole_data_overwrite() {
+        let mut app = App::new();
+        app.current_system = Some("Thera".to_owned());
+        let wh = SignatureWormhole {
+            wh_type: Some("A239".to_owned()),
+            life: WormholeLife::EndOfLife,
+            ..Default::default()
+        };
+        app.system_data.insert(
+            "Thera".to_owned(),
+            vec![Signature::new(
+                "ABC",
+                "123",
+                crate::eve_data::SignatureType::Wormhole(wh),
+            )],
+        );
+
+        app.merge_in(&[ClipboardItem::new("ABC-123", "Wormhole", "")])
+            .unwrap();
 
         assert_eq!(app.system_data.get("Thera").unwrap().len(), 1);
-        let sig = app.system_data.get("Thera").unwrap().get(0).unwrap();
+        let sig = app.system_data.get("Thera").unwrap().first().unwrap();
         assert_eq!(sig.identifier.id, "ABC".to_owned());
         assert_eq!(sig.identifier.number, "123");
         match sig.signature_type {
@@ -288,9 +2766,1457 @@ mod tests {
                 assert_eq!(data.wh_type, Some("A239".to_owned()));
                 assert_eq!(data.life, WormholeLife::EndOfLife);
             }
-            _ => {
-                panic!("Should be a wormhole sig");
+            _ => {
+                panic!("Should be a wormhole sig");
+            }
+        }
+    }
+
+    #[test]
+    fn test_app_toggle_favorite() {
+        let mut app = App::new();
+        let id = SignatureId::new("ABC", "123");
+
+        app.toggle_favorite("J173213", &id);
+        assert!(app.is_favorite("J173213", &id));
+        assert!(!app.is_favorite("Thera", &id));
+
+        app.toggle_favorite("J173213", &id);
+        assert!(app.favorites.is_empty());
+    }
+
+    #[test]
+    fn test_app_select_favorite() {
+        let mut app = App::new();
+        app.current_system = Some("Thera".to_owned());
+        app.toggle_favorite("J173213", &SignatureId::new("DEF", "456"));
+
+        app.select_favorite(0);
+
+        assert_eq!(app.current_system, Some("J173213".to_owned()));
+        assert_eq!(app.data_index, 1);
+    }
+
+    #[test]
+    fn test_app_remove_signature_prunes_favorite() {
+        let mut app = App::new();
+        app.toggle_favorite("J173213", &SignatureId::new("ABC", "123"));
+        app.toggle_favorite("J173213", &SignatureId::new("DEF", "456"));
+
+        let removed = app.remove_signature("J173213", 0).unwrap();
+
+        assert_eq!(removed.identifier, SignatureId::new("ABC", "123"));
+        assert_eq!(app.system_data.get("J173213").unwrap().len(), 1);
+        assert_eq!(
+            app.favorites,
+            vec![("J173213".to_owned(), SignatureId::new("DEF", "456"))]
+        );
+    }
+
+    #[test]
+    fn test_app_save_load_favorites() {
+        let path = std::env::temp_dir().join("evemapping_test_save_load_favorites.json");
+        let mut app = App::new();
+        app.toggle_favorite("J173213", &SignatureId::new("DEF", "456"));
+        app.save_to(&path).unwrap();
+
+        let loaded = App::load_from(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.favorites, app.favorites);
+        assert_eq!(loaded.system_data, app.system_data);
+    }
+
+    #[test]
+    fn test_system_note_save_load() {
+        let path = std::env::temp_dir().join("evemapping_test_save_load_system_note.json");
+        let mut app = App::new();
+        app.set_system_note("J173213", "  POS at planet 4, moon 2; enemy staging ");
+        app.switch_system("J105443");
+        app.switch_system("J173213");
+        assert_eq!(
+            app.system_note("J173213"),
+            Some("POS at planet 4, moon 2; enemy staging")
+        );
+        app.save_to(&path).unwrap();
+
+        let mut loaded = App::load_from(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.system_notes, app.system_notes);
+        assert_eq!(loaded.system_note("J105443"), None);
+        loaded.set_system_note("J173213", "");
+        assert_eq!(loaded.system_note("J173213"), None);
+    }
+
+    #[test]
+    fn test_interrupted_save_keeps_old_state() {
+        let dir = std::env::temp_dir().join(format!("evemapping-atomic-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("state.json");
+        let mut app = App::new();
+        app.save_to(&path).unwrap();
+        assert!(!dir.join("state.json.tmp").exists());
+        let saved = std::fs::read_to_string(&path).unwrap();
+
+        // the temporary file can't be written, as if the app died writing it
+        std::fs::create_dir(dir.join("state.json.tmp")).unwrap();
+        app.system_data.clear();
+        assert!(app.save_to(&path).is_err());
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), saved);
+        assert!(!App::load_from(&path).unwrap().system_data.is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_load_corrupt_state() {
+        let dir = std::env::temp_dir().join(format!("evemapping-corrupt-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("state.json");
+        let mut text = serde_json::to_string(&App::new()).unwrap();
+        text.truncate(text.len() / 2);
+        std::fs::write(&path, &text).unwrap();
+
+        let app = App::load_from(&path).unwrap();
+
+        // no demo signatures mixed in with the real map
+        assert!(app.system_data.is_empty());
+        assert!(app.current_system.is_none());
+        assert!(app.favorites.is_empty());
+        assert!(app.status_message.is_some());
+        assert!(!path.exists());
+        assert_eq!(
+            std::fs::read_to_string(dir.join("state.json.bak")).unwrap(),
+            text
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_load_wrong_shape_state() {
+        let dir = std::env::temp_dir().join(format!("evemapping-shape-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("state.json");
+        let mut app = App::new();
+        app.set_system_note("J173213", "backed up");
+        app.save_to(&path).unwrap();
+        app.save_to(&path).unwrap();
+        let text = format!(r#"{{"version": {STATE_VERSION}, "app": {{"systems": []}}}}"#);
+        std::fs::write(&path, &text).unwrap();
+
+        let loaded = App::load_from(&path).unwrap();
+
+        assert_eq!(loaded.system_note("J173213"), Some("backed up"));
+        assert!(loaded.status_message.is_some());
+        assert_eq!(
+            std::fs::read_to_string(dir.join("state.json.bak")).unwrap(),
+            text
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_load_compressed_state() {
+        let dir = std::env::temp_dir().join(format!("evemapping-gzip-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("state.json");
+        let mut app = App::new();
+        app.set_system_note("J173213", "compressed");
+
+        // legacy plain JSON still loads
+        app.save_to(&path).unwrap();
+        assert!(std::fs::read_to_string(&path).unwrap().starts_with('{'));
+        assert_eq!(
+            App::load_from(&path).unwrap().system_notes,
+            app.system_notes
+        );
+
+        app.compress_state = true;
+        app.save_to(&path).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        assert!(bytes.starts_with(&super::GZIP_MAGIC));
+        let loaded = App::load_from(&path).unwrap();
+        assert_eq!(loaded.system_notes, app.system_notes);
+        assert!(loaded.status_message.is_none());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_load_truncated_compressed_state() {
+        let dir = std::env::temp_dir().join(format!("evemapping-gzip-bad-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("state.json");
+        let mut app = App::new();
+        app.compress_state = true;
+        app.set_system_note("J173213", "first");
+        app.save_to(&path).unwrap();
+        app.set_system_note("J173213", "second");
+        app.save_to(&path).unwrap();
+        app.set_system_note("J173213", "third");
+        app.save_to(&path).unwrap();
+
+        // the newest backup is damaged too, so the one before it is used
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes.truncate(bytes.len() / 2);
+        std::fs::write(&path, &bytes).unwrap();
+        std::fs::write(super::backup_path(&path, 1), &bytes[..10]).unwrap();
+
+        let loaded = App::load_from(&path).unwrap();
+
+        assert_eq!(loaded.system_note("J173213"), Some("first"));
+        assert!(loaded
+            .status_message
+            .unwrap()
+            .ends_with("state.json.2 was restored"));
+        assert_eq!(std::fs::read(dir.join("state.json.bak")).unwrap(), bytes);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_migrate_v1() {
+        let text = include_str!("../tests/fixtures/state_v1.json");
+        let upgraded = super::v1_to_v2(serde_json::from_str(text).unwrap()).unwrap();
+        assert!(upgraded.get("system_data").is_none());
+        assert_eq!(upgraded["systems"]["J173213"].as_array().unwrap().len(), 2);
+
+        let app = migrate(serde_json::from_str(text).unwrap()).unwrap();
+
+        assert!(app.favorites.is_empty());
+        assert!(app.connections.is_empty());
+        let sigs = app.system_signatures();
+        assert_eq!(sigs.len(), 2);
+        match &sigs[1].signature_type {
+            SignatureType::Wormhole(wh) => {
+                assert_eq!(wh.life, WormholeLife::EndOfLife);
+                assert_eq!(wh.connection_kind, ConnectionKind::Unknown);
+                assert!(wh.origin_type.is_none());
+            }
+            _ => panic!("Should be a wormhole sig"),
+        }
+    }
+
+    #[test]
+    fn test_migrate_current_version() {
+        let text = include_str!("../tests/fixtures/state_v2.json");
+        let app = migrate(serde_json::from_str(text).unwrap()).unwrap();
+
+        assert_eq!(app.system_note("J173213"), Some("home"));
+        assert_eq!(app.system_signatures().len(), 1);
+    }
+
+    #[test]
+    fn test_migrate_rejects_newer_version() {
+        let saved = serde_json::json!({ "version": STATE_VERSION + 1, "app": {} });
+        let Err(e) = migrate(saved) else {
+            panic!("Newer versions shouldn't load");
+        };
+        assert!(e.to_string().contains("newer than this build"));
+        assert!(migrate(serde_json::json!({ "version": 0, "app": {} })).is_err());
+    }
+
+    #[test]
+    fn test_load_upgrades_old_state() {
+        let dir = std::env::temp_dir().join(format!("evemapping-upgrade-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("state.json");
+        let text = include_str!("../tests/fixtures/state_v1.json");
+        std::fs::write(&path, text).unwrap();
+
+        let app = App::load_from(&path).unwrap();
+
+        assert_eq!(app.system_signatures().len(), 2);
+        assert_eq!(
+            std::fs::read_to_string(dir.join("state.json.v1")).unwrap(),
+            text
+        );
+        let saved: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved["version"], STATE_VERSION);
+        assert!(saved["app"]["systems"].is_object());
+        assert!(saved["app"].get("system_data").is_none());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_load_newer_state_untouched() {
+        let dir = std::env::temp_dir().join(format!("evemapping-newer-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("state.json");
+        let text = format!(r#"{{"version": {}, "app": {{}}}}"#, STATE_VERSION + 1);
+        std::fs::write(&path, &text).unwrap();
+
+        assert!(App::load_from(&path).is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), text);
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_confirm_delete() {
+        let mut app = App::new();
+        let id = SignatureId::new("ABC", "123");
+
+        let removed = app.confirm_or_perform(ConfirmAction::DeleteSignatures(vec![id.clone()]));
+        assert!(removed.is_none());
+        assert!(app.has_signature("J173213", &id));
+        let ViewMode::Confirm { message, action } = app.view.clone() else {
+            panic!("Should be asking for confirmation");
+        };
+        assert_eq!(message, "Delete 1 signature(s)? (y/n)");
+        assert_eq!(app.perform(action).len(), 1);
+        assert!(!app.has_signature("J173213", &id));
+
+        let mut app = App::new();
+        app.confirmations.delete = false;
+        let removed = app.confirm_or_perform(ConfirmAction::DeleteSignatures(vec![id.clone()]));
+        assert_eq!(removed.unwrap().len(), 1);
+        assert!(!app.has_signature("J173213", &id));
+        assert!(app.view == ViewMode::Normal);
+    }
+
+    #[test]
+    fn test_find_signature_global() {
+        let mut app = App::new();
+        app.system_data.insert(
+            "Thera".to_owned(),
+            vec![
+                Signature::new("GHI", "789", SignatureType::Unknown),
+                Signature::new("DEX", "111", SignatureType::Gas(Some("Combat".to_owned()))),
+            ],
+        );
+
+        assert_eq!(
+            app.find_signature_global("ghi"),
+            Some(("Thera".to_owned(), 0))
+        );
+        assert_eq!(
+            app.find_signature_global("DEF-45"),
+            Some(("J173213".to_owned(), 1))
+        );
+        // the current system is searched first
+        assert_eq!(
+            app.find_signature_global("de"),
+            Some(("J173213".to_owned(), 1))
+        );
+        // IDs are preferred to names
+        assert_eq!(
+            app.find_signature_global("dex"),
+            Some(("Thera".to_owned(), 1))
+        );
+        assert_eq!(
+            app.find_signature_global("combat"),
+            Some(("J173213".to_owned(), 0))
+        );
+        assert_eq!(app.find_signature_global("XYZ"), None);
+        assert_eq!(app.find_signature_global(" "), None);
+    }
+
+    #[test]
+    fn test_app_import_systems() {
+        let mut app = App::new();
+        let text = include_str!("../tests/fixtures/wanderer_export.json");
+        app.system_data
+            .get_mut("J173213")
+            .unwrap()
+            .push(Signature::new("JKL", "012", SignatureType::Gas(None)));
+
+        app.import_systems(crate::wanderer::parse_wanderer_export(text).unwrap());
+
+        let home = app.system_data.get("J173213").unwrap();
+        assert_eq!(home.len(), 4);
+        assert_eq!(home[2].signature_type, SignatureType::Gas(None));
+        assert_eq!(app.system_data.get("Thera").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_app_probe_filter_hint() {
+        let mut app = App::new();
+        app.current_system = Some("Thera".to_owned());
+        assert!(app.probe_filter_hint().starts_with("No signatures"));
+
+        app.system_data.insert(
+            "Thera".to_owned(),
+            vec![
+                Signature::new("ABC", "123", SignatureType::Unknown),
+                Signature::new("DEF", "456", SignatureType::Gas(None)),
+            ],
+        );
+        assert!(app.probe_filter_hint().starts_with("Unresolved"));
+
+        app.system_data.get_mut("Thera").unwrap().remove(0);
+        assert!(app
+            .probe_filter_hint()
+            .starts_with("All signatures resolved"));
+    }
+
+    #[test]
+    fn test_app_suggest_connection_kinds() {
+        let mut app = App::new();
+        // J173213's statics are a B274 and a Z647
+        app.system_data.get_mut("J173213").unwrap().extend([
+            Signature::new(
+                "GHI",
+                "789",
+                SignatureType::Wormhole(SignatureWormhole {
+                    wh_type: Some("B274".to_owned()),
+                    ..Default::default()
+                }),
+            ),
+            Signature::new(
+                "JKL",
+                "012",
+                SignatureType::Wormhole(SignatureWormhole {
+                    wh_type: Some("D845".to_owned()),
+                    ..Default::default()
+                }),
+            ),
+            Signature::new(
+                "MNO",
+                "345",
+                SignatureType::Wormhole(SignatureWormhole::default()),
+            ),
+        ]);
+
+        app.suggest_connection_kinds("J173213");
+
+        let kinds: Vec<_> = app
+            .system_signatures()
+            .iter()
+            .filter_map(|sig| match &sig.signature_type {
+                SignatureType::Wormhole(wh) => Some(wh.connection_kind),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ConnectionKind::IncomingK162,
+                ConnectionKind::Static,
+                ConnectionKind::Wandering,
+                ConnectionKind::Unknown,
+            ]
+        );
+
+        app.add_static_placeholder("J173213", "B274");
+        let placeholder = app.system_signatures().last().unwrap().to_row();
+        assert_eq!(placeholder[0], "???-001");
+        assert!(placeholder[1].ends_with("[S]"));
+    }
+
+    #[test]
+    fn test_app_move_signature() {
+        let mut app = App::new();
+        app.toggle_favorite("J173213", &SignatureId::new("ABC", "123"));
+        let created = app.system_data["J173213"][0].created;
+
+        assert!(app.move_signature("J173213", 0, "Thera"));
+        assert_eq!(app.system_data["Thera"][0].created, created);
+
+        assert_eq!(app.system_data["J173213"].len(), 1);
+        assert_eq!(
+            app.system_data["Thera"][0].identifier,
+            SignatureId::new("ABC", "123")
+        );
+        assert!(app.is_favorite("Thera", &SignatureId::new("ABC", "123")));
+        assert!(!app.move_signature("J173213", 5, "Thera"));
+    }
+
+    #[test]
+    fn test_app_move_signatures_undo() {
+        let mut app = App::new();
+        let id = SignatureId::new("ABC", "123");
+        app.toggle_pin_selected();
+        app.toggle_favorite("J173213", &id);
+        let before = app.system_data.clone();
+
+        assert_eq!(app.move_signatures(std::slice::from_ref(&id), "Thera"), 1);
+        assert!(app.is_pinned("Thera", &id));
+        assert!(!app.pinned.contains_key("J173213"));
+        assert_eq!(
+            app.activity["J173213"].last().unwrap().kind,
+            ActivityKind::Disappeared
+        );
+        assert_eq!(
+            app.activity["Thera"].last().unwrap().kind,
+            ActivityKind::Appeared
+        );
+        assert!(app.can_undo_move());
+
+        assert!(app.undo_move());
+        assert_eq!(app.system_data, before);
+        assert!(app.is_pinned("J173213", &id));
+        assert!(app.is_favorite("J173213", &id));
+        assert!(!app.activity.contains_key("Thera"));
+        assert!(!app.undo_move());
+
+        // any later change drops the undo
+        app.move_signatures(std::slice::from_ref(&id), "Thera");
+        app.toggle_favorite("Thera", &id);
+        assert!(!app.undo_move());
+    }
+
+    #[test]
+    fn test_app_move_signatures_merges_conflicts() {
+        let mut app = App::new();
+        app.system_data.insert(
+            "Thera".to_owned(),
+            vec![
+                Signature::new("ABC", "123", SignatureType::Unknown),
+                Signature::new(
+                    "DEF",
+                    "456",
+                    SignatureType::Wormhole(SignatureWormhole {
+                        destination: Some("Jita".to_owned()),
+                        ..Default::default()
+                    }),
+                ),
+            ],
+        );
+
+        let moved = app.move_signatures(
+            &[
+                SignatureId::new("ABC", "123"),
+                SignatureId::new("DEF", "456"),
+            ],
+            "Thera",
+        );
+
+        assert_eq!(moved, 2);
+        assert!(app.system_data["J173213"].is_empty());
+        let thera = &app.system_data["Thera"];
+        assert_eq!(thera.len(), 2);
+        // the unknown takes the moved combat site
+        assert_eq!(
+            thera[0].signature_type,
+            SignatureType::Combat(Some("Some Combat Site".to_owned()))
+        );
+        // the existing wormhole keeps its data
+        match &thera[1].signature_type {
+            SignatureType::Wormhole(wh) => assert_eq!(wh.destination.as_deref(), Some("Jita")),
+            _ => panic!("Should be a wormhole sig"),
+        }
+    }
+
+    #[test]
+    fn test_app_copy_signature() {
+        let mut app = App::new();
+
+        assert!(app.copy_signature("J173213", 1, "Thera"));
+
+        assert_eq!(app.system_data["J173213"].len(), 2);
+        assert_eq!(app.system_data["Thera"][0], app.system_data["J173213"][1]);
+        // already there
+        assert!(!app.copy_signature("J173213", 1, "Thera"));
+    }
+
+    #[test]
+    fn test_app_bulk_actions() {
+        let mut app = App::new();
+        app.system_data
+            .get_mut("J173213")
+            .unwrap()
+            .push(Signature::new(
+                "GHI",
+                "789",
+                SignatureType::Wormhole(SignatureWormhole::default()),
+            ));
+        assert_eq!(app.action_targets(), vec![SignatureId::new("ABC", "123")]);
+
+        app.data_index = 1;
+        app.toggle_selection();
+        app.data_index = 2;
+        app.toggle_selection();
+        let targets = app.action_targets();
+        assert_eq!(targets.len(), 2);
+
+        app.update_wormholes(&targets, |wh| wh.life = WormholeLife::EndOfLife);
+        for sig in &app.system_data["J173213"][1..] {
+            match &sig.signature_type {
+                SignatureType::Wormhole(wh) => assert_eq!(wh.life, WormholeLife::EndOfLife),
+                _ => panic!("Should be a wormhole sig"),
+            }
+        }
+
+        let removed = app.remove_signatures(&targets);
+        assert_eq!(removed.len(), 2);
+        assert_eq!(app.system_data["J173213"].len(), 1);
+        assert!(app.selection.is_empty());
+    }
+
+    #[test]
+    fn test_app_selection_cleared_and_reconciled() {
+        let mut app = App::new();
+        app.toggle_selection();
+        assert_eq!(app.selection.len(), 1);
+        app.switch_system("Thera");
+        assert!(app.selection.is_empty());
+
+        app.switch_system("J173213");
+        app.toggle_selection();
+        app.system_data.get_mut("J173213").unwrap().remove(0);
+        app.reconcile_selection();
+        assert!(app.selection.is_empty());
+    }
+
+    #[test]
+    fn test_app_static_placeholders() {
+        let mut app = App::new();
+        app.auto_static_placeholders = true;
+
+        // J105443 has a single Z060 static
+        app.switch_system("J105443");
+        let sigs = app.system_signatures();
+        assert_eq!(sigs.len(), 1);
+        assert!(sigs[0].is_placeholder());
+        assert_eq!(
+            app.probe_filter_hint(),
+            "No signatures yet: paste from the Cosmic Signature filter"
+        );
+
+        app.paste("ABC-123\tCosmic Signature\tWormhole\tUnstable Wormhole\t100.0%\t4.99 AU");
+        // the match is offered before the placeholder goes
+        assert_eq!(app.system_signatures().len(), 2);
+        let ViewMode::Confirm { action, .. } = app.view.clone() else {
+            panic!("Placeholder match should be offered");
+        };
+        app.view = ViewMode::Normal;
+        app.perform(action);
+
+        let sigs = app.system_signatures();
+        assert_eq!(sigs.len(), 1);
+        assert_eq!(sigs[0].identifier, SignatureId::new("ABC", "123"));
+        match &sigs[0].signature_type {
+            SignatureType::Wormhole(wh) => {
+                assert_eq!(wh.wh_type.as_deref(), Some("Z060"));
+                assert_eq!(wh.connection_kind, ConnectionKind::Static);
+            }
+            _ => panic!("Should be a wormhole sig"),
+        }
+
+        // each static gets its own ID
+        app.switch_system("J164417");
+        let ids: Vec<_> = app
+            .system_signatures()
+            .iter()
+            .map(|sig| sig.identifier.to_string())
+            .collect();
+        assert_eq!(ids, vec!["???-001", "???-002"]);
+        assert!(crate::integrity::check(&app).is_empty());
+
+        // only unvisited systems
+        app.switch_system("J173213");
+        assert!(!app
+            .system_signatures()
+            .iter()
+            .any(|sig| sig.is_placeholder()));
+    }
+
+    #[test]
+    fn test_app_ignored_hidden_but_merged() {
+        let mut app = App::new();
+        app.current_system = Some("Thera".to_owned());
+        app.merge_in(&[
+            ClipboardItem::new("ABC-123", "Combat", "Perimeter Camp"),
+            ClipboardItem::new("DEF-456", "", ""),
+        ])
+        .unwrap();
+        app.toggle_ignored(&[SignatureId::new("ABC", "123")]);
+
+        assert_eq!(app.system_signatures().len(), 1);
+        assert_eq!(app.selected_data_index(), Some(1));
+
+        app.merge_in(&[
+            ClipboardItem::new("ABC-123", "Combat", "Perimeter Camp"),
+            ClipboardItem::new("DEF-456", "", ""),
+        ])
+        .unwrap();
+        assert_eq!(app.system_data["Thera"].len(), 2);
+        assert!(app.system_data["Thera"][0].ignored);
+        assert_eq!(app.system_signatures().len(), 1);
+
+        app.show_ignored = true;
+        assert_eq!(app.system_signatures().len(), 2);
+    }
+
+    #[test]
+    fn test_app_activity_recorded() {
+        let mut app = App::new();
+        app.current_system = Some("Thera".to_owned());
+        app.merge_in(&[
+            ClipboardItem::new("ABC-123", "", ""),
+            ClipboardItem::new("DEF-456", "", ""),
+        ])
+        .unwrap();
+        app.merge_in(&[ClipboardItem::new("ABC-123", "", "")])
+            .unwrap();
+        app.remove_signature("Thera", 0);
+
+        let events = &app.activity["Thera"];
+        let appeared = events
+            .iter()
+            .filter(|e| e.kind == ActivityKind::Appeared)
+            .count();
+        assert_eq!(appeared, 2);
+        assert_eq!(events.last().unwrap().kind, ActivityKind::Disappeared);
+        assert!(!app.activity.contains_key("J173213"));
+    }
+
+    #[test]
+    fn test_app_jump_to_first_unknown() {
+        let mut app = App::new();
+        app.current_system = None;
+        app.system_data
+            .get_mut("J173213")
+            .unwrap()
+            .push(Signature::new("GHI", "789", SignatureType::Unknown));
+
+        app.jump_to_first_unknown("J173213");
+
+        assert_eq!(app.current_system.as_deref(), Some("J173213"));
+        assert_eq!(app.data_index, 2);
+    }
+
+    #[test]
+    fn test_app_paste_grouped() {
+        let mut app = App::new();
+        app.current_system = Some("Thera".to_owned());
+
+        app.paste(
+            "ABC-123\tCosmic Signature\t\t\t0.0%\t4.99 AU
+J173213
+GHI-789\tCosmic Signature\tWormhole\tUnstable Wormhole\t100.0%\t4.99 AU
+Jita
+JKL-012\tCosmic Signature\t\t\t0.0%\t4.99 AU
+",
+        );
+
+        assert_eq!(app.system_data["Thera"].len(), 1);
+        assert_eq!(app.system_data["J173213"].len(), 3);
+        assert_eq!(app.system_data["Jita"].len(), 1);
+        assert_eq!(app.current_system.as_deref(), Some("Thera"));
+    }
+
+    #[test]
+    fn test_app_paste_breakdown() {
+        let mut app = App::new();
+        app.current_system = None;
+        let headered = "J173213
+ABC-123\tCosmic Signature\t\t\t0.0%\t4.99 AU
+Jita
+DEF-456\tCosmic Signature\tWormhole\tUnstable Wormhole\t100.0%\t4.99 AU
+GHI-789\tCosmic Signature\t\t\t0.0%\t4.99 AU
+";
+        assert_eq!(
+            app.paste_breakdown(headered),
+            vec![
+                (Some("J173213".to_owned()), 1),
+                (Some("Jita".to_owned()), 2)
+            ]
+        );
+
+        let unheadered = "ABC-123\tCosmic Signature\t\t\t0.0%\t4.99 AU\n";
+        assert!(app.paste_breakdown(unheadered).is_empty());
+
+        // leading results without a system, a header with nothing under it,
+        // and lines that aren't scanner results at all
+        let mixed = "JKL-012\tCosmic Signature\t\t\t0.0%\t4.99 AU
+  thera  
+scouted by Bob
+
+J105443
+not a signature
+MNO-345\tCosmic Signature\tData Site\t\t0.0%\t4.99 AU
+";
+        assert_eq!(
+            app.paste_breakdown(mixed),
+            vec![
+                (None, 1),
+                (Some("Thera".to_owned()), 0),
+                (Some("J105443".to_owned()), 1)
+            ]
+        );
+        app.current_system = Some("Jita".to_owned());
+        assert_eq!(app.paste_breakdown(mixed)[0], (Some("Jita".to_owned()), 1));
+
+        app.paste(mixed);
+        assert_eq!(app.system_data["Jita"].len(), 1);
+        assert!(app.system_data["Thera"].is_empty());
+        assert_eq!(app.system_data["J105443"].len(), 1);
+    }
+
+    #[test]
+    fn test_app_paste_valuable_site() {
+        let mut app = App::new();
+        app.current_system = Some("J173213".to_owned());
+        app.valuable_site_tier = Some(3);
+
+        let alerts = app
+            .paste(
+                "ABC-123\tCosmic Signature\tRelic Site\tRuined Angel Temple Site\t100.0%\t4.99 AU
+DEF-456\tCosmic Signature\tRelic Site\tForgotten Perimeter Power Array\t100.0%\t4.99 AU
+GHI-789\tCosmic Signature\t\t\t0.0%\t4.99 AU
+",
+            )
+            .unwrap();
+
+        assert_eq!(
+            alerts,
+            vec![Alert::ValuableSite(SignatureId::new("ABC", "123"))]
+        );
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("Valuable site: ABC-123")
+        );
+    }
+
+    #[test]
+    fn test_app_touches_systems() {
+        let mut app = App::new();
+        app.current_system = Some("J173213".to_owned());
+        app.last_activity.clear();
+
+        app.merge_in(&[ClipboardItem::new("GHI-789", "", "")])
+            .unwrap();
+        app.switch_system("Thera");
+        app.copy_signature("J173213", 0, "Jita");
+
+        assert_eq!(app.last_activity.len(), 3);
+        assert!(app.last_active("Amamake").is_none());
+    }
+
+    #[test]
+    fn test_app_last_scanned() {
+        let mut app = App::new();
+        app.current_system = Some("J173213".to_owned());
+
+        app.merge_in(&[ClipboardItem::new("GHI-789", "", "")])
+            .unwrap();
+        assert!(app.last_scanned.contains_key("J173213"));
+
+        // switching and editing aren't scans
+        app.switch_system("Thera");
+        app.set_system_note("Thera", "statics checked");
+        assert!(!app.last_scanned.contains_key("Thera"));
+
+        app.remove_system("J173213");
+        assert!(app.last_scanned.is_empty());
+    }
+
+    #[test]
+    fn test_app_stale_branches() {
+        let mut app = App::new();
+        let old = Utc::now() - Duration::days(3);
+        let wormhole_to = |id: &str, destination: &str| {
+            let mut sig = Signature::new(
+                id,
+                "123",
+                SignatureType::Wormhole(SignatureWormhole {
+                    destination: Some(destination.to_owned()),
+                    ..Default::default()
+                }),
+            );
+            sig.created = old;
+            sig
+        };
+        app.system_data.insert(
+            "J173213".to_owned(),
+            vec![wormhole_to("AAA", "Thera"), wormhole_to("BBB", "J105443")],
+        );
+        app.system_data
+            .insert("Thera".to_owned(), vec![wormhole_to("CCC", "Jita")]);
+        app.system_data
+            .insert("J105443".to_owned(), vec![wormhole_to("DDD", "Amarr")]);
+        app.favorites
+            .push(("Thera".to_owned(), SignatureId::new("CCC", "123")));
+        app.stale_after = Some(Duration::days(1));
+        app.switch_system("J173213");
+        app.merge_in_system("J105443", &[ClipboardItem::new("EEE-123", "", "")]);
+
+        let nodes = app.map_nodes();
+        let systems: Vec<_> = nodes.iter().map(|n| n.system.as_str()).collect();
+        assert_eq!(systems, vec!["J173213", "J105443", "Amarr", "Thera"]);
+        assert!(nodes[1].collapsed.is_empty());
+        assert!(nodes[2].collapsed.is_empty());
+        assert_eq!(nodes[3].collapsed, vec!["Thera", "Jita"]);
+
+        app.expand_branch("Thera");
+        assert_eq!(app.map_nodes().len(), 5);
+        // a fresh visit keeps a branch live
+        app.touch("Jita");
+        assert!(app.map_nodes().iter().all(|n| n.collapsed.is_empty()));
+        app.last_activity.remove("Jita");
+
+        let stale = app.stale_systems();
+        assert_eq!(stale, vec!["Thera", "Jita"]);
+        for system in &stale {
+            app.remove_system(system);
+        }
+        assert!(!app.system_data.contains_key("Thera"));
+        assert!(app.favorites.is_empty());
+        let systems: Vec<_> = app.map_nodes().into_iter().map(|n| n.system).collect();
+        assert_eq!(systems, vec!["J173213", "J105443", "Amarr"]);
+    }
+
+    #[test]
+    fn test_app_paste_tracks_strength() {
+        let mut app = App::new();
+        app.current_system = Some("J173213".to_owned());
+        let paste = |strength: &str| format!("GHI-789\tCosmic Signature\t\t\t{strength}%\t4.99 AU");
+
+        app.paste(&paste("20.0"));
+        app.paste(&paste("20.0"));
+        app.paste(&paste("45.0"));
+
+        let sig = app.system_data["J173213"]
+            .iter()
+            .find(|sig| sig.identifier == SignatureId::new("GHI", "789"))
+            .unwrap();
+        assert_eq!(sig.scan_strengths, vec![20.0, 45.0]);
+        assert_eq!(sig.scan_estimate(), "~3 more");
+    }
+
+    #[test]
+    fn test_app_merge_in_no_system() {
+        let mut app = App::new();
+        app.current_system = None;
+        let before = app.system_data.clone();
+
+        assert!(app
+            .merge_in(&[ClipboardItem::new("ABC-123", "Relic", "")])
+            .is_err());
+        assert_eq!(app.system_data, before);
+
+        let alerts = app.paste("GHI-789\tCosmic Signature\t\t\t0.0%\t4.99 AU");
+        assert_eq!(alerts, Some(Vec::new()));
+        assert_eq!(app.system_data, before);
+        assert_eq!(app.status_message.as_deref(), Some("Select a system first"));
+    }
+
+    #[test]
+    fn test_app_orphaned_systems() {
+        let mut app = App::new();
+        app.system_data.insert(
+            "Thera".to_owned(),
+            vec![Signature::new(
+                "AAA",
+                "123",
+                SignatureType::Wormhole(SignatureWormhole {
+                    destination: Some("Jita".to_owned()),
+                    ..Default::default()
+                }),
+            )],
+        );
+        for system in ["Jita", "Amamake", "Hek", "Rens", "J105443"] {
+            app.add_system(system);
+        }
+        app.connections.push(Connection {
+            ends: vec![("Hek".to_owned(), SignatureId::new("BBB", "123"))],
+            note: None,
+        });
+        app.home_system = Some("Rens".to_owned());
+        app.current_system = Some("J105443".to_owned());
+
+        // Jita is only referenced by a destination, but that still counts
+        assert_eq!(app.orphaned_systems(), vec!["Amamake"]);
+
+        app.system_data.get_mut("Thera").unwrap().clear();
+        app.current_system = None;
+        assert_eq!(
+            app.orphaned_systems(),
+            vec!["Amamake", "J105443", "Jita", "Thera"]
+        );
+        assert_eq!(app.sweep_orphaned_systems(), 4);
+        assert!(app.system_data.contains_key("Hek"));
+        assert!(app.orphaned_systems().is_empty());
+    }
+
+    #[test]
+    fn test_app_row_digits() {
+        let mut app = App::new();
+        let sigs = (0..15)
+            .map(|n| Signature::new("ABC", &format!("{n:03}"), SignatureType::Unknown))
+            .collect();
+        app.system_data.insert("J173213".to_owned(), sigs);
+
+        assert_eq!(app.row_digit(0, 5), Some(1));
+        assert_eq!(app.row_digit(4, 5), Some(5));
+        assert_eq!(app.row_digit(5, 5), None);
+        assert_eq!(app.row_digit(9, 12), None);
+
+        // highlighting row 7 of 5 visible scrolls rows 3-7 into view
+        app.data_index = 7;
+        assert_eq!(app.visible_row_offset(5), 3);
+        assert_eq!(app.row_digit(2, 5), None);
+        assert_eq!(app.row_digit(3, 5), Some(1));
+        assert!(app.select_row_digit(2, 5));
+        assert_eq!(app.data_index, 4);
+
+        assert!(!app.select_row_digit(6, 5));
+        assert!(!app.select_row_digit(0, 5));
+        // short lists only have digits for the rows they have
+        app.system_data.get_mut("J173213").unwrap().truncate(5);
+        app.data_index = 4;
+        assert!(!app.select_row_digit(9, 20));
+        assert_eq!(app.data_index, 4);
+    }
+
+    #[test]
+    fn test_app_wormhole_direction() {
+        let mut app = App::new();
+        let wormhole = |wh_type: &str, destination: Option<&str>| {
+            SignatureType::Wormhole(SignatureWormhole {
+                wh_type: Some(wh_type.to_owned()),
+                destination: destination.map(str::to_owned),
+                ..Default::default()
+            })
+        };
+        app.system_data.insert(
+            "J173213".to_owned(),
+            vec![
+                Signature::new("AAA", "111", wormhole("B274", Some("Thera"))),
+                Signature::new("BBB", "222", wormhole("K162", None)),
+                Signature::new("CCC", "333", wormhole("Z647", None)),
+                Signature::new("DDD", "444", SignatureType::Unknown),
+            ],
+        );
+        app.system_data.insert(
+            "Thera".to_owned(),
+            vec![Signature::new(
+                "EEE",
+                "555",
+                wormhole("K162", Some("J173213")),
+            )],
+        );
+        app.link_wormholes(
+            ("J173213".to_owned(), SignatureId::new("CCC", "333")),
+            ("Jita".to_owned(), SignatureId::new("FFF", "666")),
+        );
+        let direction = |app: &App, system: &str, index: usize| {
+            app.wormhole_direction(system, &app.system_data[system][index])
+        };
+
+        assert_eq!(
+            direction(&app, "J173213", 0),
+            Some(WormholeDirection::Bidirectional)
+        );
+        assert_eq!(
+            direction(&app, "Thera", 0),
+            Some(WormholeDirection::Bidirectional)
+        );
+        assert_eq!(
+            direction(&app, "J173213", 1),
+            Some(WormholeDirection::Inbound)
+        );
+        assert_eq!(
+            direction(&app, "J173213", 2),
+            Some(WormholeDirection::Bidirectional)
+        );
+        assert_eq!(direction(&app, "J173213", 3), None);
+
+        app.system_data.get_mut("Thera").unwrap().clear();
+        assert_eq!(
+            direction(&app, "J173213", 0),
+            Some(WormholeDirection::Outbound)
+        );
+    }
+
+    #[test]
+    fn test_app_merge_in_returns_new_ids() {
+        let mut app = App::new();
+        app.current_system = Some("J173213".to_owned());
+
+        let added = app
+            .merge_in(&[
+                ClipboardItem::new("ABC-123", "Combat", ""),
+                ClipboardItem::new("GHI-789", "", ""),
+                ClipboardItem::new("DEF-456", "Wormhole", ""),
+                ClipboardItem::new("JKL-012", "Relic", ""),
+                ClipboardItem::new("GHI-789", "", ""),
+            ])
+            .unwrap();
+
+        assert_eq!(
+            added,
+            vec![
+                SignatureId::new("GHI", "789"),
+                SignatureId::new("JKL", "012")
+            ]
+        );
+        assert!(app
+            .merge_in(&[ClipboardItem::new("JKL-012", "Relic", "")])
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_app_paste_focus() {
+        let mut app = App::new();
+        app.current_system = Some("J173213".to_owned());
+        app.focus_on_paste = true;
+
+        app.paste("GHI-789\tCosmic Signature\t\t\t0.0%\t4.99 AU");
+        assert_eq!(app.data_index, 2);
+
+        // nothing new, so the first unresolved signature
+        app.data_index = 0;
+        app.paste("ABC-123\tCosmic Signature\t\t\t0.0%\t4.99 AU");
+        assert_eq!(app.data_index, 2);
+
+        app.focus_on_paste = false;
+        app.data_index = 0;
+        app.paste("JKL-012\tCosmic Signature\t\t\t0.0%\t4.99 AU");
+        assert_eq!(app.data_index, 0);
+    }
+
+    #[test]
+    fn test_app_next_wormhole() {
+        let mut app = App::new();
+        app.system_data
+            .get_mut("J173213")
+            .unwrap()
+            .push(Signature::new("GHI", "789", SignatureType::Relic(None)));
+
+        assert_eq!(app.next_wormhole(0), Some(1));
+        assert_eq!(app.next_wormhole(1), Some(1));
+        assert_eq!(app.next_wormhole(2), Some(1));
+
+        app.system_data.get_mut("J173213").unwrap().remove(1);
+        assert_eq!(app.next_wormhole(0), None);
+
+        app.current_system = None;
+        assert_eq!(app.next_wormhole(0), None);
+    }
+
+    #[test]
+    fn test_app_connection_note_shared() {
+        let mut app = App::new();
+        let here = SignatureId::new("ABC", "123");
+        let there = SignatureId::new("XYZ", "999");
+        app.system_data.insert(
+            "J173213".to_owned(),
+            vec![Signature::new(
+                "ABC",
+                "123",
+                SignatureType::Wormhole(SignatureWormhole {
+                    destination: Some("Jita".to_owned()),
+                    ..Default::default()
+                }),
+            )],
+        );
+        app.system_data.insert(
+            "Jita".to_owned(),
+            vec![Signature::new(
+                "XYZ",
+                "999",
+                SignatureType::Wormhole(SignatureWormhole {
+                    destination: Some("J173213".to_owned()),
+                    ..Default::default()
+                }),
+            )],
+        );
+
+        app.set_connection_note("J173213", &here, "rolling at 23:00");
+
+        let other = app.connection("Jita", &there).unwrap();
+        assert_eq!(other.note.as_deref(), Some("rolling at 23:00"));
+        assert_eq!(app.connections.len(), 1);
+
+        app.set_connection_note("Jita", &there, "");
+        assert!(app.connection("J173213", &here).unwrap().note.is_none());
+
+        app.remove_signature("Jita", 0);
+        assert_eq!(app.connection("J173213", &here).unwrap().ends.len(), 1);
+    }
+
+    #[test]
+    fn test_app_prune_older_than() {
+        let mut app = App::new();
+        let mut old = Signature::new("GHI", "789", SignatureType::Unknown);
+        old.created = Utc::now() - Duration::hours(72);
+        app.system_data.get_mut("J173213").unwrap().push(old);
+        app.toggle_favorite("J173213", &SignatureId::new("GHI", "789"));
+        app.pinned
+            .insert("J173213".to_owned(), vec![SignatureId::new("GHI", "789")]);
+
+        let pruned = app.prune_older_than(Duration::hours(48));
+
+        assert_eq!(pruned, 1);
+        let sigs = app.system_data.get("J173213").unwrap();
+        assert_eq!(sigs.len(), 2);
+        assert!(sigs.iter().all(|sig| sig.identifier.id != "GHI"));
+        assert!(app.favorites.is_empty());
+        assert!(app.pinned.is_empty());
+
+        // removing a system drops everything kept about it
+        app.set_system_note("J173213", "statics rolled");
+        app.data_index = 0;
+        app.toggle_pin_selected();
+        assert!(!app.pinned.is_empty());
+        app.remove_system("J173213");
+        assert!(app.pinned.is_empty());
+        assert!(app.system_note("J173213").is_none());
+    }
+
+    #[test]
+    fn test_app_log_selected_mass() {
+        let mut app = App::new();
+        app.data_index = 0;
+        assert_eq!(app.log_selected_mass(Some(100)), None);
+
+        app.data_index = 1;
+        assert_eq!(app.log_selected_mass(Some(100)), Some(100));
+        assert_eq!(app.log_selected_mass(Some(150)), Some(250));
+        match &app.selected_signature().unwrap().signature_type {
+            SignatureType::Wormhole(wh) => assert_eq!(wh.mass_used, 250),
+            _ => panic!("Should be a wormhole sig"),
+        }
+        assert_eq!(app.log_selected_mass(None), Some(0));
+    }
+
+    #[test]
+    fn test_app_resolve_selected_origin() {
+        let mut app = App::new();
+        app.data_index = 0;
+        assert!(!app.resolve_selected_origin("C247"));
+
+        app.data_index = 1;
+        assert!(app.resolve_selected_origin("C247"));
+        match &app.selected_signature().unwrap().signature_type {
+            SignatureType::Wormhole(wh) => {
+                assert_eq!(wh.origin_type, Some("C247".to_owned()));
+            }
+            _ => panic!("Should be a wormhole sig"),
+        }
+    }
+
+    #[test]
+    fn test_app_paste_ignored_in_modal() {
+        let mut app = App::new();
+        app.view = ViewMode::Favorites(0);
+
+        let result =
+            app.paste("GHI-789\tCosmic Signature\tWormhole\tUnstable Wormhole\t100.0%\t4.99 AU");
+
+        assert!(result.is_none());
+        assert_eq!(app.system_data.get("J173213").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_app_paste_normal() {
+        let mut app = App::new();
+
+        let result =
+            app.paste("GHI-789\tCosmic Signature\tWormhole\tUnstable Wormhole\t100.0%\t4.99 AU");
+
+        assert_eq!(result.unwrap().len(), 1);
+        assert_eq!(app.system_data.get("J173213").unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_set_all_wormholes_eol() {
+        let mut app = App::new();
+        app.system_data
+            .get_mut("J173213")
+            .unwrap()
+            .push(Signature::new(
+                "GHI",
+                "789",
+                SignatureType::Wormhole(SignatureWormhole {
+                    life: WormholeLife::Unknown,
+                    ..Default::default()
+                }),
+            ));
+        let before = app.system_data["J173213"].clone();
+
+        assert_eq!(app.set_all_wormholes_eol("J173213"), 2);
+        let sigs = &app.system_data["J173213"];
+        assert_eq!(sigs[0], before[0]);
+        for sig in &sigs[1..] {
+            match &sig.signature_type {
+                SignatureType::Wormhole(wh) => assert_eq!(wh.life, WormholeLife::EndOfLife),
+                other => panic!("Expected a wormhole, got {other:?}"),
+            }
+        }
+        assert!(app.can_restore_lives("J173213"));
+
+        app.restore_wormhole_lives("J173213");
+        assert_eq!(app.system_data["J173213"], before);
+        assert!(!app.can_restore_lives("J173213"));
+    }
+
+    #[test]
+    fn test_paste_modes() {
+        const PARTIAL: &str = "DEF-456\tCosmic Signature\tWormhole\tUnstable Wormhole\t100.0%\t2.5 AU\nXYZ-999\tCosmic Signature\t\t\t10.0%\t5 AU";
+        let mut app = App::new();
+        app.add_static_placeholder("J173213", "B274");
+        assert_eq!(
+            app.missing_from_paste(PARTIAL),
+            vec![SignatureId::new("ABC", "123")]
+        );
+
+        app.paste_as(PARTIAL, PasteMode::Partial).unwrap();
+        let ids: Vec<_> = app.system_data["J173213"]
+            .iter()
+            .map(|sig| sig.identifier.to_string())
+            .collect();
+        assert!(ids.contains(&"ABC-123".to_owned()));
+        assert!(ids.contains(&"XYZ-999".to_owned()));
+
+        app.paste(PARTIAL).unwrap();
+        assert!(app.system_data["J173213"]
+            .iter()
+            .any(|sig| sig.identifier == SignatureId::new("ABC", "123")));
+
+        app.paste_as(PARTIAL, PasteMode::Full).unwrap();
+        let sigs = &app.system_data["J173213"];
+        assert!(!sigs
+            .iter()
+            .any(|sig| sig.identifier == SignatureId::new("ABC", "123")));
+        assert!(sigs.iter().any(|sig| sig.is_placeholder()));
+        assert!(app.missing_from_paste(PARTIAL).is_empty());
+    }
+
+    #[test]
+    fn test_pinned_signatures_first() {
+        let mut app = App::new();
+        app.system_data
+            .get_mut("J173213")
+            .unwrap()
+            .push(Signature::new("GHI", "789", SignatureType::Gas(None)));
+        let order = |app: &App| {
+            app.system_signatures()
+                .iter()
+                .map(|sig| sig.identifier.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(order(&app), vec!["ABC-123", "DEF-456", "GHI-789"]);
+
+        app.data_index = 2;
+        app.toggle_pin_selected();
+        assert_eq!(order(&app), vec!["GHI-789", "ABC-123", "DEF-456"]);
+        assert_eq!(app.data_index, 0);
+        assert!(app.is_pinned("J173213", &SignatureId::new("GHI", "789")));
+
+        app.data_index = 2;
+        app.toggle_pin_selected();
+        assert_eq!(order(&app), vec!["GHI-789", "DEF-456", "ABC-123"]);
+        assert_eq!(app.data_index, 1);
+
+        app.data_index = 0;
+        app.toggle_pin_selected();
+        assert_eq!(order(&app), vec!["DEF-456", "ABC-123", "GHI-789"]);
+        assert_eq!(app.data_index, 2);
+    }
+
+    #[test]
+    fn test_quick_add_wormhole_reconciled_by_paste() {
+        let mut app = App::new();
+        app.switch_system("J105443");
+
+        let first = app.quick_add_wormhole().unwrap();
+        assert_eq!(first, SignatureId::new("???", "001"));
+        assert!(app.set_selected_wormhole_type("n110"));
+        let second = app.quick_add_wormhole().unwrap();
+        assert_eq!(second, SignatureId::new("???", "002"));
+        assert_eq!(app.selected_signature().unwrap().identifier, second);
+        assert!(!app.set_selected_wormhole_type("nope"));
+
+        app.paste("UWG-400\tCosmic Signature\tWormhole\tUnstable Wormhole\t100.0%\t33.21 AU")
+            .unwrap();
+        let ViewMode::Confirm {
+            action: ConfirmAction::MatchPlaceholders(matches),
+            message,
+        } = app.view.clone()
+        else {
+            panic!("Placeholder match should be offered");
+        };
+        assert_eq!(
+            matches,
+            vec![PlaceholderMatch {
+                system: "J105443".to_owned(),
+                placeholder: first.clone(),
+                wh_type: "N110".to_owned(),
+                scanned: SignatureId::new("UWG", "400"),
+            }]
+        );
+        assert!(message.contains("J105443 ???-001 (N110) is UWG-400"));
+        app.view = ViewMode::Normal;
+        app.perform(ConfirmAction::MatchPlaceholders(matches));
+
+        let sigs = &app.system_data["J105443"];
+        assert!(!sigs.iter().any(|sig| sig.identifier == first));
+        let scanned = sigs
+            .iter()
+            .find(|sig| sig.identifier == SignatureId::new("UWG", "400"))
+            .unwrap();
+        match &scanned.signature_type {
+            SignatureType::Wormhole(wh) => {
+                assert_eq!(wh.wh_type.as_deref(), Some("N110"));
+                assert_eq!(wh.connection_kind, ConnectionKind::Wandering);
             }
+            other => panic!("Expected a wormhole, got {other:?}"),
+        }
+        // untyped placeholders wait for their type
+        assert!(sigs.iter().any(|sig| sig.identifier == second));
+    }
+
+    #[test]
+    fn test_fill_selected_static() {
+        let mut app = App::new();
+        app.switch_system("J173213");
+        let id = app.quick_add_wormhole().unwrap();
+
+        assert_eq!(app.fill_selected_static("B274"), None);
+        assert_eq!(app.fill_selected_static("C3"), Some(StaticMatch::NoMatch));
+        assert_eq!(
+            app.fill_selected_static("hs"),
+            Some(StaticMatch::Unique("B274".to_owned()))
+        );
+        let sig = app.selected_signature().unwrap();
+        assert_eq!(sig.identifier, id);
+        match &sig.signature_type {
+            SignatureType::Wormhole(wh) => assert_eq!(wh.wh_type.as_deref(), Some("B274")),
+            other => panic!("Expected a wormhole, got {other:?}"),
+        }
+
+        // k-space statics aren't offered
+        app.switch_system("Jita");
+        app.quick_add_wormhole().unwrap();
+        assert_eq!(app.fill_selected_static("HS"), Some(StaticMatch::NoMatch));
+    }
+
+    #[test]
+    fn test_cycle_selected_type() {
+        let mut app = App::new();
+        app.system_data
+            .get_mut("J173213")
+            .unwrap()
+            .push(Signature::new("GHI", "789", SignatureType::Unknown));
+        app.data_index = 2;
+
+        let kinds: Vec<_> = (0..7).map(|_| app.cycle_selected_type().unwrap()).collect();
+        assert_eq!(
+            kinds,
+            vec!["Combat", "Ore", "Gas", "Data", "Relic", "WH", "Unknown"]
+        );
+        for _ in 0..3 {
+            app.cycle_selected_type();
         }
+        assert_eq!(
+            app.selected_signature().unwrap().identifier.to_string(),
+            "GHI-789"
+        );
+
+        // a paste that still can't tell what it is keeps the manual type
+        app.paste("GHI-789\tCosmic Signature\t\t\t10.0%\t5 AU")
+            .unwrap();
+        assert_eq!(
+            app.selected_signature().unwrap().signature_type,
+            SignatureType::Gas(None)
+        );
+
+        app.data_index = 0;
+        assert_eq!(app.cycle_selected_type(), None);
     }
 }
@@ -0,0 +1,73 @@
+use crate::config::TimestampFormat;
+use chrono::{DateTime, Local, TimeZone, Utc};
+
+/// Format a timestamp for display in the user's preferred format.
+pub fn format_timestamp(ts: DateTime<Utc>, now: DateTime<Utc>, format: TimestampFormat) -> String {
+    format_timestamp_in(ts, now, format, &Local)
+}
+
+fn format_timestamp_in<Tz: TimeZone>(
+    ts: DateTime<Utc>,
+    now: DateTime<Utc>,
+    format: TimestampFormat,
+    tz: &Tz,
+) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    match format {
+        TimestampFormat::Relative => {
+            let minutes = (now - ts).num_minutes();
+            if minutes < 1 {
+                "just now".to_owned()
+            } else if minutes < 60 {
+                format!("{minutes}m ago")
+            } else if minutes < 60 * 24 {
+                format!("{}h {}m ago", minutes / 60, minutes % 60)
+            } else {
+                format!("{}d {}h ago", minutes / (60 * 24), (minutes / 60) % 24)
+            }
+        }
+        TimestampFormat::Absolute => ts.with_timezone(tz).format("%Y-%m-%d %H:%M").to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::format_timestamp_in;
+    use crate::config::TimestampFormat;
+    use chrono::{Duration, FixedOffset, TimeZone, Utc};
+
+    #[test]
+    fn test_format_timestamp_relative() {
+        let now = Utc.with_ymd_and_hms(2024, 6, 1, 12, 0, 0).unwrap();
+        let cases = [
+            (Duration::seconds(30), "just now"),
+            (Duration::minutes(5), "5m ago"),
+            (Duration::minutes(125), "2h 5m ago"),
+            (Duration::hours(50), "2d 2h ago"),
+        ];
+        for (age, expected) in cases {
+            assert_eq!(
+                format_timestamp_in(now - age, now, TimestampFormat::Relative, &Utc),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_format_timestamp_absolute() {
+        let now = Utc.with_ymd_and_hms(2024, 6, 1, 12, 0, 0).unwrap();
+        let ts = now - Duration::minutes(5);
+
+        assert_eq!(
+            format_timestamp_in(ts, now, TimestampFormat::Absolute, &Utc),
+            "2024-06-01 11:55"
+        );
+        let tz = FixedOffset::east_opt(2 * 3600).unwrap();
+        assert_eq!(
+            format_timestamp_in(ts, now, TimestampFormat::Absolute, &tz),
+            "2024-06-01 13:55"
+        );
+    }
+}
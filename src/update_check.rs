@@ -0,0 +1,145 @@
+use crate::{
+    changelog::{is_upgrade, VERSION},
+    data_update::DATA_DIR,
+    state::write_atomic,
+};
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+
+/// Latest release of the app on GitHub.
+const RELEASES_URL: &str = "https://api.github.com/repos/celeo/evemapping/releases/latest";
+/// File in the data directory the last check is kept in.
+const CACHE_FILE: &str = "update_check.json";
+/// Seconds to wait for GitHub before giving up until next time.
+const CHECK_TIMEOUT: u64 = 10;
+
+/// A release newer than this build.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Release {
+    pub version: String,
+    pub url: String,
+}
+
+/// The latest release as of the last check.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct LastCheck {
+    checked_at: DateTime<Utc>,
+    tag_name: String,
+    html_url: String,
+}
+
+#[derive(Deserialize)]
+struct GithubRelease {
+    tag_name: String,
+    html_url: String,
+}
+
+impl LastCheck {
+    /// The release, if it's newer than `current`.
+    fn newer_than(&self, current: &str) -> Option<Release> {
+        let version = self.tag_name.trim_start_matches('v');
+        is_upgrade(Some(current), version).then(|| Release {
+            version: version.to_owned(),
+            url: self.html_url.clone(),
+        })
+    }
+}
+
+/// The cached check, if there is one from the last day.
+fn read_cache(path: &Path, now: DateTime<Utc>) -> Option<LastCheck> {
+    let check: LastCheck = serde_json::from_str(&fs::read_to_string(path).ok()?).ok()?;
+    (now - check.checked_at < Duration::days(1)).then_some(check)
+}
+
+async fn fetch() -> Result<LastCheck> {
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(CHECK_TIMEOUT))
+        .user_agent(format!("evemapping/{VERSION}"))
+        .build()?;
+    let response = client.get(RELEASES_URL).send().await?.error_for_status()?;
+    let release: GithubRelease = serde_json::from_str(&response.text().await?)?;
+    Ok(LastCheck {
+        checked_at: Utc::now(),
+        tag_name: release.tag_name,
+        html_url: release.html_url,
+    })
+}
+
+/// The latest release, from the cache if it was checked in the last day.
+async fn latest() -> Result<LastCheck> {
+    let path = Path::new(DATA_DIR).join(CACHE_FILE);
+    if let Some(check) = read_cache(&path, Utc::now()) {
+        debug!("Using the update check from {}", check.checked_at);
+        return Ok(check);
+    }
+    let check = fetch().await?;
+    fs::create_dir_all(DATA_DIR)?;
+    write_atomic(&path, serde_json::to_string(&check)?.as_bytes())?;
+    Ok(check)
+}
+
+/// Check for a newer release on a background task.
+///
+/// The receiver gets the release only if there's a newer one. Failures are
+/// logged and otherwise ignored.
+pub fn spawn() -> UnboundedReceiver<Release> {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        match latest().await {
+            Ok(check) => match check.newer_than(VERSION) {
+                Some(release) => {
+                    info!("Version {} is available: {}", release.version, release.url);
+                    let _ = sender.send(release);
+                }
+                None => debug!("No newer release than {VERSION}"),
+            },
+            Err(e) => warn!("Could not check for updates: {e}"),
+        }
+    });
+    receiver
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_cache, LastCheck, Release};
+    use chrono::{Duration, Utc};
+
+    fn check(tag_name: &str) -> LastCheck {
+        LastCheck {
+            checked_at: Utc::now(),
+            tag_name: tag_name.to_owned(),
+            html_url: format!("https://github.com/celeo/evemapping/releases/tag/{tag_name}"),
+        }
+    }
+
+    #[test]
+    fn test_newer_than() {
+        assert_eq!(
+            check("v0.2.0").newer_than("0.1.5"),
+            Some(Release {
+                version: "0.2.0".to_owned(),
+                url: "https://github.com/celeo/evemapping/releases/tag/v0.2.0".to_owned(),
+            })
+        );
+        assert!(check("0.1.5").newer_than("0.1.5").is_none());
+        assert!(check("v0.1.0").newer_than("0.1.5").is_none());
+    }
+
+    #[test]
+    fn test_read_cache() {
+        let dir = std::env::temp_dir().join(format!("evemapping-update-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("update_check.json");
+        let cached = check("v0.2.0");
+        std::fs::write(&path, serde_json::to_string(&cached).unwrap()).unwrap();
+
+        assert_eq!(read_cache(&path, Utc::now()), Some(cached));
+        assert!(read_cache(&path, Utc::now() + Duration::days(2)).is_none());
+        assert!(read_cache(&dir.join("missing.json"), Utc::now()).is_none());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
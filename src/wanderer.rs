@@ -0,0 +1,168 @@
+use crate::eve_data::{
+    ClipboardItem, Provenance, Signature, SignatureType, WormholeLife, WormholeMass,
+};
+use anyhow::Result;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Deserialize)]
+struct WandererSystem {
+    solar_system_id: u64,
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct WandererConnection {
+    solar_system_source: u64,
+    solar_system_target: u64,
+    #[serde(default)]
+    mass_status: u8,
+    #[serde(default)]
+    time_status: u8,
+}
+
+#[derive(Deserialize)]
+struct WandererSignature {
+    solar_system_id: u64,
+    eve_id: String,
+    #[serde(default)]
+    group: String,
+    #[serde(default, rename = "type")]
+    wh_type: String,
+    #[serde(default)]
+    name: String,
+    linked_system_id: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct WandererExport {
+    systems: Vec<WandererSystem>,
+    #[serde(default)]
+    connections: Vec<WandererConnection>,
+    #[serde(default)]
+    signatures: Vec<WandererSignature>,
+}
+
+/// Whether the text looks like a Wanderer export rather than a scanner paste.
+pub fn is_wanderer_export(text: &str) -> bool {
+    let text = text.trim_start();
+    text.starts_with('{') && text.contains("\"solar_system_id\"")
+}
+
+/// Parse a Wanderer JSON export into signatures keyed by system name.
+///
+/// Wanderer stores life and mass on the connection, so they're copied
+/// onto the wormhole signatures at both ends of it.
+pub fn parse_wanderer_export(text: &str) -> Result<HashMap<String, Vec<Signature>>> {
+    let export: WandererExport = serde_json::from_str(text)?;
+    let names: HashMap<u64, &str> = export
+        .systems
+        .iter()
+        .map(|s| (s.solar_system_id, s.name.as_str()))
+        .collect();
+    let mut data: HashMap<String, Vec<Signature>> = HashMap::new();
+
+    for sig in &export.signatures {
+        let Some(system) = names.get(&sig.solar_system_id) else {
+            continue;
+        };
+        if !sig.eve_id.contains('-') {
+            continue;
+        }
+        let item = ClipboardItem::new(&sig.eve_id, sig.group.replace(" Site", ""), &sig.name);
+        let (identifier, mut signature_type) = (&item).into();
+
+        if let SignatureType::Wormhole(wh) = &mut signature_type {
+            if !sig.wh_type.is_empty() {
+                wh.wh_type = Some(sig.wh_type.clone());
+            }
+            if let Some(linked) = sig.linked_system_id {
+                wh.destination = names.get(&linked).map(|n| n.to_string());
+                let connection = export.connections.iter().find(|c| {
+                    (c.solar_system_source == sig.solar_system_id
+                        && c.solar_system_target == linked)
+                        || (c.solar_system_source == linked
+                            && c.solar_system_target == sig.solar_system_id)
+                });
+                if let Some(connection) = connection {
+                    if connection.time_status == 1 {
+                        wh.life = WormholeLife::EndOfLife;
+                    }
+                    wh.mass = match connection.mass_status {
+                        1 => WormholeMass::Destab,
+                        2 => WormholeMass::Critical,
+                        _ => WormholeMass::Stable,
+                    };
+                }
+            }
+        }
+
+        data.entry(system.to_string()).or_default().push(Signature {
+            provenance: Some(Provenance::Imported),
+            ..Signature::new(&identifier.id, &identifier.number, signature_type)
+        });
+    }
+
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_wanderer_export, parse_wanderer_export};
+    use crate::eve_data::{Provenance, SignatureType, WormholeLife, WormholeMass};
+
+    const FIXTURE: &str = include_str!("../tests/fixtures/wanderer_export.json");
+
+    #[test]
+    fn test_is_wanderer_export() {
+        assert!(is_wanderer_export(FIXTURE));
+        assert!(!is_wanderer_export(
+            "ABC-123\tCosmic Signature\tWormhole\tUnstable Wormhole\t100.0%\t4.99 AU"
+        ));
+    }
+
+    #[test]
+    fn test_parse_wanderer_export() {
+        let data = parse_wanderer_export(FIXTURE).unwrap();
+
+        assert_eq!(data.len(), 2);
+        let home = data.get("J173213").unwrap();
+        assert_eq!(home.len(), 4);
+        assert_eq!(home[2].identifier.to_string(), "GHI-789");
+        assert_eq!(
+            home[2].signature_type,
+            SignatureType::Relic(Some("Forgotten Perimeter Coronation Platform".to_owned()))
+        );
+        assert_eq!(home[3].signature_type, SignatureType::Unknown);
+        assert_eq!(home[3].provenance, Some(Provenance::Imported));
+
+        let SignatureType::Wormhole(wh) = &home[1].signature_type else {
+            panic!("Should be a wormhole sig");
+        };
+        assert_eq!(wh.wh_type, Some("B274".to_owned()));
+        assert_eq!(wh.destination, Some("Jita".to_owned()));
+        assert_eq!(wh.life, WormholeLife::Stable);
+        assert_eq!(wh.mass, WormholeMass::Stable);
+    }
+
+    #[test]
+    fn test_parse_wanderer_export_connection_both_ends() {
+        let data = parse_wanderer_export(FIXTURE).unwrap();
+
+        let near = &data.get("J173213").unwrap()[0];
+        let far = &data.get("Thera").unwrap()[0];
+        for (sig, destination) in [(near, "Thera"), (far, "J173213")] {
+            let SignatureType::Wormhole(wh) = &sig.signature_type else {
+                panic!("Should be a wormhole sig");
+            };
+            assert_eq!(wh.destination, Some(destination.to_owned()));
+            assert_eq!(wh.life, WormholeLife::EndOfLife);
+            assert_eq!(wh.mass, WormholeMass::Destab);
+        }
+    }
+
+    #[test]
+    fn test_parse_wanderer_export_invalid() {
+        assert!(parse_wanderer_export("{}").is_err());
+    }
+}
@@ -0,0 +1,270 @@
+use crate::eve_data::{find_system, parse_paste, ClipboardItem};
+use anyhow::{anyhow, Result};
+use log::{debug, error, info, warn};
+use std::{
+    ffi::OsStr,
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+/// Subfolder of the watched directory that merged files are moved to.
+pub const ARCHIVE_DIR: &str = "archive";
+/// Subfolder of the watched directory that files which couldn't be used are moved to.
+pub const REJECTED_DIR: &str = "rejected";
+/// Seconds between checks of the watched directory.
+const WATCH_POLL_RATE: u64 = 2;
+/// Files changed more recently than this may still be being written.
+const SETTLE_TIME: Duration = Duration::from_secs(1);
+
+/// What came of a scan file found in the watched directory.
+#[derive(Debug, PartialEq)]
+pub enum ScanFile {
+    /// Parsed and archived.
+    /// Fields: archived path, system named by the file if any, signatures
+    Parsed(PathBuf, Option<String>, Vec<ClipboardItem>),
+    /// Moved to the rejected folder.
+    /// Fields: file name, why
+    Rejected(String, String),
+}
+
+/// Path in the directory for the file name that doesn't exist yet.
+///
+/// A taken name gets a counter, e.g. "J173213-1.txt", so earlier files aren't overwritten.
+fn unused_path(dir: &Path, name: &OsStr) -> PathBuf {
+    let path = dir.join(name);
+    if !path.exists() {
+        return path;
+    }
+    let name = Path::new(name);
+    let stem = name.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = name
+        .extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy()))
+        .unwrap_or_default();
+    (1..)
+        .map(|n| dir.join(format!("{stem}-{n}{extension}")))
+        .find(|path| !path.exists())
+        .unwrap_or(path)
+}
+
+/// Move the file into the subfolder of its directory, returning the new path.
+pub fn move_to(file: &Path, subfolder: &str) -> Result<PathBuf> {
+    let dir = file
+        .parent()
+        .ok_or_else(|| anyhow!("{} has no directory", file.display()))?;
+    let name = file
+        .file_name()
+        .ok_or_else(|| anyhow!("{} has no file name", file.display()))?;
+    let target_dir = if dir.ends_with(ARCHIVE_DIR) || dir.ends_with(REJECTED_DIR) {
+        dir.parent().unwrap_or(dir).join(subfolder)
+    } else {
+        dir.join(subfolder)
+    };
+    fs::create_dir_all(&target_dir)?;
+    let target = unused_path(&target_dir, name);
+    fs::rename(file, &target)?;
+    Ok(target)
+}
+
+/// Read and parse a scan file, moving it to the archive or rejected folder.
+pub fn process_file(file: &Path) -> Result<ScanFile> {
+    let name = file
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let text = match fs::read_to_string(file) {
+        Ok(text) => text,
+        Err(e) => {
+            move_to(file, REJECTED_DIR)?;
+            return Ok(ScanFile::Rejected(name, format!("could not read it: {e}")));
+        }
+    };
+    let items = parse_paste(&text);
+    if items.is_empty() {
+        move_to(file, REJECTED_DIR)?;
+        return Ok(ScanFile::Rejected(name, "no signatures found".to_owned()));
+    }
+    let system = file
+        .file_stem()
+        .and_then(|stem| find_system(&stem.to_string_lossy()))
+        .map(str::to_owned);
+    let archived = move_to(file, ARCHIVE_DIR)?;
+    Ok(ScanFile::Parsed(archived, system, items))
+}
+
+/// `.txt` files in the directory that have finished being written, oldest name first.
+fn ready_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let now = SystemTime::now();
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let metadata = entry.metadata()?;
+        let settled = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| now.duration_since(modified).ok())
+            .is_none_or(|age| age >= SETTLE_TIME);
+        if metadata.is_file() && path.extension().is_some_and(|ext| ext == "txt") && settled {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Process every ready scan file in the directory.
+pub fn scan_dir(dir: &Path) -> Vec<ScanFile> {
+    let files = match ready_files(dir) {
+        Ok(files) => files,
+        Err(e) => {
+            warn!("Could not read scan directory {}: {e}", dir.display());
+            return Vec::new();
+        }
+    };
+    files
+        .iter()
+        .filter_map(|file| match process_file(file) {
+            Ok(scan) => Some(scan),
+            Err(e) => {
+                error!("Could not process scan file {}: {e}", file.display());
+                None
+            }
+        })
+        .collect()
+}
+
+/// Watch the directory for scan files on a background task.
+pub fn spawn(dir: PathBuf) -> UnboundedReceiver<ScanFile> {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    info!("Watching {} for scan files", dir.display());
+    tokio::spawn(watch(dir, sender));
+    receiver
+}
+
+async fn watch(dir: PathBuf, sender: UnboundedSender<ScanFile>) {
+    let mut interval = tokio::time::interval(Duration::from_secs(WATCH_POLL_RATE));
+    loop {
+        interval.tick().await;
+        let scans = match tokio::task::spawn_blocking({
+            let dir = dir.clone();
+            move || scan_dir(&dir)
+        })
+        .await
+        {
+            Ok(scans) => scans,
+            Err(e) => {
+                error!("Scan directory watcher failed: {e}");
+                continue;
+            }
+        };
+        for scan in scans {
+            debug!("Scan file: {scan:?}");
+            if sender.send(scan).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{move_to, process_file, ScanFile, ARCHIVE_DIR, REJECTED_DIR};
+    use std::{fs, path::PathBuf};
+
+    const SCAN: &str = "UWG-400\tCosmic Signature\tWormhole\tUnstable Wormhole\t100.0%\t33.21 AU\n";
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("evemapping-watch-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_process_file_named_system() {
+        let dir = temp_dir("named");
+        let file = dir.join("J173213.txt");
+        fs::write(&file, SCAN).unwrap();
+
+        match process_file(&file).unwrap() {
+            ScanFile::Parsed(archived, system, items) => {
+                assert_eq!(archived, dir.join(ARCHIVE_DIR).join("J173213.txt"));
+                assert_eq!(system.as_deref(), Some("J173213"));
+                assert_eq!(items.len(), 1);
+            }
+            other => panic!("Expected a parsed file, got {other:?}"),
+        }
+        assert!(!file.exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_process_file_rejected() {
+        let dir = temp_dir("rejected");
+        let file = dir.join("scan.txt");
+        fs::write(&file, "not a scan").unwrap();
+
+        assert_eq!(
+            process_file(&file).unwrap(),
+            ScanFile::Rejected("scan.txt".to_owned(), "no signatures found".to_owned())
+        );
+        assert!(dir.join(REJECTED_DIR).join("scan.txt").exists());
+
+        let other = dir.join("other.txt");
+        fs::write(&other, SCAN).unwrap();
+        match process_file(&other).unwrap() {
+            ScanFile::Parsed(archived, system, _) => {
+                assert_eq!(system, None);
+                let rejected = move_to(&archived, REJECTED_DIR).unwrap();
+                assert_eq!(rejected, dir.join(REJECTED_DIR).join("other.txt"));
+            }
+            other => panic!("Expected a parsed file, got {other:?}"),
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_process_file_unreadable() {
+        let dir = temp_dir("unreadable");
+        let file = dir.join("scan.txt");
+        fs::write(&file, [0xff, 0xfe, 0x00]).unwrap();
+
+        match process_file(&file).unwrap() {
+            ScanFile::Rejected(name, why) => {
+                assert_eq!(name, "scan.txt");
+                assert!(why.starts_with("could not read it"));
+            }
+            other => panic!("Expected a rejected file, got {other:?}"),
+        }
+        assert!(!file.exists());
+        assert!(dir.join(REJECTED_DIR).join("scan.txt").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_archive_keeps_same_name() {
+        let dir = temp_dir("same-name");
+        let file = dir.join("J173213.txt");
+        let mut archived = Vec::new();
+        for _ in 0..3 {
+            fs::write(&file, SCAN).unwrap();
+            archived.push(move_to(&file, ARCHIVE_DIR).unwrap());
+        }
+
+        let archive = dir.join(ARCHIVE_DIR);
+        assert_eq!(
+            archived,
+            vec![
+                archive.join("J173213.txt"),
+                archive.join("J173213-1.txt"),
+                archive.join("J173213-2.txt"),
+            ]
+        );
+        assert!(archived.iter().all(|path| path.exists()));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}